@@ -13,6 +13,7 @@ fn main() {
     let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
     let light = Light {
         color: vector![1.0, 1.0, 1.0, 1.0],
+        intensity: 1.0,
         view: Matrix4::identity(),
         proj: drawer::WGPU_OFFSET_M * Matrix4::new_orthographic(-1.0, 1.0, -1.0, 1.0, 0.0, 100.0),
     };
@@ -47,10 +48,11 @@ fn main() {
             .unwrap();
 
         let lm_builder = LightMappingBuilder::new(&device);
+        let model_m = Matrix4::new_translation(&vector![0.0, 0.0, -5.0])
+            * Matrix4::new_rotation(vector![0.0, PI * 0.25, 0.0]);
         let body_v = vec![Body {
-            model_m: 
-            Matrix4::new_translation(&vector![0.0, 0.0, -5.0])
-                * Matrix4::new_rotation(vector![0.0, PI * 0.25, 0.0]),
+            model_m,
+            prev_model_m: model_m,
             buf: Arc::new(
                 device.create_buffer_init(&BufferInitDescriptor {
                     label: None,
@@ -63,6 +65,7 @@ fn main() {
                     usage: BufferUsages::VERTEX,
                 }),
             ),
+            bound_op: None,
         }];
 
         let (_, depth_tex) = lm_builder.light_mapping(
@@ -70,16 +70,19 @@ fn main() {
             &Vector3::new(0.0, 1.0, 0.0),
         );
 
+        let model_m = Matrix4::new_translation(&vector![0.0, 1.0, -3.0])
+            * Matrix4::new_rotation(vector![0.0, PI * 0.25, 0.0]);
         let look_v = vec![
             ThreeLook::Light(Light {
                 color: vector![1.0, 1.0, 1.0, 1.0],
+                intensity: 1.0,
                 view: light_view_m,
                 proj: drawer::WGPU_OFFSET_M
                     * Matrix4::new_orthographic(-10.0, 10.0, -10.0, 10.0, 0.0, 20.0),
             }),
             ThreeLook::Body(Body {
-                model_m: Matrix4::new_translation(&vector![0.0, 0.0, -3.0])
-                    * Matrix4::new_rotation(vector![0.0, 0.0, 0.0]),
+                model_m: Matrix4::new_translation(&vector![0.0, 0.0, -3.0]),
+                prev_model_m: Matrix4::new_translation(&vector![0.0, 0.0, -3.0]),
                 buf: Arc::new(
                     device.create_buffer_init(&BufferInitDescriptor {
                         label: None,
@@ -90,10 +93,11 @@ fn main() {
                         usage: BufferUsages::VERTEX,
                     }),
                 ),
+                bound_op: None,
             }),
             ThreeLook::Body(Body {
-                model_m: Matrix4::new_translation(&vector![0.0, 1.0, -3.0])
-                    * Matrix4::new_rotation(vector![0.0, PI * 0.25, 0.0]),
+                model_m,
+                prev_model_m: model_m,
                 buf: Arc::new(
                     device.create_buffer_init(&BufferInitDescriptor {
                         label: None,
@@ -104,9 +108,10 @@ fn main() {
                         usage: BufferUsages::VERTEX,
                     }),
                 ),
+                bound_op: None,
             }),
         ];
-        let three_drawer = ThreeDrawer::new(
+        let mut three_drawer = ThreeDrawer::new(
             &device,
             wgpu::TextureFormat::Rgba8Unorm,
             drawer::WGPU_OFFSET_M * Matrix4::new_perspective(1.0, PI * 0.6, 0.1, 500.0),
@@ -117,7 +122,7 @@ fn main() {
             &queue,
             &texture.create_view(&TextureViewDescriptor::default()),
             look_v.iter().collect(),
-            texture.width() as f32 / texture.height() as f32,
+            (texture.width(), texture.height()),
         );
 
         save_texture(
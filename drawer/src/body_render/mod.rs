@@ -1,11 +1,11 @@
-use nalgebra::Matrix4;
+use nalgebra::{Matrix4, Vector4};
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
     BindGroupLayout, BlendState, BufferUsages, Device, Queue, RenderPipeline, Texture,
     TextureFormat, TextureView, TextureViewDescriptor,
 };
 
-use crate::{err, pipeline, structs::Point3Input, Light};
+use crate::{err, pipeline, structs::Point3Input, Ambient, Light};
 
 mod inner {
     use wgpu::{
@@ -23,6 +23,8 @@ mod inner {
         proj_buf: &Buffer,
         light_v_buf: &Buffer,
         light_p_buf: &Buffer,
+        light_color_buf: &Buffer,
+        ambient_buf: &Buffer,
         view_texture: &TextureView,
         light_texture: &TextureView,
         light_depth_tex: &TextureView,
@@ -35,31 +37,37 @@ mod inner {
                     position: [-1.0, 1.0, 0.0, 1.0],
                     color: [1.0, 1.0, 1.0, 1.0],
                     normal: [0.0, 0.0, 1.0, 0.0],
+                    uv: [0.0, 0.0],
                 },
                 Point3Input {
                     position: [-1.0, -1.0, 0.0, 1.0],
                     color: [1.0, 1.0, 1.0, 1.0],
                     normal: [0.0, 0.0, 1.0, 0.0],
+                    uv: [0.0, 1.0],
                 },
                 Point3Input {
                     position: [1.0, -1.0, 0.0, 1.0],
                     color: [1.0, 1.0, 1.0, 1.0],
                     normal: [0.0, 0.0, 1.0, 0.0],
+                    uv: [1.0, 1.0],
                 },
                 Point3Input {
                     position: [-1.0, 1.0, 0.0, 1.0],
                     color: [1.0, 1.0, 1.0, 1.0],
                     normal: [0.0, 0.0, 1.0, 0.0],
+                    uv: [0.0, 0.0],
                 },
                 Point3Input {
                     position: [1.0, -1.0, 0.0, 1.0],
                     color: [1.0, 1.0, 1.0, 1.0],
                     normal: [0.0, 0.0, 1.0, 0.0],
+                    uv: [1.0, 1.0],
                 },
                 Point3Input {
                     position: [1.0, 1.0, 0.0, 1.0],
                     color: [1.0, 1.0, 1.0, 1.0],
                     normal: [0.0, 0.0, 1.0, 0.0],
+                    uv: [1.0, 0.0],
                 },
             ]),
             usage: BufferUsages::VERTEX,
@@ -113,6 +121,16 @@ mod inner {
                         binding: 7,
                         resource: ratio_buf.as_entire_binding(),
                     },
+                    // light_color
+                    wgpu::BindGroupEntry {
+                        binding: 8,
+                        resource: light_color_buf.as_entire_binding(),
+                    },
+                    // ambient
+                    wgpu::BindGroupEntry {
+                        binding: 9,
+                        resource: ambient_buf.as_entire_binding(),
+                    },
                 ],
                 label: None,
             }),
@@ -222,6 +240,28 @@ impl BodyRenderer {
                     },
                     count: None,
                 },
+                // light_color
+                wgpu::BindGroupLayoutEntry {
+                    binding: 8,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // ambient
+                wgpu::BindGroupLayoutEntry {
+                    binding: 9,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
             label: Some("light"),
         });
@@ -270,6 +310,7 @@ impl BodyRenderer {
         light_texture_v: Vec<(&Light, (Texture, Texture))>,
         view_m: &Matrix4<f32>,
         proj_m: &Matrix4<f32>,
+        ambient: &Ambient,
         ratio: f32,
     ) -> err::Result<()> {
         let view_buf = device.create_buffer_init(&BufferInitDescriptor {
@@ -289,14 +330,14 @@ impl BodyRenderer {
             .iter()
             .map(|(light, (color_tex, depth_tex))| {
                 (
-                    (&light.view, &light.proj),
+                    *light,
                     (
                         color_tex.create_view(&TextureViewDescriptor::default()),
                         depth_tex.create_view(&TextureViewDescriptor::default()),
                     ),
                 )
             })
-            .collect::<Vec<((&Matrix4<f32>, &Matrix4<f32>), (TextureView, TextureView))>>();
+            .collect::<Vec<(&Light, (TextureView, TextureView))>>();
         let view_texture_view = view_texture.create_view(&TextureViewDescriptor::default());
 
         {
@@ -317,16 +358,35 @@ impl BodyRenderer {
 
             render_pass.set_pipeline(&self.render_pipeline);
 
-            for ((light_v, light_p), (color_texture_view, depth_tex_view)) in &light_texture_view_v
+            for (i, (light, (color_texture_view, depth_tex_view))) in
+                light_texture_view_v.iter().enumerate()
             {
                 let light_v_buf = device.create_buffer_init(&BufferInitDescriptor {
                     label: None,
-                    contents: bytemuck::cast_slice(light_v.data.as_slice()),
+                    contents: bytemuck::cast_slice(light.view.data.as_slice()),
                     usage: BufferUsages::UNIFORM,
                 });
                 let light_p_buf = device.create_buffer_init(&BufferInitDescriptor {
                     label: None,
-                    contents: bytemuck::cast_slice(light_p.data.as_slice()),
+                    contents: bytemuck::cast_slice(light.proj.data.as_slice()),
+                    usage: BufferUsages::UNIFORM,
+                });
+                // The color is scaled by the intensity here, for the shader to take it as is.
+                let light_color = (light.color.xyz() * light.intensity).push(1.0);
+                let light_color_buf = device.create_buffer_init(&BufferInitDescriptor {
+                    label: None,
+                    contents: bytemuck::cast_slice(light_color.as_slice()),
+                    usage: BufferUsages::UNIFORM,
+                });
+                // The passes of the lights add up, so only the first one takes the ambient.
+                let ambient_color = if i == 0 {
+                    (ambient.color.xyz() * ambient.intensity).push(1.0)
+                } else {
+                    Vector4::zeros()
+                };
+                let ambient_buf = device.create_buffer_init(&BufferInitDescriptor {
+                    label: None,
+                    contents: bytemuck::cast_slice(ambient_color.as_slice()),
                     usage: BufferUsages::UNIFORM,
                 });
 
@@ -338,6 +398,8 @@ impl BodyRenderer {
                     &proj_buf,
                     &light_v_buf,
                     &light_p_buf,
+                    &light_color_buf,
+                    &ambient_buf,
                     &view_texture_view,
                     color_texture_view,
                     depth_tex_view,
@@ -19,6 +19,10 @@ struct Fragment {
 @group(0) @binding(5) var light_depth_tex: texture_depth_2d;
 @group(0) @binding(6) var<uniform> light_p: mat4x4<f32>;
 @group(0) @binding(7) var<uniform> ratio: f32;
+// rgb of the light, scaled by its intensity
+@group(0) @binding(8) var<uniform> light_color: vec4<f32>;
+// rgb of the ambient, scaled by its intensity
+@group(0) @binding(9) var<uniform> ambient: vec4<f32>;
 
 fn f_2_f4(f: f32) -> vec4<f32> {
     let bit_shift = vec4<f32>(1.0, 10.0, 10.0 * 10.0, 10.0 * 10.0 * 10.0);
@@ -88,7 +92,7 @@ fn fs_main(in: Fragment) -> @location(0) vec4<f32> {
     let crd = vec2<i32>(i32(f_crd.x * half_sz + half_sz), i32(-f_crd.y * half_sz + half_sz));
 
     let i_light_in_view = normalize(view * reverse_vec_from_mat(vec4<f32>(0.0, 0.0, -1.0, 0.0), light_v));
-    var lightness = 0.08;
+    var lightness = ambient.rgb;
 
     let pos_vc = textureLoad(view_tex, crd, 0);
 
@@ -116,7 +120,9 @@ fn fs_main(in: Fragment) -> @location(0) vec4<f32> {
     if (abs(cur_depth_in_light_proj - std_depth_in_light_proj) < 0.0035) {
         // let color_in_light = f_2_f4(nml_lc.w);
 
-        lightness += calc_light_income(normal_in_view, income_in_view, i_light_in_view);
+        let income = calc_light_income(normal_in_view, income_in_view, i_light_in_view);
+
+        lightness += light_color.rgb * income;
     }
 
     lightness += calc_normal_income(normal_in_view, income_in_view);
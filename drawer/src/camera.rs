@@ -1,16 +1,22 @@
-use std::f32::consts::FRAC_PI_2;
+use std::{
+    f32::consts::FRAC_PI_2,
+    ops::{Deref, DerefMut},
+};
 
-use nalgebra::{Matrix4, Point3, Vector3};
+use nalgebra::{Matrix4, Point3, UnitQuaternion, Vector3};
 
 use crate::WGPU_OFFSET_M;
 
 pub const SAFE_FRAC_PI_2: f32 = FRAC_PI_2 - 0.0001;
 
-#[derive(Debug)]
+/// Where the camera is and where it looks.
+///
+/// The orientation turns the camera from looking along -Z with +Y up; as euler angles, it is the
+/// yaw around Y, then the pitch up, then the roll around the direction looked at.
+#[derive(Clone, Debug, PartialEq)]
 pub struct CameraState {
     position: Point3<f32>,
-    yaw: f32,
-    pitch: f32,
+    orientation: UnitQuaternion<f32>,
 }
 
 impl CameraState {
@@ -21,20 +27,15 @@ impl CameraState {
     ) -> Self {
         Self {
             position: position.into(),
-            yaw: yaw.into(),
-            pitch: pitch.into(),
+            orientation: orientation_of(yaw.into(), pitch.into(), 0.0),
         }
     }
 
     pub fn calc_matrix(&self) -> Matrix4<f32> {
         Matrix4::look_at_rh(
             &self.position,
-            &Point3::new(
-                self.position.x - self.yaw.sin(),
-                self.position.y + self.pitch.sin(),
-                self.position.z - self.yaw.cos(),
-            ),
-            &Vector3::new(0.0, 1.0, 0.0),
+            &(self.position + self.forward()),
+            &self.up(),
         )
     }
 
@@ -46,20 +47,139 @@ impl CameraState {
         &mut self.position
     }
 
+    pub fn orientation(&self) -> &UnitQuaternion<f32> {
+        &self.orientation
+    }
+
+    pub fn orientation_mut(&mut self) -> &mut UnitQuaternion<f32> {
+        &mut self.orientation
+    }
+
+    /// called => the result = the unit direction looked at
+    pub fn forward(&self) -> Vector3<f32> {
+        self.orientation * -Vector3::z()
+    }
+
+    /// called => the result = the unit direction to the right of the view
+    pub fn right(&self) -> Vector3<f32> {
+        self.orientation * Vector3::x()
+    }
+
+    /// called => the result = the unit direction to the top of the view
+    pub fn up(&self) -> Vector3<f32> {
+        self.orientation * Vector3::y()
+    }
+
+    /// Let the camera turn to look at the target, keeping its roll.
+    ///
+    /// A target at the camera turns it nowhere.
+    pub fn look_at(&mut self, target: &Point3<f32>) {
+        let dir = target - self.position;
+        if dir.norm() <= f32::EPSILON {
+            return;
+        }
+
+        self.orientation = orientation_of(
+            (-dir.x).atan2(-dir.z),
+            (dir.y / dir.norm()).clamp(-1.0, 1.0).asin(),
+            self.roll(),
+        );
+    }
+
+    /// called => the result = the camera the fraction `t` of the way to the other, the
+    /// orientation turning along the shortest arc
+    pub fn interpolate(&self, other: &CameraState, t: f32) -> CameraState {
+        CameraState {
+            position: self.position + (other.position - self.position) * t,
+            orientation: self.orientation.slerp(&other.orientation, t),
+        }
+    }
+
+    /// called => the result = the yaw, the pitch and the roll
+    fn euler_angles(&self) -> [f32; 3] {
+        let forward = self.forward();
+        let yaw = (-forward.x).atan2(-forward.z);
+        let pitch = forward.y.clamp(-1.0, 1.0).asin();
+
+        // The up and the right of no roll.
+        let unrolled = orientation_of(yaw, pitch, 0.0);
+        let up = self.up();
+        let roll = up
+            .dot(&(unrolled * Vector3::x()))
+            .atan2(up.dot(&(unrolled * Vector3::y())));
+
+        [yaw, pitch, roll]
+    }
+
+    fn euler_mut(&mut self, index: usize) -> EulerMut {
+        EulerMut {
+            angle_v: self.euler_angles(),
+            index,
+            camera_state: self,
+        }
+    }
+
     pub fn yaw(&self) -> f32 {
-        self.yaw
+        self.euler_angles()[0]
     }
 
-    pub fn yaw_mut(&mut self) -> &mut f32 {
-        &mut self.yaw
+    pub fn yaw_mut(&mut self) -> EulerMut {
+        self.euler_mut(0)
     }
 
     pub fn pitch(&self) -> f32 {
-        self.pitch
+        self.euler_angles()[1]
+    }
+
+    pub fn pitch_mut(&mut self) -> EulerMut {
+        self.euler_mut(1)
+    }
+
+    /// The bank around the direction looked at, rightward if positive.
+    pub fn roll(&self) -> f32 {
+        self.euler_angles()[2]
     }
 
-    pub fn pitch_mut(&mut self) -> &mut f32 {
-        &mut self.pitch
+    pub fn roll_mut(&mut self) -> EulerMut {
+        self.euler_mut(2)
+    }
+}
+
+/// called => the result = the orientation of the yaw, the pitch and the roll, see [CameraState]
+fn orientation_of(yaw: f32, pitch: f32, roll: f32) -> UnitQuaternion<f32> {
+    UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw)
+        * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), pitch)
+        // Around -Z, where the camera looks.
+        * UnitQuaternion::from_axis_angle(&Vector3::z_axis(), -roll)
+}
+
+/// One euler angle of a [CameraState] to be changed, written back to its orientation when
+/// dropped.
+pub struct EulerMut<'a> {
+    camera_state: &'a mut CameraState,
+    angle_v: [f32; 3],
+    index: usize,
+}
+
+impl Deref for EulerMut<'_> {
+    type Target = f32;
+
+    fn deref(&self) -> &f32 {
+        &self.angle_v[self.index]
+    }
+}
+
+impl DerefMut for EulerMut<'_> {
+    fn deref_mut(&mut self) -> &mut f32 {
+        &mut self.angle_v[self.index]
+    }
+}
+
+impl Drop for EulerMut<'_> {
+    fn drop(&mut self) {
+        let [yaw, pitch, roll] = self.angle_v;
+
+        self.camera_state.orientation = orientation_of(yaw, pitch, roll);
     }
 }
 
@@ -88,3 +208,43 @@ impl Projection {
         WGPU_OFFSET_M * Matrix4::new_perspective(self.aspect, self.fovy, self.znear, self.zfar)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{point, vector};
+
+    use super::CameraState;
+
+    #[test]
+    fn test_euler_angles() {
+        let mut camera_state = CameraState::new(point![0.0, 0.0, 0.0], 0.5, -0.3);
+        *camera_state.roll_mut() = 0.2;
+        *camera_state.yaw_mut() += 0.1;
+
+        assert!((camera_state.yaw() - 0.6).abs() < 1e-5);
+        assert!((camera_state.pitch() + 0.3).abs() < 1e-5);
+        assert!((camera_state.roll() - 0.2).abs() < 1e-5);
+
+        // The forward of the yaw and the pitch, as before the orientation was a quaternion.
+        let (yaw, pitch) = (0.6_f32, -0.3_f32);
+        let forward = vector![
+            -yaw.sin() * pitch.cos(),
+            pitch.sin(),
+            -yaw.cos() * pitch.cos()
+        ];
+        assert!((camera_state.forward() - forward).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_look_at() {
+        let mut camera_state = CameraState::new(point![0.0, 0.0, 0.0], 0.0, 0.0);
+        camera_state.look_at(&point![1.0, 0.0, 0.0]);
+
+        assert!((camera_state.forward() - vector![1.0, 0.0, 0.0]).norm() < 1e-5);
+        assert!((camera_state.right() - vector![0.0, 0.0, 1.0]).norm() < 1e-5);
+
+        let half =
+            CameraState::new(point![0.0, 0.0, 0.0], 0.0, 0.0).interpolate(&camera_state, 0.5);
+        assert!((half.yaw() + std::f32::consts::FRAC_PI_4).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,390 @@
+use std::sync::{Arc, Mutex};
+
+use nalgebra::{Matrix4, Point3};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroupLayout, Buffer, BufferAsyncError, BufferUsages, Device, Extent3d, Queue,
+    RenderPipeline, Texture, TextureDescriptor, TextureFormat, TextureUsages,
+};
+
+use crate::{pipeline, Body};
+
+/// The texels of the finest level of the pyramid, along a side of the view.
+const LEVEL_SIZE: u32 = 64;
+
+/// The most any element of the projection times the view may change since the pyramid was made
+/// for the pyramid to still cull, about a centimeter moved or a hundredth of a radian turned.
+const MAX_VIEW_PROJ_CHANGE: f32 = 0.01;
+
+/// The farthest depth of each block of a view, each level half the size of the one before.
+struct Pyramid {
+    /// From the finest level to the one of a texel, row by row from the top.
+    level_v: Vec<Vec<f32>>,
+    size: usize,
+    /// The projection times the view the depth was rendered by.
+    view_proj_m: Matrix4<f32>,
+}
+
+impl Pyramid {
+    /// called => the result = the pyramid of the depth, `size` by `size` texels
+    fn new(depth_v: Vec<f32>, size: usize, view_proj_m: Matrix4<f32>) -> Self {
+        let mut level_v = vec![depth_v];
+
+        let mut level_size = size;
+        while level_size > 1 {
+            let next_size = level_size.div_ceil(2);
+            let level = level_v.last().unwrap();
+            let depth_at = |x: usize, y: usize| {
+                level[y.min(level_size - 1) * level_size + x.min(level_size - 1)]
+            };
+
+            let next = (0..next_size * next_size)
+                .map(|i| {
+                    let (x, y) = (i % next_size * 2, i / next_size * 2);
+
+                    depth_at(x, y)
+                        .max(depth_at(x + 1, y))
+                        .max(depth_at(x, y + 1))
+                        .max(depth_at(x + 1, y + 1))
+                })
+                .collect();
+            level_v.push(next);
+            level_size = next_size;
+        }
+
+        Self {
+            level_v,
+            size,
+            view_proj_m,
+        }
+    }
+
+    /// called => false only if the box of the model, moved by the model matrix, is behind what
+    /// the pyramid was made of
+    fn is_visible(&self, model_m: &Matrix4<f32>, [min, max]: &[Point3<f32>; 2]) -> bool {
+        let m = self.view_proj_m * model_m;
+
+        let mut ndc_min = Point3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut ndc_max = Point3::new(f32::MIN, f32::MIN, f32::MIN);
+        for i in 0..8 {
+            let corner = Point3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            );
+            let clip = m * corner.to_homogeneous();
+            // A box reaching behind the eye is not told.
+            if clip.w <= f32::EPSILON {
+                return true;
+            }
+
+            let ndc = Point3::from(clip.xyz() / clip.w);
+            ndc_min = ndc_min.inf(&ndc);
+            ndc_max = ndc_max.sup(&ndc);
+        }
+        // Off the view, nothing is known of what is in front.
+        if ndc_min.x < -1.0 || ndc_max.x > 1.0 || ndc_min.y < -1.0 || ndc_max.y > 1.0 {
+            return true;
+        }
+
+        let texel_of =
+            |n: f32| ((n * 0.5 + 0.5) * self.size as f32).clamp(0.0, self.size as f32 - 1.0);
+        let (mut x0, mut x1) = (texel_of(ndc_min.x) as usize, texel_of(ndc_max.x) as usize);
+        let (mut y0, mut y1) = (texel_of(-ndc_max.y) as usize, texel_of(-ndc_min.y) as usize);
+
+        // The level where the box covers no more than two by two texels.
+        let mut level = 0;
+        let mut level_size = self.size;
+        while (x1 - x0 > 1 || y1 - y0 > 1) && level + 1 < self.level_v.len() {
+            (x0, x1, y0, y1) = (x0 / 2, x1 / 2, y0 / 2, y1 / 2);
+            level += 1;
+            level_size = level_size.div_ceil(2);
+        }
+
+        let farthest = (y0..=y1)
+            .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+            .map(|(x, y)| self.level_v[level][y * level_size + x])
+            .fold(0.0, f32::max);
+
+        ndc_min.z <= farthest
+    }
+
+    /// called => false only if the view of the projection times the view is close to the one
+    /// the pyramid was made by, and the box is behind what the pyramid was made of
+    fn is_visible_by(
+        &self,
+        view_proj_m: &Matrix4<f32>,
+        model_m: &Matrix4<f32>,
+        bound: &[Point3<f32>; 2],
+    ) -> bool {
+        // Seen from farther away, what was hidden may show now, and the pyramid is not to tell
+        // till one is made of the new view.
+        (view_proj_m - self.view_proj_m).amax() > MAX_VIEW_PROJ_CHANGE
+            || self.is_visible(model_m, bound)
+    }
+}
+
+/// A read back of the finest level, in flight.
+struct Readback {
+    buffer: Buffer,
+    view_proj_m: Matrix4<f32>,
+    result_op: Arc<Mutex<Option<Result<(), BufferAsyncError>>>>,
+}
+
+/// The occlusion culling, which tells the bodies hidden behind what was seen the frame before by
+/// a pyramid of its depth.
+///
+/// The pyramid is read back without waiting on the GPU, so it may be a few frames old; a body
+/// coming out from behind a wall is drawn once it shows in the pyramid. While the camera moves
+/// or turns beyond `MAX_VIEW_PROJ_CHANGE` from where the pyramid was made, nothing is culled.
+pub struct HiZ {
+    render_pipeline: RenderPipeline,
+    bind_group_layout: BindGroupLayout,
+    /// The finest level, rendered to be read back.
+    level_texture: Texture,
+    readback_op: Option<Readback>,
+    pyramid_op: Option<Pyramid>,
+}
+
+impl HiZ {
+    pub fn new(device: &Device) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                // block
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // depth_tex
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("hi_z"),
+        });
+
+        let render_pipeline = pipeline::RenderPipelineBuilder::new(
+            &device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Hi-Z Render Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            }),
+            &device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Hi-Z Render Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shader/hi_z.wgsl").into()),
+            }),
+            &[],
+            TextureFormat::R32Float,
+        )
+        .set_name(Some("Hi-Z Render Pipeline"))
+        .set_blend(None)
+        .build(device);
+        let level_texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: LEVEL_SIZE,
+                height: LEVEL_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TextureFormat::R32Float,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        Self {
+            render_pipeline,
+            bind_group_layout,
+            level_texture,
+            readback_op: None,
+            pyramid_op: None,
+        }
+    }
+
+    /// Let the pyramid be made of the read back, if it is done.
+    pub fn poll(&mut self, device: &Device) {
+        let _ = device.poll(wgpu::Maintain::Poll);
+
+        let result = match &self.readback_op {
+            Some(readback) => match readback.result_op.lock().unwrap().take() {
+                Some(result) => result,
+                None => return,
+            },
+            None => return,
+        };
+        let readback = self.readback_op.take().unwrap();
+        if let Err(e) = result {
+            log::error!("{e:?}");
+            return;
+        }
+
+        let depth_v = readback
+            .buffer
+            .slice(..)
+            .get_mapped_range()
+            .chunks_exact(4)
+            .map(|bytes| crate::f32_at(bytes, 0))
+            .collect();
+        readback.buffer.unmap();
+
+        self.pyramid_op = Some(Pyramid::new(
+            depth_v,
+            LEVEL_SIZE as usize,
+            readback.view_proj_m,
+        ));
+    }
+
+    /// called => false only if the body is behind what was seen from about the view of the
+    /// projection times the view, a body of no bound never
+    pub fn is_visible(&self, body: &Body, view_proj_m: &Matrix4<f32>) -> bool {
+        match (&self.pyramid_op, &body.bound_op) {
+            (Some(pyramid), Some(bound)) => {
+                pyramid.is_visible_by(view_proj_m, &body.model_m, bound)
+            }
+            _ => true,
+        }
+    }
+
+    /// Let the depth, rendered by the projection times the view, be read back for the pyramid,
+    /// unless the one before is still in flight.
+    pub fn build(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        depth_texture: &Texture,
+        view_proj_m: &Matrix4<f32>,
+    ) {
+        if self.readback_op.is_some() {
+            return;
+        }
+
+        let block_buf = device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(&[
+                depth_texture.width() / LEVEL_SIZE,
+                depth_texture.height() / LEVEL_SIZE,
+                0,
+                0,
+            ]),
+            usage: BufferUsages::UNIFORM,
+        });
+        let level_view = self
+            .level_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Encoder"),
+        });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &level_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_bind_group(
+                0,
+                &device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: block_buf.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(&depth_view),
+                        },
+                    ],
+                    label: None,
+                }),
+                &[],
+            );
+            render_pass.draw(0..3, 0..1);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let buffer = crate::copy_to_buffer(device, queue, &self.level_texture, 4);
+        let result_op = Arc::new(Mutex::new(None));
+        let callback_result_op = result_op.clone();
+        buffer.slice(..).map_async(wgpu::MapMode::Read, move |rs| {
+            *callback_result_op.lock().unwrap() = Some(rs);
+        });
+
+        self.readback_op = Some(Readback {
+            buffer,
+            view_proj_m: *view_proj_m,
+            result_op,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{point, vector, Matrix4};
+
+    use super::Pyramid;
+
+    #[test]
+    fn test_pyramid() {
+        // A wall at 0.5 over the view but for the texel at the top right, seen through.
+        let mut depth_v = vec![0.5; 16];
+        depth_v[3] = 1.0;
+        let pyramid = Pyramid::new(depth_v, 4, Matrix4::identity());
+
+        assert_eq!(pyramid.level_v.len(), 3);
+        assert_eq!(pyramid.level_v[1], vec![0.5, 1.0, 0.5, 0.5]);
+        assert_eq!(pyramid.level_v[2], vec![1.0]);
+
+        let bound = [point![-0.1, -0.9, 0.6], point![0.1, -0.1, 0.7]];
+        // Behind the wall.
+        assert!(!pyramid.is_visible(&Matrix4::identity(), &bound));
+        // In front of the wall.
+        assert!(pyramid.is_visible(&Matrix4::new_translation(&vector![0.0, 0.0, -0.3]), &bound));
+        // Behind the wall, but seen through the hole.
+        assert!(pyramid.is_visible(&Matrix4::new_translation(&vector![0.7, 1.0, 0.0]), &bound));
+    }
+
+    #[test]
+    fn test_stale_view() {
+        // A wall at 0.5 over the whole view.
+        let pyramid = Pyramid::new(vec![0.5; 16], 4, Matrix4::identity());
+        let bound = [point![-0.1, -0.9, 0.6], point![0.1, -0.1, 0.7]];
+
+        // Behind the wall, from the view the pyramid was made by or one close to it.
+        assert!(!pyramid.is_visible_by(&Matrix4::identity(), &Matrix4::identity(), &bound));
+        let close_m = Matrix4::new_translation(&vector![0.005, 0.0, 0.0]);
+        assert!(!pyramid.is_visible_by(&close_m, &Matrix4::identity(), &bound));
+
+        // The camera moved away since, so the wall may no longer be in front.
+        let moved_m = Matrix4::new_translation(&vector![0.5, 0.0, 0.0]);
+        assert!(pyramid.is_visible_by(&moved_m, &Matrix4::identity(), &bound));
+    }
+}
@@ -0,0 +1,24 @@
+// the texels of the depth in a block, along x and y
+@group(0) @binding(0) var<uniform> block: vec4<u32>;
+@group(0) @binding(1) var depth_tex: texture_depth_2d;
+
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+    // One triangle covering the target.
+    return vec4<f32>(f32(i / 2u) * 4.0 - 1.0, f32(i % 2u) * 4.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let origin = vec2<u32>(position.xy) * block.xy;
+
+    // The farthest of the block, for nothing behind it to be seen.
+    var farthest = 0.0;
+    for (var y = 0u; y < block.y; y++) {
+        for (var x = 0u; x < block.x; x++) {
+            farthest = max(farthest, textureLoad(depth_tex, origin + vec2<u32>(x, y), 0));
+        }
+    }
+
+    return vec4<f32>(farthest, 0.0, 0.0, 1.0);
+}
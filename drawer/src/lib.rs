@@ -1,13 +1,17 @@
 use std::{
-    sync::{mpsc::channel, Arc},
+    future::Future,
+    pin::Pin,
+    sync::{mpsc::channel, Arc, Mutex},
+    task::{Context, Poll, Waker},
     time::Duration,
 };
 
+use error_stack::ResultExt;
 use image::Rgba;
-use nalgebra::{point, Matrix4, Vector4};
+use nalgebra::{point, Matrix4, Point3, Vector3, Vector4};
 use wgpu::{
-    BufferDescriptor, BufferUsages, Device, ImageCopyBuffer, ImageDataLayout, Queue, Texture,
-    TextureFormat, TextureView,
+    util::DeviceExt, Buffer, BufferAsyncError, BufferDescriptor, BufferUsages, Device,
+    ImageCopyBuffer, ImageDataLayout, Queue, Texture, TextureFormat, TextureView,
 };
 
 mod pipeline {
@@ -25,6 +29,8 @@ mod pipeline {
         topology: wgpu::PrimitiveTopology,
         depth_stencil_op: Option<DepthStencilState>,
         blend_op: Option<wgpu::BlendState>,
+        /// The formats of the targets after the first, written without blending.
+        extra_format_v: Vec<TextureFormat>,
     }
 
     impl<'a> RenderPipelineBuilder<'a> {
@@ -43,6 +49,7 @@ mod pipeline {
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 depth_stencil_op: None,
                 blend_op: Some(wgpu::BlendState::REPLACE),
+                extra_format_v: Vec::new(),
             }
         }
 
@@ -64,7 +71,27 @@ mod pipeline {
             self
         }
 
+        /// Let the fragment shader write one more target, at the next location.
+        pub fn add_target(mut self, format: TextureFormat) -> Self {
+            self.extra_format_v.push(format);
+
+            self
+        }
+
         pub fn build(self, device: &Device) -> RenderPipeline {
+            let mut target_v = vec![Some(wgpu::ColorTargetState {
+                format: self.format,
+                blend: self.blend_op,
+                write_mask: wgpu::ColorWrites::ALL,
+            })];
+            target_v.extend(self.extra_format_v.iter().map(|format| {
+                Some(wgpu::ColorTargetState {
+                    format: *format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+            }));
+
             device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
                 label: self.name_op,
                 layout: Some(&self.render_pipeline_layout),
@@ -77,11 +104,7 @@ mod pipeline {
                 fragment: Some(wgpu::FragmentState {
                     module: self.shader,
                     entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: self.format,
-                        blend: self.blend_op,
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
+                    targets: &target_v,
                     compilation_options: wgpu::PipelineCompilationOptions::default(),
                 }),
                 primitive: wgpu::PrimitiveState {
@@ -138,12 +161,17 @@ mod pipeline {
     // }
 }
 mod body_render;
+mod font;
+mod hi_z;
 mod view_renderer;
 
 pub mod camera;
 pub mod err;
 pub mod light_mapping;
+pub mod overlay;
+pub mod quad_renderer;
 pub mod structs;
+pub mod taa_renderer;
 
 pub const WGPU_OFFSET_M: Matrix4<f32> = Matrix4::new(
     1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.5, 0.0, 0.0, 0.0, 1.0,
@@ -190,13 +218,162 @@ impl ThreeLook {
 
 pub struct Light {
     pub color: Vector4<f32>,
+    /// The factor of the color lighting the bodies, 1 for as is.
+    pub intensity: f32,
     pub view: Matrix4<f32>,
     pub proj: Matrix4<f32>,
 }
 
+impl Light {
+    /// Let the light shine along the direction, its shadow box fitting the points, like the
+    /// corners of what the camera sees.
+    pub fn fit_directional(&mut self, direction: &Vector3<f32>, point_v: &[Point3<f32>]) {
+        let direction = match direction.try_normalize(f32::EPSILON) {
+            Some(direction) => direction,
+            None => return,
+        };
+        if point_v.is_empty() {
+            return;
+        }
+
+        let center = Point3::from(
+            point_v
+                .iter()
+                .map(|point| point.coords)
+                .sum::<Vector3<f32>>()
+                / point_v.len() as f32,
+        );
+        let up = if direction.y.abs() > 0.99 {
+            Vector3::z()
+        } else {
+            Vector3::y()
+        };
+        let view = Matrix4::look_at_rh(&center, &(center + direction), &up);
+
+        let (min, max) = point_v
+            .iter()
+            .map(|point| view.transform_point(point).coords)
+            .fold(
+                (Vector3::repeat(f32::MAX), Vector3::repeat(f32::MIN)),
+                |(min, max), point| (min.inf(&point), max.sup(&point)),
+            );
+        // The bodies between the light and the box cast their shadows into it too.
+        let reach = (max - min).norm();
+
+        self.view = view;
+        self.proj = WGPU_OFFSET_M
+            * Matrix4::new_orthographic(min.x, max.x, min.y, max.y, -max.z - reach, -min.z);
+    }
+}
+
+/// The light lighting every body alike, for the faces no light reaches to stay readable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ambient {
+    pub color: Vector4<f32>,
+    pub intensity: f32,
+}
+
+impl Default for Ambient {
+    fn default() -> Self {
+        Self {
+            color: Vector4::new(1.0, 1.0, 1.0, 1.0),
+            intensity: 0.08,
+        }
+    }
+}
+
 pub struct Body {
     pub model_m: Matrix4<f32>,
+    /// The model matrix of the frame before, for the motion of the body to be told from the one
+    /// of the camera.
+    pub prev_model_m: Matrix4<f32>,
     pub buf: Arc<wgpu::Buffer>,
+    /// The corners of the box the vertices fit in, for the body to be culled once hidden, or
+    /// `None` for it to be drawn always.
+    pub bound_op: Option<[Point3<f32>; 2]>,
+    /// The image the colors of the vertices are multiplied by at their UVs, or `None` for the
+    /// colors alone.
+    pub texture_op: Option<Arc<wgpu::Texture>>,
+}
+
+/// The weight of the current frame in the history, the rest being of the frames before.
+const TAA_BLEND: f32 = 0.1;
+
+/// The frames after which the jitter repeats.
+const TAA_JITTER_PERIOD: u32 = 8;
+
+/// called => the result = the number at the index in the Halton sequence of the base, in (0, 1)
+fn halton(mut index: u32, base: u32) -> f32 {
+    let (mut result, mut f) = (0.0, 1.0);
+
+    while index > 0 {
+        f /= base as f32;
+        result += f * (index % base) as f32;
+        index /= base;
+    }
+
+    result
+}
+
+/// The temporal anti-aliasing, which jitters the view each frame and blends the frames seen.
+struct Taa {
+    taa_renderer: taa_renderer::TaaRenderer,
+    quad_renderer: quad_renderer::QuadRenderer,
+    /// The frame lit, the frames blended so far and the one to blend them into, of the size of
+    /// the surface, or `None` before the first frame.
+    texture_op: Option<[Texture; 3]>,
+    /// Whether the frames blended so far are of the frames before, false before the first frame
+    /// or after a resize.
+    has_history: bool,
+    frame: u32,
+}
+
+impl Taa {
+    /// called => the result = the offset of the projection this frame, in clip space, for a
+    /// view of the size
+    fn next_jitter(&mut self, size: f32) -> [f32; 2] {
+        self.frame = self.frame % TAA_JITTER_PERIOD + 1;
+
+        [2, 3].map(|base| (halton(self.frame, base) - 0.5) * 2.0 / size)
+    }
+
+    /// called => the result = the textures of the frame lit, the frames blended so far and the
+    /// one to blend them into, made again only if the size changed
+    fn texture_v(
+        &mut self,
+        device: &Device,
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> &[Texture; 3] {
+        let is_fit = self
+            .texture_op
+            .as_ref()
+            .is_some_and(|[cur, ..]| cur.width() == width && cur.height() == height);
+
+        if !is_fit {
+            self.has_history = false;
+            self.texture_op = Some([(); 3].map(|_| {
+                device.create_texture(&wgpu::TextureDescriptor {
+                    label: None,
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+            }));
+        }
+
+        self.texture_op.as_ref().unwrap()
+    }
 }
 
 pub struct ThreeDrawer {
@@ -205,6 +382,13 @@ pub struct ThreeDrawer {
     camera_state: camera::CameraState,
     proj_m: Matrix4<f32>,
     view_renderer: view_renderer::ViewRenderer,
+    ambient: Ambient,
+    /// The format of what is rendered to.
+    format: TextureFormat,
+    /// The projection times the view of the frame before, for the velocity.
+    prev_view_proj_m: Matrix4<f32>,
+    taa_op: Option<Taa>,
+    hi_z_op: Option<hi_z::HiZ>,
 }
 
 impl ThreeDrawer {
@@ -219,17 +403,24 @@ impl ThreeDrawer {
             camera_state: camera::CameraState::new(point![0.0, 0.0, 0.0], 0.0, 0.0),
             proj_m,
             view_renderer,
+            ambient: Ambient::default(),
+            format,
+            prev_view_proj_m: proj_m,
+            taa_op: None,
+            hi_z_op: None,
         }
     }
 
+    /// called => the looks = rendered to the surface of the width and the height
     pub fn render(
-        &self,
+        &mut self,
         device: &Device,
         queue: &Queue,
         surface: &TextureView,
         look_v: Vec<&ThreeLook>,
-        ratio: f32,
+        (width, height): (u32, u32),
     ) -> err::Result<()> {
+        let ratio = width as f32 / height as f32;
         let mut body_v = vec![];
         let mut light_v = vec![];
 
@@ -240,6 +431,17 @@ impl ThreeDrawer {
             }
         }
 
+        let view_m = self.camera_state.calc_matrix();
+        let view_proj_m = self.proj_m * view_m;
+
+        // The bodies hidden behind what was seen are not drawn, but still cast their shadows
+        // onto what is.
+        let mut view_body_v = body_v.clone();
+        if let Some(hi_z) = &mut self.hi_z_op {
+            hi_z.poll(device);
+            view_body_v.retain(|body| hi_z.is_visible(body, &view_proj_m));
+        }
+
         // mapping of light_v
         let light_texture_v = light_v
             .iter()
@@ -256,45 +458,216 @@ impl ThreeDrawer {
             })
             .collect::<Vec<(&Light, (Texture, Texture))>>();
 
-        let view_m = self.camera_state.calc_matrix();
+        let prev_view_proj_m = std::mem::replace(&mut self.prev_view_proj_m, view_proj_m);
+        let jitter = match &mut self.taa_op {
+            Some(taa) => taa.next_jitter(self.view_renderer.velocity_texture().width() as f32),
+            None => [0.0, 0.0],
+        };
 
         // color and depth of view
-        let view_texture =
-            self.view_renderer
-                .view_renderer(device, queue, &view_m, &self.proj_m, &body_v);
+        let view_texture = self.view_renderer.view_renderer(
+            device,
+            queue,
+            &view_m,
+            &self.proj_m,
+            &prev_view_proj_m,
+            jitter,
+            &view_body_v,
+        );
+        if let Some(hi_z) = &mut self.hi_z_op {
+            hi_z.build(
+                device,
+                queue,
+                self.view_renderer.depth_texture(),
+                &view_proj_m,
+            );
+        }
 
+        let taa = match &mut self.taa_op {
+            Some(taa) => taa,
+            None => {
+                return self.body_renderer.body_render(
+                    device,
+                    queue,
+                    surface,
+                    view_texture,
+                    light_texture_v,
+                    &view_m,
+                    &self.proj_m,
+                    &self.ambient,
+                    ratio,
+                )
+            }
+        };
+
+        // The frame is lit into a texture of its own, then blended into the history, which is
+        // what the surface shows.
+        let [cur, prev_history, history] = taa
+            .texture_v(device, self.format, width, height)
+            .each_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
         self.body_renderer.body_render(
             device,
             queue,
-            surface,
+            &cur,
             view_texture,
             light_texture_v,
             &view_m,
             &self.proj_m,
+            &self.ambient,
+            ratio,
+        )?;
+
+        taa.taa_renderer.taa_render(
+            device,
+            queue,
+            &history,
+            &cur,
+            taa.has_history.then_some(&prev_history),
+            &self
+                .view_renderer
+                .velocity_texture()
+                .create_view(&wgpu::TextureViewDescriptor::default()),
             ratio,
-        )
+            TAA_BLEND,
+        );
+        taa.quad_renderer
+            .quad_render(device, queue, surface, &history, [-1.0, -1.0, 1.0, 1.0]);
+        // The next frame blends from the history of this one, into the one of the frame before.
+        if let Some(texture_v) = &mut taa.texture_op {
+            texture_v.swap(1, 2);
+        }
+        taa.has_history = true;
+
+        Ok(())
+    }
+
+    pub fn is_taa_enabled(&self) -> bool {
+        self.taa_op.is_some()
+    }
+
+    /// Let the edges be smoothed by the temporal anti-aliasing, or not.
+    pub fn set_taa(&mut self, device: &Device, is_enabled: bool) {
+        if is_enabled == self.is_taa_enabled() {
+            return;
+        }
+
+        self.taa_op = is_enabled.then(|| Taa {
+            taa_renderer: taa_renderer::TaaRenderer::new(device, self.format),
+            quad_renderer: quad_renderer::QuadRenderer::new(device, self.format),
+            texture_op: None,
+            has_history: false,
+            frame: 0,
+        });
+    }
+
+    pub fn is_occlusion_culling_enabled(&self) -> bool {
+        self.hi_z_op.is_some()
+    }
+
+    /// Let the bodies hidden behind others be culled, or not.
+    pub fn set_occlusion_culling(&mut self, device: &Device, is_enabled: bool) {
+        if is_enabled == self.is_occlusion_culling_enabled() {
+            return;
+        }
+
+        self.hi_z_op = is_enabled.then(|| hi_z::HiZ::new(device));
+    }
+
+    /// called => the result = a texture of the size, in the format of this drawer, the looks
+    /// rendered to
+    pub fn render_to_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        look_v: Vec<&ThreeLook>,
+        width: u32,
+        height: u32,
+    ) -> err::Result<Texture> {
+        let texture = self.target_texture(device, width, height);
+
+        self.render(
+            device,
+            queue,
+            &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            look_v,
+            (width, height),
+        )?;
+
+        Ok(texture)
+    }
+
+    /// called => the result = a texture of the size, in the format of this drawer, to be
+    /// rendered to by [ThreeDrawer::render] frame after frame
+    pub fn target_texture(&self, device: &Device, width: u32, height: u32) -> Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
     }
 
     pub fn camera_state(&self) -> &camera::CameraState {
         &self.camera_state
     }
 
+    pub fn proj_m(&self) -> &Matrix4<f32> {
+        &self.proj_m
+    }
+
+    pub fn set_proj_m(&mut self, proj_m: Matrix4<f32>) {
+        self.proj_m = proj_m;
+    }
+
     pub fn camera_state_mut(&mut self) -> &mut camera::CameraState {
         &mut self.camera_state
     }
+
+    /// called => the result = the corners of what the camera sees, no farther than the distance
+    pub fn view_corner_v(&self, distance: f32) -> Vec<Point3<f32>> {
+        let view_m = self.camera_state.calc_matrix();
+        let inverse = match (self.proj_m * view_m).try_inverse() {
+            Some(inverse) => inverse,
+            None => return Vec::new(),
+        };
+        let depth_of = |point: &Point3<f32>| -view_m.transform_point(point).z;
+
+        let mut corner_v = Vec::with_capacity(8);
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            let near = inverse.transform_point(&point![x, y, 0.0]);
+            let far = inverse.transform_point(&point![x, y, 1.0]);
+            let t =
+                ((distance - depth_of(&near)) / (depth_of(&far) - depth_of(&near))).clamp(0.0, 1.0);
+
+            corner_v.push(near);
+            corner_v.push(near + (far - near) * t);
+        }
+
+        corner_v
+    }
+
+    pub fn ambient(&self) -> &Ambient {
+        &self.ambient
+    }
+
+    pub fn set_ambient(&mut self, ambient: Ambient) {
+        self.ambient = ambient;
+    }
 }
 
-pub fn save_texture(
-    device: &Device,
-    queue: &Queue,
-    texture: &Texture,
-    path: &str,
-    p_sz: usize,
-    f: impl Fn(u32, u32, &[u8]) -> Rgba<u8>,
-) {
+/// called => the result = a buffer the texture is being copied to, `p_sz` bytes a pixel
+fn copy_to_buffer(device: &Device, queue: &Queue, texture: &Texture, p_sz: usize) -> Buffer {
     let mut encoder =
         device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-    let (tx, rx) = channel::<bool>();
 
     let buffer = device.create_buffer(&BufferDescriptor {
         label: None,
@@ -317,6 +690,68 @@ pub fn save_texture(
 
     queue.submit(std::iter::once(encoder.finish()));
 
+    buffer
+}
+
+/// called => the result = the image at the path as an sRGB texture, for a [Body] to be drawn
+/// with
+pub fn load_texture(device: &Device, queue: &Queue, path: &str) -> err::Result<Texture> {
+    let image = image::open(path)
+        .change_context(err::Error::NotFound)
+        .attach_printable_lazy(|| format!("failed to load the image {path}"))?
+        .to_rgba8();
+
+    Ok(device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some(path),
+            size: wgpu::Extent3d {
+                width: image.width(),
+                height: image.height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        },
+        wgpu::util::TextureDataOrder::LayerMajor,
+        &image,
+    ))
+}
+
+/// Let the bytes of a texture be saved to the path as an image of its size, each pixel made by
+/// `f`.
+fn write_image(
+    bytes: &[u8],
+    (width, height): (u32, u32),
+    path: &str,
+    f: impl Fn(u32, u32, &[u8]) -> Rgba<u8>,
+) -> image::ImageResult<()> {
+    let mut img_buf: image::ImageBuffer<image::Rgba<u8>, Vec<u8>> =
+        image::ImageBuffer::new(width, height);
+
+    for (c, r, p) in img_buf.enumerate_pixels_mut() {
+        *p = f(c, r, bytes);
+    }
+
+    img_buf.save(path)
+}
+
+pub fn save_texture(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+    path: &str,
+    p_sz: usize,
+    f: impl Fn(u32, u32, &[u8]) -> Rgba<u8>,
+) {
+    let (tx, rx) = channel::<bool>();
+
+    let buffer = copy_to_buffer(device, queue, texture, p_sz);
+
     buffer.slice(..).map_async(wgpu::MapMode::Read, move |rs| {
         if let Err(e) = rs {
             log::error!("{e:?}");
@@ -333,18 +768,388 @@ pub fn save_texture(
     }
 
     log::info!("mapped");
-    {
-        let buf_view = buffer.slice(..).get_mapped_range();
+    let _ = write_image(
+        &buffer.slice(..).get_mapped_range(),
+        (texture.width(), texture.height()),
+        path,
+        f,
+    );
+
+    buffer.unmap();
+}
 
-        let mut img_buf: image::ImageBuffer<image::Rgba<u8>, Vec<u8>> =
-            image::ImageBuffer::new(texture.width(), texture.height());
+/// Let the texture be saved like [save_texture], but without blocking the thread on the GPU or
+/// on the encoding, done on a blocking thread of the tokio runtime.
+///
+/// The device must be polled elsewhere, like by the submits of the next frames or
+/// `device.poll(wgpu::Maintain::Poll)` in a loop, for the buffer to be mapped.
+///
+/// called => the result = a future resolved once the buffer is mapped and the image is saved
+pub async fn save_texture_async(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+    path: &str,
+    p_sz: usize,
+    f: impl Fn(u32, u32, &[u8]) -> Rgba<u8> + Send + 'static,
+) -> err::Result<()> {
+    let buffer = copy_to_buffer(device, queue, texture, p_sz);
+
+    MapRead::new(&buffer)
+        .await
+        .change_context(err::Error::Other)
+        .attach_printable("failed to map the buffer of the texture")?;
+
+    let bytes = buffer.slice(..).get_mapped_range().to_vec();
+    buffer.unmap();
+
+    let size = (texture.width(), texture.height());
+    let path = path.to_string();
+    tokio::task::spawn_blocking(move || {
+        write_image(&bytes, size, &path, f)
+            .change_context(err::Error::Other)
+            .attach_printable_lazy(|| format!("failed to save {path}"))
+    })
+    .await
+    .change_context(err::Error::Other)?
+}
 
-        for (c, r, p) in img_buf.enumerate_pixels_mut() {
-            *p = f(c, r, &buf_view);
+/// The result of a mapping and the task waiting for it.
+#[derive(Default)]
+struct MapState {
+    result_op: Option<Result<(), BufferAsyncError>>,
+    waker_op: Option<Waker>,
+}
+
+/// A future of a buffer mapped for reading, woken by the callback of the mapping.
+///
+/// The device is not polled by the future, see [save_texture_async].
+struct MapRead {
+    state: Arc<Mutex<MapState>>,
+}
+
+impl MapRead {
+    fn new(buffer: &Buffer) -> Self {
+        let state = Arc::new(Mutex::new(MapState::default()));
+
+        let callback_state = state.clone();
+        buffer.slice(..).map_async(wgpu::MapMode::Read, move |rs| {
+            let mut state = callback_state.lock().unwrap();
+
+            state.result_op = Some(rs);
+            if let Some(waker) = state.waker_op.take() {
+                waker.wake();
+            }
+        });
+
+        Self { state }
+    }
+}
+
+impl Future for MapRead {
+    type Output = Result<(), BufferAsyncError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+
+        match state.result_op.take() {
+            Some(rs) => Poll::Ready(rs),
+            None => {
+                state.waker_op = Some(cx.waker().clone());
+
+                Poll::Pending
+            }
         }
+    }
+}
+
+/// The file a texture is saved to by [save_texture_file], by the extension of its path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFile {
+    Png,
+    /// With no alpha.
+    Jpeg,
+    /// OpenEXR, keeping the floats of a float texture like `Rgba32Float`.
+    Exr,
+    /// The bytes of the texture as they are, row by row, for `.raw` or `.bin`.
+    Raw,
+}
 
-        let _ = img_buf.save(path);
+impl ImageFile {
+    /// called => the result = the file of the extension of the path, or `None` if unknown
+    pub fn of_path(path: &str) -> Option<Self> {
+        let ext = std::path::Path::new(path)
+            .extension()?
+            .to_str()?
+            .to_lowercase();
+
+        match ext.as_str() {
+            "png" => Some(Self::Png),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "exr" => Some(Self::Exr),
+            "raw" | "bin" => Some(Self::Raw),
+            _ => None,
+        }
     }
+}
 
-    buffer.unmap();
+fn f32_at(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_ne_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// How a pixel of the texture is read: its size in bytes and its color.
+type PixelDecoder = (u32, fn(&[u8]) -> [f32; 4]);
+
+/// called => the result = how a pixel of the format is read, or `None` for a format not
+/// supported
+fn pixel_decoder_of(format: TextureFormat) -> Option<PixelDecoder> {
+    match format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+            Some((4, |bytes| [0, 1, 2, 3].map(|i| bytes[i] as f32 / 255.0)))
+        }
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+            Some((4, |bytes| [2, 1, 0, 3].map(|i| bytes[i] as f32 / 255.0)))
+        }
+        TextureFormat::Rgba32Float => {
+            Some((16, |bytes| [0, 1, 2, 3].map(|i| f32_at(bytes, i * 4))))
+        }
+        TextureFormat::R32Float | TextureFormat::Depth32Float => Some((4, |bytes| {
+            let n = f32_at(bytes, 0);
+
+            [n, n, n, 1.0]
+        })),
+        _ => None,
+    }
+}
+
+/// A texture being copied to a buffer, to be saved once the buffer is mapped.
+struct TextureDump {
+    file: ImageFile,
+    decoder: PixelDecoder,
+    /// The rows are padded to the alignment of copies.
+    bytes_per_row: u32,
+    buffer: Buffer,
+}
+
+impl TextureDump {
+    fn new(device: &Device, queue: &Queue, texture: &Texture, path: &str) -> err::Result<Self> {
+        let file = ImageFile::of_path(path)
+            .ok_or(err::Error::NotFound)
+            .attach_printable_lazy(|| format!("no image file of the extension of {path}"))?;
+        let decoder = pixel_decoder_of(texture.format())
+            .ok_or(err::Error::NotFound)
+            .attach_printable_lazy(|| {
+                format!("can not save a texture of {:?}", texture.format())
+            })?;
+
+        let bytes_per_row = (texture.width() * decoder.0)
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: (bytes_per_row * texture.height()) as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            texture.size(),
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        Ok(Self {
+            file,
+            decoder,
+            bytes_per_row,
+            buffer,
+        })
+    }
+
+    /// called => the result = the pixels of the mapped buffer, which is unmapped
+    fn read(self, texture: &Texture) -> DumpImage {
+        let row_size = (texture.width() * self.decoder.0) as usize;
+
+        let bytes = self
+            .buffer
+            .slice(..)
+            .get_mapped_range()
+            .chunks(self.bytes_per_row as usize)
+            .flat_map(|row| row[..row_size].iter().copied())
+            .collect::<Vec<u8>>();
+        self.buffer.unmap();
+
+        DumpImage {
+            file: self.file,
+            decoder: self.decoder,
+            width: texture.width(),
+            height: texture.height(),
+            bytes,
+        }
+    }
+}
+
+/// The pixels of a [TextureDump] read out of its buffer, to be encoded to the file.
+struct DumpImage {
+    file: ImageFile,
+    decoder: PixelDecoder,
+    width: u32,
+    height: u32,
+    /// The rows without their padding.
+    bytes: Vec<u8>,
+}
+
+impl DumpImage {
+    /// Let the pixels be saved to the path.
+    fn save(&self, path: &str) -> err::Result<()> {
+        let (pixel_size, decode) = self.decoder;
+
+        match self.file {
+            ImageFile::Raw => std::fs::write(path, &self.bytes).change_context(err::Error::Other),
+            file => {
+                let n_v = self
+                    .bytes
+                    .chunks(pixel_size as usize)
+                    .flat_map(decode)
+                    .collect::<Vec<f32>>();
+                let image = image::DynamicImage::from(
+                    image::Rgba32FImage::from_raw(self.width, self.height, n_v)
+                        .ok_or(err::Error::Other)?,
+                );
+
+                match file {
+                    ImageFile::Png => image.to_rgba8().save(path),
+                    ImageFile::Jpeg => image.to_rgb8().save(path),
+                    _ => image.save(path),
+                }
+                .change_context(err::Error::Other)
+            }
+        }
+        .attach_printable_lazy(|| format!("failed to save {path}"))
+    }
+}
+
+/// Let the texture be saved to the path as the file of its extension, see [ImageFile], the
+/// pixels read by its format.
+///
+/// Unlike [save_texture], an error is returned instead of a panic.
+pub fn save_texture_file(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+    path: &str,
+) -> err::Result<()> {
+    let dump = TextureDump::new(device, queue, texture, path)?;
+
+    let (tx, rx) = channel();
+    dump.buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, move |rs| {
+            let _ = tx.send(rs);
+        });
+    let _ = device.poll(wgpu::Maintain::Wait);
+    rx.recv()
+        .change_context(err::Error::Other)?
+        .change_context(err::Error::Other)
+        .attach_printable("failed to map the buffer of the texture")?;
+
+    dump.read(texture).save(path)
+}
+
+/// Let the texture be saved like [save_texture_file], but without blocking the thread on the GPU
+/// or on the encoding, see [save_texture_async].
+pub async fn save_texture_file_async(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+    path: &str,
+) -> err::Result<()> {
+    let dump = TextureDump::new(device, queue, texture, path)?;
+
+    MapRead::new(&dump.buffer)
+        .await
+        .change_context(err::Error::Other)
+        .attach_printable("failed to map the buffer of the texture")?;
+
+    let image = dump.read(texture);
+    let path = path.to_string();
+    tokio::task::spawn_blocking(move || image.save(&path))
+        .await
+        .change_context(err::Error::Other)?
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{point, vector, Matrix4};
+    use wgpu::TextureFormat;
+
+    #[test]
+    fn test_fit_directional() {
+        let mut light = super::Light {
+            color: vector![1.0, 1.0, 1.0, 1.0],
+            intensity: 1.0,
+            view: Matrix4::identity(),
+            proj: Matrix4::identity(),
+        };
+        let point_v = [
+            point![-4.0, 0.0, -4.0],
+            point![4.0, 0.0, 4.0],
+            point![2.0, 3.0, -1.0],
+        ];
+
+        light.fit_directional(&vector![1.0, -2.0, 0.5], &point_v);
+
+        for point in &point_v {
+            let p = (light.proj * light.view).transform_point(point);
+
+            assert!(p.x.abs() <= 1.0 + 1e-4 && p.y.abs() <= 1.0 + 1e-4);
+            assert!((-1e-4..=1.0 + 1e-4).contains(&p.z));
+        }
+    }
+
+    #[test]
+    fn test_halton() {
+        assert_eq!(super::halton(1, 2), 0.5);
+        assert_eq!(super::halton(2, 2), 0.25);
+        assert_eq!(super::halton(3, 2), 0.75);
+        assert!((super::halton(2, 3) - 2.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_pixel_decoder() {
+        assert_eq!(
+            super::ImageFile::of_path("out/view.EXR"),
+            Some(super::ImageFile::Exr)
+        );
+        assert_eq!(super::ImageFile::of_path("view"), None);
+
+        let (size, decode) = super::pixel_decoder_of(TextureFormat::Bgra8Unorm).unwrap();
+        assert_eq!(size, 4);
+        assert_eq!(decode(&[0, 51, 255, 255]), [1.0, 0.2, 0.0, 1.0]);
+
+        let bytes = [0.5_f32, 0.25, 0.0, 1.0]
+            .iter()
+            .flat_map(|n| n.to_ne_bytes())
+            .collect::<Vec<u8>>();
+        let (size, decode) = super::pixel_decoder_of(TextureFormat::Rgba32Float).unwrap();
+        assert_eq!(size, 16);
+        assert_eq!(decode(&bytes), [0.5, 0.25, 0.0, 1.0]);
+
+        assert!(super::pixel_decoder_of(TextureFormat::Rgba16Float).is_none());
+    }
 }
@@ -227,6 +227,7 @@ mod tests {
             let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
             let light = Light {
                 color: vector![1.0, 1.0, 1.0, 1.0],
+                intensity: 1.0,
                 view: Matrix4::new_translation(&vector![0.0, 2.5, 0.0])
                     * Matrix4::new_rotation(vector![PI * 0.25, 0.0, 0.0]),
                 proj: WGPU_OFFSET_M
@@ -259,9 +260,11 @@ mod tests {
                 .unwrap();
 
             let lm_builder = LightMappingBuilder::new(&device);
+            let model_m = Matrix4::new_translation(&vector![0.0, 0.0, -3.0])
+                * Matrix4::new_rotation(vector![0.0, -PI * 0.25, 0.0]);
             let body_v = vec![Body {
-                model_m: Matrix4::new_translation(&vector![0.0, 0.0, -3.0])
-                    * Matrix4::new_rotation(vector![0.0, -PI * 0.25, 0.0]),
+                model_m,
+                prev_model_m: model_m,
                 buf: Arc::new(device.create_buffer_init(&BufferInitDescriptor {
                     label: None,
                     contents: bytemuck::cast_slice(
@@ -269,6 +272,8 @@ mod tests {
                     ),
                     usage: BufferUsages::VERTEX,
                 })),
+                bound_op: None,
+                texture_op: None,
             }];
 
             let (_, depth_texture) = lm_builder.light_mapping(
@@ -0,0 +1,190 @@
+//! Help the text and the graphs be drawn over the view, like a debug HUD, with no font files.
+
+use wgpu::{
+    util::{DeviceExt, TextureDataOrder},
+    Device, Queue, Texture,
+};
+
+use crate::font;
+
+/// The pixels of the image a pixel of a glyph takes, along x and y.
+const SCALE: usize = 2;
+/// The pixels from a glyph to the next.
+const ADVANCE: usize = (font::WIDTH + 1) * SCALE;
+/// The pixels from a line to the next.
+const LINE_HEIGHT: usize = (font::HEIGHT + 2) * SCALE;
+/// The pixels between the border and what is drawn.
+pub const PADDING: usize = 4;
+
+const BACKGROUND: [u8; 4] = [16, 16, 16, 255];
+
+/// An image drawn over the view, its pixels row by row from the top left.
+pub struct OverlayImage {
+    width: usize,
+    height: usize,
+    pixel_v: Vec<[u8; 4]>,
+}
+
+impl OverlayImage {
+    /// called => the result = an image of the size, of the background only
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixel_v: vec![BACKGROUND; width * height],
+        }
+    }
+
+    /// called => the result = an image just large enough for the lines, drawn in the color
+    pub fn of_line_v(line_v: &[String], color: [u8; 4]) -> Self {
+        let (width, height) = text_size_of(line_v);
+        let mut image = Self::new(width + PADDING * 2, height + PADDING * 2);
+
+        image.draw_line_v(PADDING, PADDING, line_v, color);
+
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// called => the result = the pixel at the column and the row
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.pixel_v[y * self.width + x]
+    }
+
+    /// Let the text be drawn in the color with its top left at the pixel, what is out of the
+    /// image being left out.
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, color: [u8; 4]) {
+        for (i, c) in text.chars().enumerate() {
+            let glyph = font::glyph(c);
+
+            for (row, bits) in glyph.iter().enumerate() {
+                for column in 0..font::WIDTH {
+                    if bits & (1 << (font::WIDTH - 1 - column)) == 0 {
+                        continue;
+                    }
+
+                    self.fill(
+                        x + i * ADVANCE + column * SCALE,
+                        y + row * SCALE,
+                        SCALE,
+                        SCALE,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Let the values be drawn as bars rising from the bottom of the rect, `[x, y,
+    /// width, height]` in pixels, a value of `max` as high as the rect.
+    ///
+    /// The newest values are kept if there are more than the width.
+    pub fn draw_graph(&mut self, [x, y, width, height]: [usize; 4], value_v: &[f32], max: f32) {
+        let skip = value_v.len().saturating_sub(width);
+
+        for (i, value) in value_v[skip..].iter().enumerate() {
+            let ratio = if max > 0.0 {
+                (value / max).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let bar_height = (ratio * height as f32).round() as usize;
+            // Green for a short frame, red for one as long as `max`.
+            let color = [
+                (255.0 * ratio) as u8,
+                (255.0 * (1.0 - ratio)) as u8,
+                64,
+                255,
+            ];
+
+            self.fill(x + i, y + height - bar_height, 1, bar_height, color);
+        }
+    }
+
+    /// Let the lines be drawn in the color one under another, the first with its top left at
+    /// the pixel.
+    pub fn draw_line_v(&mut self, x: usize, y: usize, line_v: &[String], color: [u8; 4]) {
+        for (i, line) in line_v.iter().enumerate() {
+            self.draw_text(x, y + i * LINE_HEIGHT, line, color);
+        }
+    }
+
+    /// Let the rect be filled with the color, what is out of the image being left out.
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 4]) {
+        for row in y..(y + height).min(self.height) {
+            for column in x..(x + width).min(self.width) {
+                self.pixel_v[row * self.width + column] = color;
+            }
+        }
+    }
+
+    /// called => the result = a texture of the image, to be drawn by a
+    /// [crate::quad_renderer::QuadRenderer]
+    pub fn texture(&self, device: &Device, queue: &Queue) -> Texture {
+        device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: self.width as u32,
+                    height: self.height as u32,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            TextureDataOrder::LayerMajor,
+            bytemuck::cast_slice(&self.pixel_v),
+        )
+    }
+}
+
+/// called => the result = the width and the height in pixels of the lines of text
+pub fn text_size_of(line_v: &[String]) -> (usize, usize) {
+    let width = line_v
+        .iter()
+        .map(|line| line.chars().count() * ADVANCE)
+        .max()
+        .unwrap_or(0);
+
+    (width, line_v.len() * LINE_HEIGHT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OverlayImage, BACKGROUND, PADDING, SCALE};
+
+    #[test]
+    fn test_of_line_v() {
+        let image = OverlayImage::of_line_v(&["-".to_string(), "--".to_string()], [255; 4]);
+
+        assert_eq!(image.width(), super::ADVANCE * 2 + PADDING * 2);
+        assert_eq!(image.height(), super::LINE_HEIGHT * 2 + PADDING * 2);
+        // The bar of `-` is its fourth row.
+        assert_eq!(image.pixel(PADDING, PADDING + 3 * SCALE), [255; 4]);
+        assert_eq!(image.pixel(PADDING, PADDING), BACKGROUND);
+    }
+
+    #[test]
+    fn test_draw_graph() {
+        let mut image = OverlayImage::new(4, 10);
+        image.draw_graph([0, 0, 4, 10], &[0.0, 5.0, 10.0, 20.0, 10.0], 10.0);
+
+        // The first value is left out, the others as high as they are.
+        assert_eq!(image.pixel(0, 4), BACKGROUND);
+        assert_ne!(image.pixel(0, 5), BACKGROUND);
+        assert_ne!(image.pixel(1, 0), BACKGROUND);
+        assert_ne!(image.pixel(2, 0), BACKGROUND);
+    }
+}
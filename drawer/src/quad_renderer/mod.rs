@@ -0,0 +1,144 @@
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroupLayout, BufferUsages, Device, Queue, RenderPipeline, Sampler, TextureFormat,
+    TextureView,
+};
+
+use crate::pipeline;
+
+/// Draws a texture onto a rectangle of the target, like a minimap onto a corner of the screen.
+pub struct QuadRenderer {
+    render_pipeline: RenderPipeline,
+    bind_group_layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl QuadRenderer {
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                // rect
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // tex
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                // tex_sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("quad"),
+        });
+
+        let render_pipeline = pipeline::RenderPipelineBuilder::new(
+            &device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Quad Render Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            }),
+            &device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Quad Render Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shader/quad_renderer.wgsl").into()),
+            }),
+            &[],
+            format,
+        )
+        .set_name(Some("Quad Render Pipeline"))
+        .build(device);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            render_pipeline,
+            bind_group_layout,
+            sampler,
+        }
+    }
+
+    /// called => the texture = drawn onto the rect of the target, `[left, bottom, right, top]` in
+    /// clip space
+    pub fn quad_render(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        target: &TextureView,
+        texture: &TextureView,
+        rect: [f32; 4],
+    ) {
+        let rect_buf = device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(&rect),
+            usage: BufferUsages::UNIFORM,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Encoder"),
+        });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_bind_group(
+                0,
+                &device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: rect_buf.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(texture),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::Sampler(&self.sampler),
+                        },
+                    ],
+                    label: None,
+                }),
+                &[],
+            );
+            render_pass.draw(0..6, 0..1);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
@@ -0,0 +1,34 @@
+struct Fragment {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// left, bottom, right and top, in clip space
+@group(0) @binding(0) var<uniform> rect: vec4<f32>;
+@group(0) @binding(1) var tex: texture_2d<f32>;
+@group(0) @binding(2) var tex_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> Fragment {
+    var corner_v = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+    );
+    let corner = corner_v[i];
+
+    var out: Fragment;
+
+    out.position = vec4<f32>(mix(rect.xy, rect.zw, corner), 0.0, 1.0);
+    out.uv = vec2<f32>(corner.x, 1.0 - corner.y);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: Fragment) -> @location(0) vec4<f32> {
+    return vec4<f32>(textureSample(tex, tex_sampler, in.uv).rgb, 1.0);
+}
@@ -1,6 +1,11 @@
-use std::f32::consts::PI;
+use std::{
+    collections::HashMap,
+    f32::consts::{PI, TAU},
+};
 
-use nalgebra::{point, vector, Matrix4, Vector4};
+use nalgebra::{point, vector, Matrix4, Point3, Vector2, Vector3, Vector4};
+
+use crate::font;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
@@ -40,12 +45,14 @@ pub struct Point3Input {
     pub position: [f32; 4],
     pub color: [f32; 4],
     pub normal: [f32; 4],
+    /// The texture coordinates, `(0, 0)` at the top left.
+    pub uv: [f32; 2],
 }
 
 impl Point3Input {
     const POS_ONLY_ATTRIBS: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![0 => Float32x4];
-    const ATTRIBS: [wgpu::VertexAttribute; 3] =
-        wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4, 2 => Float32x4];
+    const ATTRIBS: [wgpu::VertexAttribute; 4] =
+        wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4, 2 => Float32x4, 3 => Float32x2];
 
     pub fn pos_only_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         wgpu::VertexBufferLayout {
@@ -62,15 +69,127 @@ impl Point3Input {
             attributes: &Self::ATTRIBS,
         }
     }
+
+    fn new(position: Vector3<f32>, normal: Vector3<f32>, color: [f32; 4], uv: [f32; 2]) -> Self {
+        Self {
+            position: [position.x, position.y, position.z, 1.0],
+            color,
+            normal: [normal.x, normal.y, normal.z, 0.0],
+            uv,
+        }
+    }
+}
+
+/// The fractal Perlin noise the heights of a terrain are made of.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseParams {
+    pub seed: u32,
+    /// The height from the lowest to the highest point, about.
+    pub amplitude: f32,
+    /// The features per unit of length of the first octave.
+    pub frequency: f32,
+    pub octaves: u32,
+    /// How much lower each octave is than the one before.
+    pub persistence: f32,
+    /// How much finer each octave is than the one before.
+    pub lacunarity: f32,
+}
+
+impl Default for NoiseParams {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            amplitude: 1.0,
+            frequency: 0.1,
+            octaves: 4,
+            persistence: 0.5,
+            lacunarity: 2.0,
+        }
+    }
 }
 
+impl NoiseParams {
+    /// called => the result = the unit gradient at the lattice point
+    fn gradient(&self, x: i32, z: i32) -> Vector2<f32> {
+        let mut h =
+            self.seed ^ (x as u32).wrapping_mul(0x27d4_eb2d) ^ (z as u32).wrapping_mul(0x1656_67b1);
+        h ^= h >> 15;
+        h = h.wrapping_mul(0x2c1b_3c6d);
+        h ^= h >> 12;
+        h = h.wrapping_mul(0x297a_2d39);
+        h ^= h >> 15;
+
+        let angle = h as f32 / u32::MAX as f32 * TAU;
+        vector![angle.cos(), angle.sin()]
+    }
+
+    /// called => the result = the Perlin noise at the point, in about `[-0.7, 0.7]`
+    fn perlin(&self, x: f32, z: f32) -> f32 {
+        let (x0, z0) = (x.floor(), z.floor());
+        let (fx, fz) = (x - x0, z - z0);
+        let (ix, iz) = (x0 as i32, z0 as i32);
+        let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let dot = |dx: i32, dz: i32| {
+            self.gradient(ix + dx, iz + dz)
+                .dot(&vector![fx - dx as f32, fz - dz as f32])
+        };
+
+        let (u, v) = (fade(fx), fade(fz));
+        let near = dot(0, 0) + (dot(1, 0) - dot(0, 0)) * u;
+        let far = dot(0, 1) + (dot(1, 1) - dot(0, 1)) * u;
+
+        near + (far - near) * v
+    }
+
+    /// called => the result = the height of the terrain at the point
+    pub fn height(&self, x: f32, z: f32) -> f32 {
+        let (mut sum, mut weight) = (0.0, 0.0);
+        let (mut octave_amplitude, mut frequency) = (1.0, self.frequency);
+
+        for _ in 0..self.octaves.max(1) {
+            sum += self.perlin(x * frequency, z * frequency) * octave_amplitude;
+            weight += octave_amplitude;
+            octave_amplitude *= self.persistence;
+            frequency *= self.lacunarity;
+        }
+
+        sum / weight * self.amplitude
+    }
+
+    /// called => the result = the heights of the terrain of [Point3InputArray::terrain] at its
+    /// grid points, a row along X for each step along Z from `-depth / 2`, as a heightfield
+    /// takes them
+    pub fn height_v(&self, width: f32, depth: f32, resolution: usize) -> Vec<Vec<f32>> {
+        let resolution = resolution.max(1);
+
+        (0..=resolution)
+            .map(|row| {
+                let z = (row as f32 / resolution as f32 - 0.5) * depth;
+
+                (0..=resolution)
+                    .map(|column| self.height((column as f32 / resolution as f32 - 0.5) * width, z))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// A list of triangles, three vertices a triangle, counterclockwise seen from outside.
 pub struct Point3InputArray {
     vertex_v: Vec<Point3Input>,
 }
 
 impl Point3InputArray {
     pub fn cube(color: Vector4<f32>) -> Point3InputArray {
-        let color = [color.x, color.y, color.z, color.w];
+        Self::cube_faces([color; 6])
+    }
+
+    /// called => the result = a cube from the origin to `(1, 1, -1)`, its faces facing +Z, -X,
+    /// +Y, -Z, +X and -Y in the colors in that order, each face mapping the whole texture with
+    /// `v` going down
+    pub fn cube_faces(color_v: [Vector4<f32>; 6]) -> Point3InputArray {
+        let color_v = color_v.map(|color| [color.x, color.y, color.z, color.w]);
+        let color = color_v[0];
         let normal = [0.0, 0.0, 1.0, 0.0];
 
         let mut vertex_v = vec![
@@ -78,31 +197,37 @@ impl Point3InputArray {
                 position: [0.0, 0.0, 0.0, 1.0],
                 color,
                 normal,
+                uv: [0.0, 1.0],
             },
             Point3Input {
                 position: [1.0, 0.0, 0.0, 1.0],
                 color,
                 normal,
+                uv: [1.0, 1.0],
             },
             Point3Input {
                 position: [0.0, 1.0, 0.0, 1.0],
                 color,
                 normal,
+                uv: [0.0, 0.0],
             },
             Point3Input {
                 position: [0.0, 1.0, 0.0, 1.0],
                 color,
                 normal,
+                uv: [0.0, 0.0],
             },
             Point3Input {
                 position: [1.0, 0.0, 0.0, 1.0],
                 color,
                 normal,
+                uv: [1.0, 1.0],
             },
             Point3Input {
                 position: [1.0, 1.0, 0.0, 1.0],
                 color,
                 normal,
+                uv: [1.0, 0.0],
             },
         ];
 
@@ -161,8 +286,9 @@ impl Point3InputArray {
 
                     Point3Input {
                         position: [position.x, position.y, position.z, 1.0],
-                        color: vertex.color,
+                        color: color_v[i],
                         normal: [normal.x, normal.y, normal.z, 0.0],
+                        uv: vertex.uv,
                     }
                 })
                 .collect::<Vec<Point3Input>>();
@@ -174,9 +300,316 @@ impl Point3InputArray {
         Self { vertex_v }
     }
 
+    /// called => the result = the surface of `f`, which gives the position and the normal at
+    /// `(u, v)` in `[0, 1]`, in columns by rows of quads, the texture mapped with `v` going up
+    fn grid(
+        color: Vector4<f32>,
+        columns: usize,
+        rows: usize,
+        f: impl Fn(f32, f32) -> (Vector3<f32>, Vector3<f32>),
+    ) -> Point3InputArray {
+        let color = [color.x, color.y, color.z, color.w];
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        let vertex = |i: usize, j: usize| {
+            let (u, v) = (i as f32 / columns as f32, j as f32 / rows as f32);
+            let (position, normal) = f(u, v);
+
+            Point3Input::new(position, normal, color, [u, 1.0 - v])
+        };
+
+        let mut vertex_v = Vec::with_capacity(columns * rows * 6);
+        for j in 0..rows {
+            for i in 0..columns {
+                let (v00, v10, v01, v11) = (
+                    vertex(i, j),
+                    vertex(i + 1, j),
+                    vertex(i, j + 1),
+                    vertex(i + 1, j + 1),
+                );
+
+                vertex_v.extend([v00, v10, v11, v00, v11, v01]);
+            }
+        }
+
+        Self { vertex_v }
+    }
+
+    /// called => the result = a sphere of radius 0.5 around the origin, in segments around Y and
+    /// rings from pole to pole
+    pub fn uv_sphere(color: Vector4<f32>, segments: usize, rings: usize) -> Point3InputArray {
+        Self::grid(color, segments.max(3), rings.max(2), |u, v| {
+            let (phi, theta) = (u * TAU, (v - 0.5) * PI);
+            let normal = vector![
+                theta.cos() * phi.cos(),
+                theta.sin(),
+                -theta.cos() * phi.sin()
+            ];
+
+            (normal * 0.5, normal)
+        })
+    }
+
+    /// called => the result = a sphere of radius 0.5 around the origin, an icosahedron with each
+    /// triangle split in four the times of the subdivisions
+    pub fn icosphere(color: Vector4<f32>, subdivisions: usize) -> Point3InputArray {
+        let t = (1.0 + 5.0_f32.sqrt()) * 0.5;
+        let corner_v = [
+            vector![-1.0, t, 0.0],
+            vector![1.0, t, 0.0],
+            vector![-1.0, -t, 0.0],
+            vector![1.0, -t, 0.0],
+            vector![0.0, -1.0, t],
+            vector![0.0, 1.0, t],
+            vector![0.0, -1.0, -t],
+            vector![0.0, 1.0, -t],
+            vector![t, 0.0, -1.0],
+            vector![t, 0.0, 1.0],
+            vector![-t, 0.0, -1.0],
+            vector![-t, 0.0, 1.0],
+        ]
+        .map(|corner: Vector3<f32>| corner.normalize());
+        let face_v = [
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        let mut triangle_v = face_v
+            .iter()
+            .map(|face| face.map(|i| corner_v[i]))
+            .collect::<Vec<[Vector3<f32>; 3]>>();
+        for _ in 0..subdivisions {
+            triangle_v = triangle_v
+                .into_iter()
+                .flat_map(|[a, b, c]| {
+                    let (ab, bc, ca) = (
+                        (a + b).normalize(),
+                        (b + c).normalize(),
+                        (c + a).normalize(),
+                    );
+
+                    [[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        let color = [color.x, color.y, color.z, color.w];
+        let vertex_v = triangle_v
+            .into_iter()
+            .flatten()
+            .map(|normal| {
+                // The same mapping as the uv_sphere, seamed at +X.
+                let uv = [
+                    (-normal.z).atan2(normal.x).rem_euclid(TAU) / TAU,
+                    0.5 - normal.y.asin() / PI,
+                ];
+
+                Point3Input::new(normal * 0.5, normal, color, uv)
+            })
+            .collect();
+
+        Self { vertex_v }
+    }
+
+    /// called => the result = a cylinder of radius 0.5 and height 1 around the origin along Y,
+    /// capped, in the segments around
+    pub fn cylinder(color: Vector4<f32>, segments: usize) -> Point3InputArray {
+        let segments = segments.max(3);
+        let mut array = Self::grid(color, segments, 1, |u, v| {
+            let phi = u * TAU;
+            let normal = vector![phi.cos(), 0.0, -phi.sin()];
+
+            (normal * 0.5 + vector![0.0, v - 0.5, 0.0], normal)
+        });
+
+        let color = [color.x, color.y, color.z, color.w];
+        for i in 0..segments {
+            let (phi0, phi1) = (
+                i as f32 / segments as f32 * TAU,
+                (i + 1) as f32 / segments as f32 * TAU,
+            );
+            let rim0 = vector![phi0.cos(), 0.0, -phi0.sin()] * 0.5;
+            let rim1 = vector![phi1.cos(), 0.0, -phi1.sin()] * 0.5;
+
+            for (y, normal, [a, b]) in [
+                (0.5, Vector3::y(), [rim0, rim1]),
+                (-0.5, -Vector3::y(), [rim1, rim0]),
+            ] {
+                let center = vector![0.0, y, 0.0];
+                let uv_of = |rim: Vector3<f32>| [rim.x + 0.5, rim.z + 0.5];
+
+                array.vertex_v.extend([
+                    Point3Input::new(center, normal, color, [0.5, 0.5]),
+                    Point3Input::new(a + center, normal, color, uv_of(a)),
+                    Point3Input::new(b + center, normal, color, uv_of(b)),
+                ]);
+            }
+        }
+
+        array
+    }
+
+    /// called => the result = a torus around the origin in the XZ plane, of the radius from its
+    /// center to its tube and the radius of the tube, in segments around Y and sides around the
+    /// tube
+    pub fn torus(
+        color: Vector4<f32>,
+        radius: f32,
+        tube_radius: f32,
+        segments: usize,
+        sides: usize,
+    ) -> Point3InputArray {
+        Self::grid(color, segments.max(3), sides.max(3), |u, v| {
+            let (phi, psi) = (u * TAU, v * TAU);
+            let normal = vector![psi.cos() * phi.cos(), psi.sin(), -psi.cos() * phi.sin()];
+            let center = vector![phi.cos(), 0.0, -phi.sin()] * radius;
+
+            (center + normal * tube_radius, normal)
+        })
+    }
+
+    /// called => the result = a 1 by 1 square around the origin in the XZ plane, facing +Y, in
+    /// the subdivisions along each side
+    pub fn plane(color: Vector4<f32>, subdivisions: usize) -> Point3InputArray {
+        Self::grid(color, subdivisions, subdivisions, |u, v| {
+            (vector![u - 0.5, 0.0, 0.5 - v], Vector3::y())
+        })
+    }
+
+    /// called => the result = a line of the text in the font of [crate::font], one unit high,
+    /// centered on the origin along X, its bottom at Y 0, facing +Z
+    pub fn text(color: Vector4<f32>, text: &str) -> Point3InputArray {
+        let color = [color.x, color.y, color.z, color.w];
+        let pixel = 1.0 / font::HEIGHT as f32;
+        let advance = font::WIDTH + 1;
+        let count = text.chars().count();
+        let left = -((count * advance).saturating_sub(1) as f32 * pixel) * 0.5;
+        let vertex =
+            |x: f32, y: f32| Point3Input::new(vector![x, y, 0.0], Vector3::z(), color, [0.0, 0.0]);
+
+        let mut vertex_v = Vec::new();
+        for (i, c) in text.chars().enumerate() {
+            for (row, bits) in font::glyph(c).into_iter().enumerate() {
+                for column in (0..font::WIDTH).filter(|column| bits & (0x10 >> column) != 0) {
+                    let x = left + (i * advance + column) as f32 * pixel;
+                    let y = (font::HEIGHT - 1 - row) as f32 * pixel;
+                    let (v00, v10, v01, v11) = (
+                        vertex(x, y),
+                        vertex(x + pixel, y),
+                        vertex(x, y + pixel),
+                        vertex(x + pixel, y + pixel),
+                    );
+
+                    vertex_v.extend([v00, v10, v11, v00, v11, v01]);
+                }
+            }
+        }
+
+        Self { vertex_v }
+    }
+
+    /// called => the result = a terrain of the width along X and the depth along Z around the
+    /// origin, its heights by the noise, in the resolution of quads along each side
+    pub fn terrain(
+        color: Vector4<f32>,
+        width: f32,
+        depth: f32,
+        resolution: usize,
+        noise_params: &NoiseParams,
+    ) -> Point3InputArray {
+        // The step the slope is measured over.
+        const D: f32 = 1e-2;
+
+        Self::grid(color, resolution, resolution, |u, v| {
+            let (x, z) = ((u - 0.5) * width, (0.5 - v) * depth);
+            let dx = noise_params.height(x + D, z) - noise_params.height(x - D, z);
+            let dz = noise_params.height(x, z + D) - noise_params.height(x, z - D);
+
+            (
+                vector![x, noise_params.height(x, z), z],
+                vector![-dx, 2.0 * D, -dz].normalize(),
+            )
+        })
+    }
+
+    /// called => the result = the triangle list of the vertices, like those of an imported mesh
+    pub fn from_vertex_v(vertex_v: Vec<Point3Input>) -> Point3InputArray {
+        Self { vertex_v }
+    }
+
+    /// Let each vertex take the normal of the triangles sharing its position, each weighted by
+    /// its area and its angle there, so a curved surface is not faceted.
+    ///
+    /// A vertex of no triangle with an area keeps its normal.
+    pub fn smooth_normals(&mut self) {
+        // Positions closer than this are the same.
+        const WELD: f32 = 1e-5;
+        let key_of = |vertex: &Point3Input| vertex.position.map(|n| (n / WELD).round() as i64);
+        let position_of = |vertex: &Point3Input| Vector3::from_column_slice(&vertex.position[..3]);
+
+        let mut normal_mp: HashMap<[i64; 4], Vector3<f32>> = HashMap::new();
+        for triangle in self.vertex_v.chunks_exact(3) {
+            let p_v = [0, 1, 2].map(|i| position_of(&triangle[i]));
+            // Twice the area, along the normal.
+            let area_normal = (p_v[1] - p_v[0]).cross(&(p_v[2] - p_v[0]));
+
+            for i in 0..3 {
+                let (e1, e2) = (p_v[(i + 1) % 3] - p_v[i], p_v[(i + 2) % 3] - p_v[i]);
+                let angle = e1.angle(&e2);
+                if !angle.is_finite() {
+                    continue;
+                }
+
+                *normal_mp.entry(key_of(&triangle[i])).or_default() += area_normal * angle;
+            }
+        }
+
+        for vertex in &mut self.vertex_v {
+            if let Some(normal) = normal_mp
+                .get(&key_of(vertex))
+                .and_then(|normal| normal.try_normalize(f32::EPSILON))
+            {
+                vertex.normal = [normal.x, normal.y, normal.z, 0.0];
+            }
+        }
+    }
+
     pub fn vertex_v(&self) -> &[Point3Input] {
         &self.vertex_v
     }
+
+    /// called => the result = the corners of the box the vertices fit in, or `None` if there is
+    /// no vertex
+    pub fn bound(&self) -> Option<[Point3<f32>; 2]> {
+        let position_of = |vertex: &Point3Input| Point3::from_slice(&vertex.position[..3]);
+        let first = position_of(self.vertex_v.first()?);
+
+        Some(
+            self.vertex_v
+                .iter()
+                .map(position_of)
+                .fold([first, first], |[min, max], position| {
+                    [min.inf(&position), max.sup(&position)]
+                }),
+        )
+    }
 }
 
 #[repr(C)]
@@ -198,3 +631,135 @@ impl LineIn {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{point, vector, Vector3};
+
+    use super::{NoiseParams, Point3InputArray};
+
+    /// called => whether each triangle is counterclockwise seen from where its normal points
+    fn is_outward(array: &Point3InputArray) -> bool {
+        array.vertex_v().chunks(3).all(|triangle| {
+            let [a, b, c] =
+                [0, 1, 2].map(|i| Vector3::from_column_slice(&triangle[i].position[..3]));
+            let normal = Vector3::from_column_slice(&triangle[0].normal[..3]);
+
+            // A triangle at a pole of the sphere has no area to tell.
+            (b - a).cross(&(c - a)).dot(&normal) >= -1e-6
+        })
+    }
+
+    #[test]
+    fn test_generators() {
+        let color = vector![1.0, 1.0, 1.0, 1.0];
+
+        let icosphere = Point3InputArray::icosphere(color, 1);
+        assert_eq!(icosphere.vertex_v().len(), 20 * 4 * 3);
+        assert!(icosphere
+            .vertex_v()
+            .iter()
+            .all(
+                |vertex| (Vector3::from_column_slice(&vertex.position[..3]).norm() - 0.5).abs()
+                    < 1e-5
+            ));
+
+        for array in [
+            Point3InputArray::uv_sphere(color, 8, 4),
+            icosphere,
+            Point3InputArray::cylinder(color, 8),
+            Point3InputArray::torus(color, 0.5, 0.2, 8, 6),
+            Point3InputArray::plane(color, 2),
+            Point3InputArray::text(color, "Hp 10"),
+        ] {
+            assert_eq!(array.vertex_v().len() % 3, 0);
+            assert!(is_outward(&array));
+        }
+    }
+
+    #[test]
+    fn test_smooth_normals() {
+        let mut cube = Point3InputArray::cube(vector![1.0, 1.0, 1.0, 1.0]);
+        cube.smooth_normals();
+
+        // The corner at the origin is shared by the faces facing +Z, -X and -Y.
+        let corner = cube
+            .vertex_v()
+            .iter()
+            .find(|vertex| vertex.position[..3].iter().all(|n| n.abs() < 1e-5))
+            .unwrap();
+        let normal = Vector3::from_column_slice(&corner.normal[..3]);
+        assert!((normal - vector![-1.0, -1.0, 1.0].normalize()).norm() < 1e-4);
+
+        let mut sphere = Point3InputArray::uv_sphere(vector![1.0, 1.0, 1.0, 1.0], 16, 8);
+        sphere.smooth_normals();
+        assert!(sphere.vertex_v().iter().all(|vertex| {
+            let position = Vector3::from_column_slice(&vertex.position[..3]);
+            let normal = Vector3::from_column_slice(&vertex.normal[..3]);
+
+            (position.normalize() - normal).norm() < 0.05
+        }));
+    }
+
+    #[test]
+    fn test_text() {
+        let text = Point3InputArray::text(vector![1.0, 1.0, 1.0, 1.0], "-i-");
+
+        // The I lights 11 pixels and each dash 5, two triangles each.
+        assert_eq!(text.vertex_v().len(), (5 + 11 + 5) * 6);
+        let x_v = text.vertex_v().iter().map(|vertex| vertex.position[0]);
+        let (min, max) = x_v.fold((f32::MAX, f32::MIN), |(min, max), x| {
+            (min.min(x), max.max(x))
+        });
+        assert!((min + max).abs() < 1e-5);
+        assert!(text
+            .vertex_v()
+            .iter()
+            .all(|vertex| (0.0..=1.0 + 1e-5).contains(&vertex.position[1])));
+    }
+
+    #[test]
+    fn test_terrain() {
+        let noise_params = NoiseParams {
+            seed: 7,
+            amplitude: 4.0,
+            ..Default::default()
+        };
+        let terrain =
+            Point3InputArray::terrain(vector![1.0, 1.0, 1.0, 1.0], 20.0, 10.0, 8, &noise_params);
+        assert_eq!(terrain.vertex_v().len(), 8 * 8 * 6);
+        assert!(is_outward(&terrain));
+
+        let height_v = noise_params.height_v(20.0, 10.0, 8);
+        assert_eq!((height_v.len(), height_v[0].len()), (9, 9));
+        // The first vertex is at the corner of -X and +Z, the last row.
+        let first = terrain.vertex_v()[0].position;
+        assert_eq!([first[0], first[2]], [-10.0, 5.0]);
+        assert!((first[1] - height_v[8][0]).abs() < 1e-5);
+        assert!(height_v.iter().flatten().all(|h| h.abs() <= 4.0));
+        assert_ne!(height_v[0][0], height_v[4][4]);
+    }
+
+    #[test]
+    fn test_cube_faces() {
+        let color_v = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0].map(|n| vector![n, 0.0, 0.0, 1.0]);
+        let cube = Point3InputArray::cube_faces(color_v);
+
+        // The face facing +Y is the third.
+        let top = &cube.vertex_v()[12..18];
+        assert!(top.iter().all(|vertex| vertex.color[0] == 2.0));
+        assert_eq!(top[0].normal[1].round(), 1.0);
+        assert!(cube
+            .vertex_v()
+            .iter()
+            .all(|vertex| vertex.uv.iter().all(|n| (0.0..=1.0).contains(n))));
+    }
+
+    #[test]
+    fn test_bound() {
+        let bound = Point3InputArray::cube(vector![1.0, 1.0, 1.0, 1.0]).bound();
+
+        assert_eq!(bound, Some([point![0.0, 0.0, -1.0], point![1.0, 1.0, 0.0]]));
+        assert_eq!(Point3InputArray::from_vertex_v(vec![]).bound(), None);
+    }
+}
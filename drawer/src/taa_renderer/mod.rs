@@ -0,0 +1,166 @@
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroupLayout, BufferUsages, Device, Queue, RenderPipeline, Sampler, TextureFormat,
+    TextureView,
+};
+
+use crate::pipeline;
+
+/// Blends a frame into the history of the frames before, each pixel taken from where it was by
+/// the velocity.
+pub struct TaaRenderer {
+    render_pipeline: RenderPipeline,
+    bind_group_layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl TaaRenderer {
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let texture_entry = |binding: u32, filterable: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                // params
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // cur_tex
+                texture_entry(1, true),
+                // history_tex
+                texture_entry(2, true),
+                // velocity_tex
+                texture_entry(3, false),
+                // history_sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("taa"),
+        });
+
+        let render_pipeline = pipeline::RenderPipelineBuilder::new(
+            &device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("TAA Render Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            }),
+            &device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("TAA Render Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shader/taa_renderer.wgsl").into()),
+            }),
+            &[],
+            format,
+        )
+        .set_name(Some("TAA Render Pipeline"))
+        .build(device);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            render_pipeline,
+            bind_group_layout,
+            sampler,
+        }
+    }
+
+    /// called => the target = the current frame blended into the history by the weight of the
+    /// blend, or the current frame as is if there is no history
+    ///
+    /// The velocity is the one of [crate::view_renderer::ViewRenderer], of the square view
+    /// cropped to the target of the ratio.
+    pub fn taa_render(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        target: &TextureView,
+        cur: &TextureView,
+        history_op: Option<&TextureView>,
+        velocity: &TextureView,
+        ratio: f32,
+        blend: f32,
+    ) {
+        let has_history = if history_op.is_some() { 1.0 } else { 0.0 };
+        let params_buf = device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(&[blend, ratio, has_history, 0.0]),
+            usage: BufferUsages::UNIFORM,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Encoder"),
+        });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_bind_group(
+                0,
+                &device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: params_buf.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(cur),
+                        },
+                        // Without a history, the current frame stands in for it unread.
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureView(history_op.unwrap_or(cur)),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 3,
+                            resource: wgpu::BindingResource::TextureView(velocity),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 4,
+                            resource: wgpu::BindingResource::Sampler(&self.sampler),
+                        },
+                    ],
+                    label: None,
+                }),
+                &[],
+            );
+            render_pass.draw(0..3, 0..1);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
@@ -0,0 +1,79 @@
+struct Fragment {
+    @builtin(position) position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+}
+
+// the weight of the current frame, the ratio of the target, and 1 if there is a history
+@group(0) @binding(0) var<uniform> params: vec4<f32>;
+@group(0) @binding(1) var cur_tex: texture_2d<f32>;
+@group(0) @binding(2) var history_tex: texture_2d<f32>;
+// the velocity in the clip space of the view
+@group(0) @binding(3) var velocity_tex: texture_2d<f32>;
+@group(0) @binding(4) var history_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> Fragment {
+    // One triangle covering the target.
+    let ndc = vec2<f32>(f32(i / 2u) * 4.0 - 1.0, f32(i % 2u) * 4.0 - 1.0);
+
+    var out: Fragment;
+
+    out.position = vec4<f32>(ndc, 0.0, 1.0);
+    out.ndc = ndc;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: Fragment) -> @location(0) vec4<f32> {
+    let blend = params.x;
+    let ratio = params.y;
+
+    let crd = vec2<i32>(in.position.xy);
+    let size = vec2<i32>(textureDimensions(cur_tex));
+    let cur = textureLoad(cur_tex, crd, 0);
+
+    if (params.z < 0.5) {
+        return cur;
+    }
+
+    // The history is kept in the colors around, for what is no longer seen not to ghost.
+    var lo = cur.rgb;
+    var hi = cur.rgb;
+    for (var dy = -1; dy <= 1; dy++) {
+        for (var dx = -1; dx <= 1; dx++) {
+            let c = textureLoad(cur_tex, clamp(crd + vec2<i32>(dx, dy), vec2<i32>(0), size - 1), 0).rgb;
+
+            lo = min(lo, c);
+            hi = max(hi, c);
+        }
+    }
+
+    // The view is square, cropped to the target as in the body render.
+    var f_crd = in.ndc;
+    var scale = vec2<f32>(1.0, 1.0);
+
+    if (ratio > 1.0) {
+        f_crd = vec2<f32>(in.ndc.x, in.ndc.y / ratio);
+        scale = vec2<f32>(1.0, ratio);
+    } else if (ratio < 1.0) {
+        f_crd = vec2<f32>(in.ndc.x * ratio, in.ndc.y);
+        scale = vec2<f32>(1.0 / ratio, 1.0);
+    }
+
+    let view_size = vec2<i32>(textureDimensions(velocity_tex));
+    let half_sz = vec2<f32>(view_size) * 0.5;
+    let view_crd = vec2<i32>(vec2<f32>(f_crd.x, -f_crd.y) * half_sz + half_sz);
+    let velocity = textureLoad(velocity_tex, clamp(view_crd, vec2<i32>(0), view_size - 1), 0).xy * scale;
+
+    let prev_ndc = in.ndc - velocity;
+    let prev_uv = vec2<f32>(prev_ndc.x, -prev_ndc.y) * 0.5 + 0.5;
+
+    if (any(prev_uv < vec2<f32>(0.0)) || any(prev_uv > vec2<f32>(1.0))) {
+        return cur;
+    }
+
+    let history = clamp(textureSampleLevel(history_tex, history_sampler, prev_uv, 0.0).rgb, lo, hi);
+
+    return vec4<f32>(mix(history, cur.rgb, blend), cur.a);
+}
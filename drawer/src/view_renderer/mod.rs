@@ -2,8 +2,8 @@ use nalgebra::Matrix4;
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
     BindGroupLayout, BufferUsages, Color, DepthBiasState, DepthStencilState, Device, Extent3d,
-    Operations, Queue, RenderPassDepthStencilAttachment, RenderPipeline, StencilState, Texture,
-    TextureDescriptor, TextureFormat, TextureUsages,
+    Operations, Queue, RenderPassDepthStencilAttachment, RenderPipeline, Sampler, StencilState,
+    Texture, TextureDescriptor, TextureFormat, TextureUsages,
 };
 
 use crate::{pipeline, structs::Point3Input, Body};
@@ -12,7 +12,11 @@ pub struct ViewRenderer {
     render_pipeline: RenderPipeline,
     bind_group_layout: BindGroupLayout,
     view_texture: Texture,
+    velocity_texture: Texture,
     depth_texture: Texture,
+    /// Bound for the bodies with no texture, whose weight is zero.
+    blank_texture: Texture,
+    sampler: Sampler,
 }
 
 impl ViewRenderer {
@@ -49,6 +53,68 @@ impl ViewRenderer {
                     },
                     count: None,
                 },
+                // prev_model
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // prev_view_proj
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // jitter
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // tex
+                wgpu::BindGroupLayoutEntry {
+                    binding: 6,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                // tex_sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 7,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                // tex_weight
+                wgpu::BindGroupLayoutEntry {
+                    binding: 8,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
             label: Some("light"),
         });
@@ -67,6 +133,7 @@ impl ViewRenderer {
             TextureFormat::Rgba32Float,
         )
         .set_name(Some("View Render Pipeline"))
+        .add_target(TextureFormat::Rg32Float)
         .set_depth_stencil(Some(DepthStencilState {
             format: TextureFormat::Depth32Float,
             depth_write_enabled: true,
@@ -89,6 +156,20 @@ impl ViewRenderer {
             usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
             view_formats: &[],
         });
+        let velocity_texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: 1024,
+                height: 1024,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TextureFormat::Rg32Float,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
         let depth_texture = device.create_texture(&TextureDescriptor {
             label: None,
             size: Extent3d {
@@ -109,20 +190,61 @@ impl ViewRenderer {
             view_formats: &[],
         });
 
+        let blank_texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
         Self {
             render_pipeline,
             bind_group_layout,
             view_texture,
+            velocity_texture,
             depth_texture,
+            blank_texture,
+            sampler,
         }
     }
 
+    /// called => the result = how far each point seen moved since the frame before, in clip
+    /// space, as of the last [ViewRenderer::view_renderer]
+    pub fn velocity_texture(&self) -> &Texture {
+        &self.velocity_texture
+    }
+
+    /// called => the result = the depth of the bodies seen, as of the last
+    /// [ViewRenderer::view_renderer]
+    pub fn depth_texture(&self) -> &Texture {
+        &self.depth_texture
+    }
+
+    /// called => the result = the positions and the colors of the bodies seen, each pixel offset
+    /// by the jitter in clip space
     pub fn view_renderer(
         &self,
         device: &Device,
         queue: &Queue,
         view: &Matrix4<f32>,
         proj: &Matrix4<f32>,
+        prev_view_proj: &Matrix4<f32>,
+        jitter: [f32; 2],
         body_v: &[&Body],
     ) -> &Texture {
         let view_buf = device.create_buffer_init(&BufferInitDescriptor {
@@ -135,6 +257,16 @@ impl ViewRenderer {
             contents: bytemuck::cast_slice(proj.as_slice()),
             usage: BufferUsages::UNIFORM,
         });
+        let prev_view_proj_buf = device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(prev_view_proj.as_slice()),
+            usage: BufferUsages::UNIFORM,
+        });
+        let jitter_buf = device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(&[jitter[0], jitter[1], 0.0, 0.0]),
+            usage: BufferUsages::UNIFORM,
+        });
 
         let mut is_first = true;
 
@@ -147,10 +279,28 @@ impl ViewRenderer {
                 contents: bytemuck::cast_slice(body.model_m.as_slice()),
                 usage: BufferUsages::UNIFORM,
             });
+            let prev_model_buf = device.create_buffer_init(&BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(body.prev_model_m.as_slice()),
+                usage: BufferUsages::UNIFORM,
+            });
+            let (texture, tex_weight) = match &body.texture_op {
+                Some(texture) => (texture.as_ref(), 1.0f32),
+                None => (&self.blank_texture, 0.0),
+            };
+            let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let tex_weight_buf = device.create_buffer_init(&BufferInitDescriptor {
+                label: None,
+                contents: &tex_weight.to_ne_bytes(),
+                usage: BufferUsages::UNIFORM,
+            });
 
             let view_texture_view = self
                 .view_texture
                 .create_view(&wgpu::TextureViewDescriptor::default());
+            let velocity_texture_view = self
+                .velocity_texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
             let depth_texture_view = self
                 .depth_texture
                 .create_view(&wgpu::TextureViewDescriptor::default());
@@ -158,18 +308,32 @@ impl ViewRenderer {
             {
                 let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: Some("Render Pass"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &view_texture_view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: if is_first {
-                                wgpu::LoadOp::Clear(Color::TRANSPARENT)
-                            } else {
-                                wgpu::LoadOp::Load
+                    color_attachments: &[
+                        Some(wgpu::RenderPassColorAttachment {
+                            view: &view_texture_view,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: if is_first {
+                                    wgpu::LoadOp::Clear(Color::TRANSPARENT)
+                                } else {
+                                    wgpu::LoadOp::Load
+                                },
+                                store: wgpu::StoreOp::Store,
                             },
-                            store: wgpu::StoreOp::Store,
-                        },
-                    })],
+                        }),
+                        Some(wgpu::RenderPassColorAttachment {
+                            view: &velocity_texture_view,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: if is_first {
+                                    wgpu::LoadOp::Clear(Color::TRANSPARENT)
+                                } else {
+                                    wgpu::LoadOp::Load
+                                },
+                                store: wgpu::StoreOp::Store,
+                            },
+                        }),
+                    ],
                     depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
                         view: &depth_texture_view,
                         depth_ops: Some(Operations {
@@ -204,6 +368,30 @@ impl ViewRenderer {
                                 binding: 2,
                                 resource: model_buf.as_entire_binding(),
                             },
+                            wgpu::BindGroupEntry {
+                                binding: 3,
+                                resource: prev_model_buf.as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 4,
+                                resource: prev_view_proj_buf.as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 5,
+                                resource: jitter_buf.as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 6,
+                                resource: wgpu::BindingResource::TextureView(&texture_view),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 7,
+                                resource: wgpu::BindingResource::Sampler(&self.sampler),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 8,
+                                resource: tex_weight_buf.as_entire_binding(),
+                            },
                         ],
                         label: None,
                     }),
@@ -279,9 +467,11 @@ mod tests {
                 .unwrap();
 
             let renderer = ViewRenderer::new(&device);
+            let model_m = Matrix4::new_translation(&vector![0.0, 0.0, -2.0])
+                * Matrix4::new_rotation(vector![0.0, PI * 0.25, 0.0]);
             let look_v = vec![Body {
-                model_m: Matrix4::new_translation(&vector![0.0, 0.0, -2.0])
-                    * Matrix4::new_rotation(vector![0.0, PI * 0.25, 0.0]),
+                model_m,
+                prev_model_m: model_m,
                 buf: Arc::new(device.create_buffer_init(&BufferInitDescriptor {
                     label: None,
                     contents: bytemuck::cast_slice(
@@ -289,13 +479,18 @@ mod tests {
                     ),
                     usage: BufferUsages::VERTEX,
                 })),
+                bound_op: None,
+                texture_op: None,
             }];
 
+            let proj = WGPU_OFFSET_M * Matrix4::new_perspective(1.0, PI * 0.6, 0.1, 500.0);
             renderer.view_renderer(
                 &device,
                 &queue,
                 &Matrix4::identity(),
-                &(WGPU_OFFSET_M * Matrix4::new_perspective(1.0, PI * 0.6, 0.1, 500.0)),
+                &proj,
+                &proj,
+                [0.0, 0.0],
                 &look_v.iter().collect::<Vec<&Body>>(),
             );
         })
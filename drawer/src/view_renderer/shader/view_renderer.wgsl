@@ -2,17 +2,37 @@ struct Vertex {
     @location(0) position: vec4<f32>,
     @location(1) color: vec4<f32>,
     @location(2) normal: vec4<f32>,
+    @location(3) uv: vec2<f32>,
 }
 
 struct Fragment {
     @builtin(position) position: vec4<f32>,
     @location(0) pos: vec4<f32>,
     @location(1) color: vec4<f32>,
+    @location(2) cur_clip: vec4<f32>,
+    @location(3) prev_clip: vec4<f32>,
+    @location(4) uv: vec2<f32>,
+}
+
+struct Output {
+    // pos + color
+    @location(0) view: vec4<f32>,
+    // how far the point moved since the frame before, in clip space
+    @location(1) velocity: vec2<f32>,
 }
 
 @group(0) @binding(0) var<uniform> view: mat4x4<f32>;
 @group(0) @binding(1) var<uniform> proj: mat4x4<f32>;
 @group(0) @binding(2) var<uniform> model: mat4x4<f32>;
+@group(0) @binding(3) var<uniform> prev_model: mat4x4<f32>;
+@group(0) @binding(4) var<uniform> prev_view_proj: mat4x4<f32>;
+// the offset of the projection in clip space, for the temporal anti-aliasing
+@group(0) @binding(5) var<uniform> jitter: vec4<f32>;
+// the image the color is multiplied by at the uv
+@group(0) @binding(6) var tex: texture_2d<f32>;
+@group(0) @binding(7) var tex_sampler: sampler;
+// 1 for a body with a texture, 0 for one without
+@group(0) @binding(8) var<uniform> tex_weight: f32;
 
 fn f4_2_f(f4: vec4<f32>) -> f32 {
     let bit_shift = vec4<f32>(1.0, 1.0 / 10.0, 1.0 / (10.0 * 10.0), 1.0 / (10.0 * 10.0 * 10.0)) * 0.9;
@@ -26,13 +46,24 @@ fn vs_main(in: Vertex) -> Fragment {
 
     out.pos = model * in.position;
 
-    out.position = proj * view * out.pos;
+    out.cur_clip = proj * view * out.pos;
+    out.prev_clip = prev_view_proj * prev_model * in.position;
+    out.position = out.cur_clip + vec4<f32>(jitter.xy * out.cur_clip.w, 0.0, 0.0);
     out.color = in.color;
+    out.uv = in.uv;
 
     return out;
 }
 
 @fragment
-fn fs_main(in: Fragment) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.pos.xyz, f4_2_f(in.color));
+fn fs_main(in: Fragment) -> Output {
+    var out: Output;
+
+    let texel = textureSample(tex, tex_sampler, in.uv);
+    let color = in.color * mix(vec4<f32>(1.0), texel, tex_weight);
+
+    out.view = vec4<f32>(in.pos.xyz, f4_2_f(color));
+    out.velocity = in.cur_clip.xy / in.cur_clip.w - in.prev_clip.xy / in.prev_clip.w;
+
+    return out;
 }
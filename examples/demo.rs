@@ -1,10 +1,35 @@
-mod app;
+use moon_class::util::executor::ClassExecutor;
+use moon_world::{dm::MemoryDataManager, runner::Runner};
+use view_manager::ViewProps;
 
-fn main() {
+async fn mock_data() -> MemoryDataManager {
+    let mut dm = MemoryDataManager::new();
+
+    let mut ce = ClassExecutor::new(&mut dm);
+
+    ce.execute_script(include_str!("class/demo.class"))
+        .await
+        .unwrap();
+
+    dm
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
     env_logger::Builder::from_env(
         env_logger::Env::default().default_filter_or("info,wgpu=warn,demo=debug,moon_world=debug"),
     )
     .init();
 
-    app::Application::new().run().unwrap()
+    let dm = mock_data().await;
+
+    Runner::new(
+        ViewProps {
+            class: "Main".to_string(),
+            props: json::Null,
+        },
+        Box::new(dm),
+    )
+    .run()
+    .unwrap()
 }
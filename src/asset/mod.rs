@@ -0,0 +1,212 @@
+//! Help the assets like decoded sounds or vertex buffers be shared instead of loaded once per
+//! element.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    thread,
+};
+
+use crate::err;
+
+/// Assets shared by key, like the path of a file.
+///
+/// An asset is in use as long as a clone of its [Arc] is alive, so the users never release
+/// it explicitly.
+pub struct AssetCache<T> {
+    asset_mp: HashMap<String, Arc<T>>,
+}
+
+impl<T> AssetCache<T> {
+    pub fn new() -> Self {
+        Self {
+            asset_mp: HashMap::new(),
+        }
+    }
+
+    /// called => the result = the asset cached by the key, loaded by `load` if not cached yet
+    pub fn get_or_load(
+        &mut self,
+        key: &str,
+        load: impl FnOnce() -> err::Result<T>,
+    ) -> err::Result<Arc<T>> {
+        if let Some(asset) = self.asset_mp.get(key) {
+            return Ok(asset.clone());
+        }
+
+        let asset = Arc::new(load()?);
+        self.asset_mp.insert(key.to_string(), asset.clone());
+
+        Ok(asset)
+    }
+
+    /// called => the result = the asset cached by the key, made by `make` if not cached yet
+    pub fn get_or_make(&mut self, key: &str, make: impl FnOnce() -> T) -> Arc<T> {
+        self.asset_mp
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(make()))
+            .clone()
+    }
+
+    /// called => the result = the asset cached by the key, `None` if not cached
+    pub fn get(&self, key: &str) -> Option<Arc<T>> {
+        self.asset_mp.get(key).cloned()
+    }
+
+    /// Let the asset loaded elsewhere, like by an [AssetLoader], be cached by the key.
+    pub fn insert(&mut self, key: &str, asset: T) -> Arc<T> {
+        let asset = Arc::new(asset);
+        self.asset_mp.insert(key.to_string(), asset.clone());
+
+        asset
+    }
+
+    #[cfg_attr(not(feature = "audio"), allow(dead_code))]
+    pub fn contains(&self, key: &str) -> bool {
+        self.asset_mp.contains_key(key)
+    }
+
+    /// called => the result = how many users share the asset, 0 if not cached
+    pub fn ref_count(&self, key: &str) -> usize {
+        self.asset_mp
+            .get(key)
+            .map(|asset| Arc::strong_count(asset) - 1)
+            .unwrap_or(0)
+    }
+
+    /// Let the asset be dropped from the cache, still alive for its users.
+    ///
+    /// called => the result = whether the asset was cached
+    pub fn evict(&mut self, key: &str) -> bool {
+        self.asset_mp.remove(key).is_some()
+    }
+
+    /// Let the assets no one uses be dropped.
+    ///
+    /// called => the result = how many assets are dropped
+    pub fn evict_unused(&mut self) -> usize {
+        let len = self.asset_mp.len();
+
+        self.asset_mp
+            .retain(|_, asset| Arc::strong_count(asset) > 1);
+
+        len - self.asset_mp.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.asset_mp.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.asset_mp.is_empty()
+    }
+}
+
+/// Loads assets on background threads, so that a large file never stalls a frame.
+pub struct AssetLoader<T> {
+    tx: Sender<(String, err::Result<T>)>,
+    rx: Receiver<(String, err::Result<T>)>,
+    loading_set: HashSet<String>,
+}
+
+impl<T: Send + 'static> AssetLoader<T> {
+    pub fn new() -> Self {
+        let (tx, rx) = channel();
+
+        Self {
+            tx,
+            rx,
+            loading_set: HashSet::new(),
+        }
+    }
+
+    /// Let the asset of the key be loaded by `load` in the background, unless it is loading.
+    pub fn load(&mut self, key: &str, load: impl FnOnce() -> err::Result<T> + Send + 'static) {
+        if !self.loading_set.insert(key.to_string()) {
+            return;
+        }
+
+        let tx = self.tx.clone();
+        let key = key.to_string();
+        thread::spawn(move || {
+            let result = load();
+            let _ = tx.send((key, result));
+        });
+    }
+
+    pub fn is_loading(&self, key: &str) -> bool {
+        self.loading_set.contains(key)
+    }
+
+    /// called => the result = the keys and results of the loads finished since the last poll
+    pub fn poll(&mut self) -> Vec<(String, err::Result<T>)> {
+        let result_v = self
+            .rx
+            .try_iter()
+            .collect::<Vec<(String, err::Result<T>)>>();
+        for (key, _) in &result_v {
+            self.loading_set.remove(key);
+        }
+
+        result_v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_asset_cache() {
+        let mut cache = super::AssetCache::new();
+        let mut load_count = 0;
+
+        let a = cache.get_or_make("a", || {
+            load_count += 1;
+            1
+        });
+        let a2 = cache.get_or_make("a", || {
+            load_count += 1;
+            1
+        });
+        let b = cache.get_or_load("b", || Ok(2)).unwrap();
+        assert!(cache
+            .get_or_load("c", || Err(crate::err::Error::NotFound.into()))
+            .is_err());
+
+        assert_eq!(load_count, 1);
+        assert_eq!(cache.get("b").as_deref(), Some(&2));
+        assert!(cache.get("c").is_none());
+        assert_eq!(cache.ref_count("a"), 2);
+        assert_eq!(cache.len(), 2);
+
+        drop(a);
+        drop(a2);
+        assert_eq!(cache.evict_unused(), 1);
+        assert_eq!(cache.ref_count("a"), 0);
+
+        assert!(cache.evict("b"));
+        assert_eq!(*b, 2);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_asset_loader() {
+        let mut loader = super::AssetLoader::new();
+
+        loader.load("a", || Ok(1));
+        loader.load("a", || Ok(2));
+        assert!(loader.is_loading("a"));
+
+        let mut result_v = Vec::new();
+        while result_v.is_empty() {
+            result_v = loader.poll();
+        }
+
+        assert_eq!(result_v.len(), 1);
+        assert_eq!(result_v[0].0, "a");
+        assert_eq!(*result_v[0].1.as_ref().unwrap(), 1);
+        assert!(!loader.is_loading("a"));
+    }
+}
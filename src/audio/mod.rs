@@ -0,0 +1,796 @@
+use std::{
+    collections::HashMap,
+    f64::consts::TAU,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use drawer::camera::CameraState;
+use error_stack::ResultExt;
+use nalgebra::Vector3;
+use rodio::{
+    cpal::{self, traits::HostTrait},
+    source::Buffered,
+    Decoder, DeviceTrait, OutputStream, OutputStreamHandle, Sink, Source, SpatialSink,
+};
+
+use crate::{asset::AssetCache, err};
+
+/// Half of the distance between the ears of the listener.
+const HALF_HEAD_WIDTH: f32 = 0.1;
+
+type FileDecoder = Decoder<BufReader<File>>;
+/// The samples of a file, decoded once and shared by its sounds.
+pub type Decoded = Buffered<FileDecoder>;
+
+/// called => the result = the names of the available output devices
+pub fn output_device_name_v() -> Vec<String> {
+    match cpal::default_host().output_devices() {
+        Ok(device_iter) => device_iter
+            .filter_map(|device| device.name().ok())
+            .collect(),
+        Err(e) => {
+            log::warn!("output_device_name_v: {e:?}");
+
+            Vec::new()
+        }
+    }
+}
+
+/// called => the result = a stream on the output device named `name`, or on the default one if
+/// `name_op` is `None`
+fn open_stream(name_op: Option<&str>) -> err::Result<(OutputStream, OutputStreamHandle)> {
+    match name_op {
+        Some(name) => {
+            let device = cpal::default_host()
+                .output_devices()
+                .change_context(err::Error::Other)?
+                .find(|device| device.name().ok().as_deref() == Some(name))
+                .ok_or(err::Error::NotFound)
+                .attach_printable_lazy(|| format!("no output device named {name}"))?;
+
+            OutputStream::try_from_device(&device).change_context(err::Error::Other)
+        }
+        None => OutputStream::try_default().change_context(err::Error::Other),
+    }
+}
+
+/// The audio formats the engine decodes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AudioFormat {
+    Wav,
+    Vorbis,
+    Flac,
+    Mp3,
+}
+
+impl AudioFormat {
+    /// called => the result = the format told by the magic bytes at the head of a file
+    pub fn sniff(header: &[u8]) -> Option<Self> {
+        if header.starts_with(b"RIFF") && header.get(8..12) == Some(&b"WAVE"[..]) {
+            Some(AudioFormat::Wav)
+        } else if header.starts_with(b"OggS") {
+            Some(AudioFormat::Vorbis)
+        } else if header.starts_with(b"fLaC") {
+            Some(AudioFormat::Flac)
+        } else if header.starts_with(b"ID3")
+            || (header.len() >= 2 && header[0] == 0xFF && header[1] & 0xE0 == 0xE0)
+        {
+            Some(AudioFormat::Mp3)
+        } else {
+            None
+        }
+    }
+}
+
+/// called => the result = the samples of the file at `src`, all decoded
+///
+/// It takes a while for a long file, so better be called in the background.
+pub fn decode(src: &str) -> err::Result<Decoded> {
+    let decoded = open_decoder(src)?.buffered();
+    // The clones share the frames, so reading one through decodes them for all.
+    decoded.clone().for_each(drop);
+
+    Ok(decoded)
+}
+
+/// called => the result = a decoder of the file at `src`, chosen by its format
+fn open_decoder(src: &str) -> err::Result<FileDecoder> {
+    let mut file = File::open(src)
+        .change_context(err::Error::NotFound)
+        .attach_printable_lazy(|| format!("failed to open audio file '{src}'"))?;
+    let mut header = [0; 12];
+    let header_len = file.read(&mut header).change_context(err::Error::Other)?;
+    file.seek(SeekFrom::Start(0))
+        .change_context(err::Error::Other)?;
+
+    let reader = BufReader::new(file);
+    match AudioFormat::sniff(&header[..header_len]) {
+        Some(AudioFormat::Wav) => Decoder::new_wav(reader),
+        Some(AudioFormat::Vorbis) => Decoder::new_vorbis(reader),
+        Some(AudioFormat::Flac) => Decoder::new_flac(reader),
+        Some(AudioFormat::Mp3) => Decoder::new_mp3(reader),
+        None => Decoder::new(reader),
+    }
+    .change_context(err::Error::Other)
+    .attach_printable_lazy(|| format!("failed to decode audio file '{src}'"))
+}
+
+/// Either a plain stereo sink or a sink panned relative to the listener.
+pub enum AudioSink {
+    Flat(Sink),
+    Spatial(SpatialSink),
+}
+
+impl AudioSink {
+    pub fn play(&self) {
+        match self {
+            AudioSink::Flat(sink) => sink.play(),
+            AudioSink::Spatial(sink) => sink.play(),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        match self {
+            AudioSink::Flat(sink) => sink.is_paused(),
+            AudioSink::Spatial(sink) => sink.is_paused(),
+        }
+    }
+
+    pub fn pause(&self) {
+        match self {
+            AudioSink::Flat(sink) => sink.pause(),
+            AudioSink::Spatial(sink) => sink.pause(),
+        }
+    }
+
+    pub fn stop(&self) {
+        match self {
+            AudioSink::Flat(sink) => sink.stop(),
+            AudioSink::Spatial(sink) => sink.stop(),
+        }
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        match self {
+            AudioSink::Flat(sink) => sink.set_volume(volume),
+            AudioSink::Spatial(sink) => sink.set_volume(volume),
+        }
+    }
+
+    pub fn empty(&self) -> bool {
+        match self {
+            AudioSink::Flat(sink) => sink.empty(),
+            AudioSink::Spatial(sink) => sink.empty(),
+        }
+    }
+
+    /// called => the result = how far the source being played is played
+    pub fn position(&self) -> Duration {
+        match self {
+            AudioSink::Flat(sink) => sink.get_pos(),
+            AudioSink::Spatial(sink) => sink.get_pos(),
+        }
+    }
+
+    /// Let the source being played go on from the position.
+    pub fn try_seek(&self, position: Duration) -> err::Result<()> {
+        match self {
+            AudioSink::Flat(sink) => sink.try_seek(position),
+            AudioSink::Spatial(sink) => sink.try_seek(position),
+        }
+        .change_context(err::Error::Other)
+        .attach_printable_lazy(|| format!("failed to seek to {position:?}"))
+    }
+
+    pub fn queued_len(&self) -> usize {
+        match self {
+            AudioSink::Flat(sink) => sink.len(),
+            AudioSink::Spatial(sink) => sink.len(),
+        }
+    }
+
+    /// Let the source be queued after the current sounds.
+    fn append(&self, source: impl Source<Item = i16> + Send + 'static) {
+        match self {
+            AudioSink::Flat(sink) => sink.append(source),
+            AudioSink::Spatial(sink) => sink.append(source),
+        }
+    }
+}
+
+/// A linear ramp of volume over time.
+pub struct Fade {
+    from: f32,
+    to: f32,
+    /// When the fade started, or `None` until the sound is first played.
+    start_op: Option<Instant>,
+    duration: Duration,
+}
+
+impl Fade {
+    /// called => the result = a fade starting now
+    pub fn new(from: f32, to: f32, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            start_op: Some(Instant::now()),
+            duration,
+        }
+    }
+
+    /// called => the result = a fade starting once the sound is first played, see [Fade::start]
+    pub fn on_play(from: f32, to: f32, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            start_op: None,
+            duration,
+        }
+    }
+
+    /// Let the fade start now, unless it is started.
+    pub fn start(&mut self) {
+        self.start_op.get_or_insert_with(Instant::now);
+    }
+
+    /// called => the result = the volume at this moment
+    pub fn volume(&self) -> f32 {
+        let start = match self.start_op {
+            Some(start) => start,
+            None => return self.from,
+        };
+        if self.is_done() {
+            return self.to;
+        }
+
+        let t = start.elapsed().as_secs_f32() / self.duration.as_secs_f32();
+
+        self.from + (self.to - self.from) * t
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.start_op
+            .is_some_and(|start| start.elapsed() >= self.duration)
+    }
+}
+
+/// The sample rate of generated sounds.
+const SYNTH_SAMPLE_RATE: u32 = 48000;
+
+/// The shapes of generated signals.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Sawtooth,
+    Triangle,
+    Noise,
+}
+
+impl Waveform {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sine" => Some(Waveform::Sine),
+            "square" => Some(Waveform::Square),
+            "sawtooth" => Some(Waveform::Sawtooth),
+            "triangle" => Some(Waveform::Triangle),
+            "noise" => Some(Waveform::Noise),
+            _ => None,
+        }
+    }
+}
+
+/// A generated mono signal, endless unless it has a duration.
+#[derive(Debug, Clone)]
+pub struct Synth {
+    waveform: Waveform,
+    frequency: f32,
+    duration_op: Option<Duration>,
+    phase: f64,
+    seed: u32,
+}
+
+impl Synth {
+    pub fn new(waveform: Waveform, frequency: f32, duration_op: Option<Duration>) -> Self {
+        Self {
+            waveform,
+            frequency,
+            duration_op,
+            phase: 0.0,
+            seed: 0x9E37_79B9,
+        }
+    }
+
+    /// called => the result = the text telling what this generates
+    pub fn describe(&self) -> String {
+        format!(
+            "{:?}:{}:{:?}",
+            self.waveform, self.frequency, self.duration_op
+        )
+    }
+}
+
+impl Iterator for Synth {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let phase = self.phase;
+        self.phase = (self.phase + self.frequency as f64 / SYNTH_SAMPLE_RATE as f64).fract();
+
+        let value = match self.waveform {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Sawtooth => 2.0 * phase - 1.0,
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Noise => {
+                // xorshift32
+                self.seed ^= self.seed << 13;
+                self.seed ^= self.seed >> 17;
+                self.seed ^= self.seed << 5;
+
+                self.seed as f64 / u32::MAX as f64 * 2.0 - 1.0
+            }
+        };
+
+        Some((value * i16::MAX as f64) as i16)
+    }
+}
+
+impl Source for Synth {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SYNTH_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Where the samples of a sound come from.
+enum Origin {
+    /// The file is streamed from disk.
+    Stream,
+    /// The decoded samples are shared by the sounds of the same file.
+    Cached(Arc<Buffered<FileDecoder>>),
+    Synth(Synth),
+}
+
+/// How a sound is played.
+#[derive(Debug, Clone, Copy)]
+pub struct PlayMode {
+    pub is_loop: bool,
+    pub is_autoplay: bool,
+    /// Whether the decoded samples are kept for later sounds of the same file, otherwise the
+    /// file is streamed from disk.
+    pub is_cached: bool,
+}
+
+/// A playing or paused sound owned by an audio element.
+pub struct Sound {
+    pub sink: AudioSink,
+    /// The file of the sound, or the description of its generator.
+    pub src: String,
+    pub is_loop: bool,
+    /// The vnode whose physics body carries this sound.
+    pub target_op: Option<u64>,
+    /// The position of the emitter, or `None` if the sound is not panned.
+    pub position_op: Option<[f32; 3]>,
+    pub fade_op: Option<Fade>,
+    /// The volume of the sound itself, before the gain of its bus.
+    pub volume: f32,
+    pub bus: String,
+    /// How long the sound fades out once its element is deleted.
+    pub fade_out: Duration,
+    is_ended: bool,
+    origin: Origin,
+}
+
+impl Sound {
+    /// Let one more round of the sound be queued.
+    fn queue_round(&self) -> err::Result<()> {
+        match &self.origin {
+            Origin::Stream => self.sink.append(open_decoder(&self.src)?),
+            Origin::Cached(cached) => self.sink.append(cached.as_ref().clone()),
+            Origin::Synth(synth) => match synth.duration_op {
+                Some(duration) => self.sink.append(synth.clone().take_duration(duration)),
+                None => self.sink.append(synth.clone()),
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Let the sound be started, the result = the sound.
+    fn start(self) -> err::Result<Self> {
+        self.queue_round()?;
+        if self.is_loop {
+            // One more round is always queued, so the loop has no gap.
+            self.queue_round()?;
+        }
+
+        Ok(self)
+    }
+
+    /// called => the result = the event of the sound since the last poll
+    ///
+    /// A looping sound queues its next round whenever one is finished.
+    pub fn poll_event(&mut self) -> Option<&'static str> {
+        if self.is_loop {
+            if self.sink.queued_len() < 2 {
+                if let Err(e) = self.queue_round() {
+                    log::error!("poll_event: {e:?}");
+
+                    return None;
+                }
+
+                return Some("$onloop");
+            }
+        } else if !self.is_ended && self.sink.empty() {
+            self.is_ended = true;
+
+            return Some("$onended");
+        }
+
+        None
+    }
+
+    /// Let the sink be as loud as the sound through the gain of its bus.
+    pub fn apply_volume(&self, gain: f32) {
+        self.sink.set_volume(self.volume * gain);
+    }
+
+    /// called => whether the sound is played to its end, never for a looping one
+    pub fn is_finished(&self) -> bool {
+        !self.is_loop && (self.is_ended || self.sink.empty())
+    }
+
+    /// Let the volume follow the fade, the result = whether the fade is just finished.
+    pub fn apply_fade(&mut self, gain: f32) -> bool {
+        if !self.sink.is_paused() {
+            if let Some(fade) = &mut self.fade_op {
+                fade.start();
+            }
+        }
+
+        let (volume, is_done) = match &self.fade_op {
+            Some(fade) => (fade.volume(), fade.is_done()),
+            None => return false,
+        };
+
+        self.volume = volume;
+        self.apply_volume(gain);
+
+        if is_done {
+            self.fade_op = None;
+        }
+
+        is_done
+    }
+}
+
+/// Named buses scaling the volume of the sounds routed to them.
+///
+/// Every sound passes through the `master` bus as well as its own.
+pub struct Mixer {
+    bus_mp: HashMap<String, f32>,
+}
+
+impl Mixer {
+    pub fn new() -> Self {
+        Self {
+            bus_mp: HashMap::from([
+                ("master".to_string(), 1.0),
+                ("music".to_string(), 1.0),
+                ("sfx".to_string(), 1.0),
+            ]),
+        }
+    }
+
+    pub fn bus_volume(&self, bus: &str) -> f32 {
+        self.bus_mp.get(bus).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_bus_volume(&mut self, bus: &str, volume: f32) {
+        self.bus_mp.insert(bus.to_string(), volume);
+    }
+
+    /// called => the result = the gain of a sound routed to the bus
+    pub fn gain(&self, bus: &str) -> f32 {
+        if bus == "master" {
+            self.bus_volume("master")
+        } else {
+            self.bus_volume("master") * self.bus_volume(bus)
+        }
+    }
+}
+
+/// opened => the default output device = ready to play
+pub struct AudioEngine {
+    pub mixer: Mixer,
+
+    _stream_op: Option<OutputStream>,
+    handle_op: Option<OutputStreamHandle>,
+    /// The name of the chosen output device, or `None` for the default one.
+    device_name_op: Option<String>,
+
+    left_ear: [f32; 3],
+    right_ear: [f32; 3],
+
+    /// Decoded samples shared by the sounds of the same file.
+    cache: AssetCache<Buffered<FileDecoder>>,
+}
+
+impl AudioEngine {
+    pub fn new(device_name_op: Option<&str>) -> Self {
+        let (_stream_op, handle_op) = match open_stream(device_name_op) {
+            Ok((stream, handle)) => (Some(stream), Some(handle)),
+            Err(e) => {
+                log::warn!("no audio output device available: {e:?}");
+
+                (None, None)
+            }
+        };
+
+        Self {
+            mixer: Mixer::new(),
+            _stream_op,
+            handle_op,
+            device_name_op: device_name_op.map(|name| name.to_string()),
+            left_ear: [-HALF_HEAD_WIDTH, 0.0, 0.0],
+            right_ear: [HALF_HEAD_WIDTH, 0.0, 0.0],
+            cache: AssetCache::new(),
+        }
+    }
+
+    pub fn device_name_op(&self) -> Option<&str> {
+        self.device_name_op.as_deref()
+    }
+
+    /// Let the output be switched to the device named `name`, or to the default one if `name_op`
+    /// is `None`.
+    ///
+    /// The sounds made before must be rebound by [AudioEngine::rebind].
+    pub fn set_device(&mut self, name_op: Option<&str>) -> err::Result<()> {
+        let (stream, handle) = open_stream(name_op)?;
+
+        self._stream_op = Some(stream);
+        self.handle_op = Some(handle);
+        self.device_name_op = name_op.map(|name| name.to_string());
+
+        Ok(())
+    }
+
+    /// Let the sound be moved onto a new sink of the current device, going on from where it is,
+    /// or from its head if its source can not seek.
+    pub fn rebind(&self, sound: &mut Sound) -> err::Result<()> {
+        let position = sound.sink.position();
+        let sink = self.new_sink(
+            !sound.sink.is_paused(),
+            sound.position_op,
+            sound.volume * self.mixer.gain(&sound.bus),
+        )?;
+
+        sound.sink.stop();
+        sound.sink = sink;
+        sound.queue_round()?;
+        if sound.is_loop {
+            sound.queue_round()?;
+        }
+        if let Err(e) = sound.sink.try_seek(position) {
+            log::warn!("{} restarts from its head: {e:?}", sound.src);
+        }
+
+        Ok(())
+    }
+
+    /// called => the result = the cached samples of the file at `src`
+    fn cached_source(&mut self, src: &str) -> err::Result<Arc<Buffered<FileDecoder>>> {
+        self.cache
+            .get_or_load(src, || Ok(open_decoder(src)?.buffered()))
+    }
+
+    /// called => the result = whether the samples of the file at `src` are decoded
+    pub fn is_cached(&self, src: &str) -> bool {
+        self.cache.contains(src)
+    }
+
+    /// Let the samples decoded by [decode] be shared by the later sounds of the file at `src`.
+    pub fn cache_decoded(&mut self, src: &str, decoded: Decoded) {
+        self.cache.insert(src, decoded);
+    }
+
+    /// Let the decoded samples no sound uses be dropped.
+    ///
+    /// called => the result = how many files are dropped
+    pub fn evict_unused(&mut self) -> usize {
+        self.cache.evict_unused()
+    }
+
+    /// called => the result = a new sink, panned relative to the listener if `position_op` is
+    /// given
+    fn new_sink(
+        &self,
+        is_autoplay: bool,
+        position_op: Option<[f32; 3]>,
+        volume: f32,
+    ) -> err::Result<AudioSink> {
+        let handle = self.handle_op.as_ref().ok_or(err::Error::NotFound)?;
+        let sink = match position_op {
+            Some(position) => AudioSink::Spatial(
+                SpatialSink::try_new(handle, position, self.left_ear, self.right_ear)
+                    .change_context(err::Error::Other)?,
+            ),
+            None => AudioSink::Flat(Sink::try_new(handle).change_context(err::Error::Other)?),
+        };
+        if !is_autoplay {
+            sink.pause();
+        }
+        sink.set_volume(volume);
+
+        Ok(sink)
+    }
+
+    /// called => the result = a new sound playing the file at `src` through the bus
+    ///
+    /// The sound is panned relative to the listener if `position_op` is given.
+    pub fn load_sound(
+        &mut self,
+        src: &str,
+        mode: PlayMode,
+        position_op: Option<[f32; 3]>,
+        volume: f32,
+        bus: &str,
+    ) -> err::Result<Sound> {
+        let origin = if mode.is_cached {
+            Origin::Cached(self.cached_source(src)?)
+        } else {
+            Origin::Stream
+        };
+
+        Sound {
+            sink: self.new_sink(mode.is_autoplay, position_op, volume * self.mixer.gain(bus))?,
+            src: src.to_string(),
+            is_loop: mode.is_loop,
+            target_op: None,
+            position_op,
+            fade_op: None,
+            volume,
+            bus: bus.to_string(),
+            fade_out: Duration::ZERO,
+            is_ended: false,
+            origin,
+        }
+        .start()
+    }
+
+    /// called => the result = a new sound playing the generated signal through the bus
+    pub fn load_synth(
+        &mut self,
+        synth: Synth,
+        mode: PlayMode,
+        position_op: Option<[f32; 3]>,
+        volume: f32,
+        bus: &str,
+    ) -> err::Result<Sound> {
+        Sound {
+            sink: self.new_sink(mode.is_autoplay, position_op, volume * self.mixer.gain(bus))?,
+            src: synth.describe(),
+            is_loop: mode.is_loop,
+            target_op: None,
+            position_op,
+            fade_op: None,
+            volume,
+            bus: bus.to_string(),
+            fade_out: Duration::ZERO,
+            is_ended: false,
+            origin: Origin::Synth(synth),
+        }
+        .start()
+    }
+
+    /// Let the ears of the listener follow the camera.
+    pub fn update_listener<'a>(
+        &mut self,
+        camera_state: &CameraState,
+        sink_iter: impl Iterator<Item = &'a AudioSink>,
+    ) {
+        let (yaw_sin, yaw_cos) = camera_state.yaw().sin_cos();
+        let right = Vector3::new(yaw_cos, 0.0, -yaw_sin) * HALF_HEAD_WIDTH;
+        let left_ear = camera_state.position() - right;
+        let right_ear = camera_state.position() + right;
+
+        self.left_ear = [left_ear.x, left_ear.y, left_ear.z];
+        self.right_ear = [right_ear.x, right_ear.y, right_ear.z];
+
+        for sink in sink_iter {
+            if let AudioSink::Spatial(sink) = sink {
+                sink.set_left_ear_position(self.left_ear);
+                sink.set_right_ear_position(self.right_ear);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AudioFormat, Mixer, Synth, Waveform};
+
+    #[test]
+    fn test_synth() {
+        // 12000 Hz at 48000 Hz = 4 samples per period
+        let square = Synth::new(Waveform::Square, 12000.0, None)
+            .take(4)
+            .collect::<Vec<i16>>();
+        assert_eq!(square, vec![i16::MAX, i16::MAX, -i16::MAX, -i16::MAX]);
+
+        let triangle = Synth::new(Waveform::Triangle, 12000.0, None)
+            .take(3)
+            .collect::<Vec<i16>>();
+        assert_eq!(triangle, vec![-i16::MAX, 0, i16::MAX]);
+
+        assert!(Synth::new(Waveform::Noise, 0.0, None)
+            .take(1000)
+            .any(|sample| sample != 0));
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(
+            AudioFormat::sniff(b"RIFF\x24\x08\x00\x00WAVE"),
+            Some(AudioFormat::Wav)
+        );
+        assert_eq!(
+            AudioFormat::sniff(b"OggS\x00\x02"),
+            Some(AudioFormat::Vorbis)
+        );
+        assert_eq!(AudioFormat::sniff(b"fLaC\x00"), Some(AudioFormat::Flac));
+        assert_eq!(AudioFormat::sniff(b"ID3\x04"), Some(AudioFormat::Mp3));
+        assert_eq!(
+            AudioFormat::sniff(&[0xFF, 0xFB, 0x90]),
+            Some(AudioFormat::Mp3)
+        );
+        assert_eq!(AudioFormat::sniff(b"RIFF\x24\x08\x00\x00AVI "), None);
+        assert_eq!(AudioFormat::sniff(b""), None);
+    }
+
+    #[test]
+    fn test_fade_on_play() {
+        let mut fade = super::Fade::on_play(0.0, 1.0, std::time::Duration::ZERO);
+        assert_eq!(fade.volume(), 0.0);
+        assert!(!fade.is_done());
+
+        fade.start();
+        assert_eq!(fade.volume(), 1.0);
+        assert!(fade.is_done());
+    }
+
+    #[test]
+    fn test_mixer_gain() {
+        let mut mixer = Mixer::new();
+
+        mixer.set_bus_volume("master", 0.5);
+        mixer.set_bus_volume("music", 0.4);
+
+        assert_eq!(mixer.gain("master"), 0.5);
+        assert_eq!(mixer.gain("music"), 0.2);
+        assert_eq!(mixer.gain("sfx"), 0.5);
+        assert_eq!(mixer.gain("unknown"), 0.5);
+    }
+}
@@ -0,0 +1,94 @@
+//! Help the engine tell which classes a script or a network peer may read or write.
+
+use crate::dm::is_like;
+
+/// A rule of the classes and sources matching the patterns of [is_like].
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    class: String,
+    source: String,
+    can_read: bool,
+    can_write: bool,
+}
+
+/// What a principal, like a player or the console, may read and write.
+///
+/// The first rule matching a class and a source decides; with none matching, the default
+/// does.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Auth {
+    rule_v: Vec<Rule>,
+    is_allowed_by_default: bool,
+}
+
+impl Auth {
+    /// called => the result = an [Auth] allowing what no rule denies
+    pub fn allow_all() -> Self {
+        Self {
+            rule_v: Vec::new(),
+            is_allowed_by_default: true,
+        }
+    }
+
+    /// called => the result = an [Auth] denying what no rule allows
+    pub fn deny_all() -> Self {
+        Self {
+            rule_v: Vec::new(),
+            is_allowed_by_default: false,
+        }
+    }
+
+    /// Let the classes and the sources matching the patterns, like `@camera\_%` and `%`, be read
+    /// and written as told, unless an earlier rule matches them.
+    ///
+    /// A `_` not escaped matches any character, so `@camera_%` also matches `@cameras`.
+    pub fn with_rule(mut self, class: &str, source: &str, can_read: bool, can_write: bool) -> Self {
+        self.rule_v.push(Rule {
+            class: class.to_string(),
+            source: source.to_string(),
+            can_read,
+            can_write,
+        });
+        self
+    }
+
+    fn rule_op(&self, class: &str, source: &str) -> Option<&Rule> {
+        self.rule_v
+            .iter()
+            .find(|rule| is_like(&rule.class, class) && is_like(&rule.source, source))
+    }
+
+    pub fn can_read(&self, class: &str, source: &str) -> bool {
+        self.rule_op(class, source)
+            .map(|rule| rule.can_read)
+            .unwrap_or(self.is_allowed_by_default)
+    }
+
+    /// Whether the class of the source may be appended to or removed from.
+    pub fn can_write(&self, class: &str, source: &str) -> bool {
+        self.rule_op(class, source)
+            .map(|rule| rule.can_write)
+            .unwrap_or(self.is_allowed_by_default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_can_write() {
+        let auth = super::Auth::allow_all()
+            .with_rule(r"@new\_%", "%", true, false)
+            .with_rule("@%", "@window", false, false);
+
+        assert!(!auth.can_write("@new_size", "@window"));
+        assert!(auth.can_read("@new_size", "@window"));
+        assert!(!auth.can_read("@title", "@window"));
+        assert!(auth.can_write("hp", "player"));
+        // The `_` escaped is no wildcard.
+        assert!(auth.can_write("@newXsize", "@window"));
+
+        let auth = super::Auth::deny_all().with_rule("hp", "%", true, true);
+        assert!(auth.can_write("hp", "player"));
+        assert!(!auth.can_read("name", "player"));
+    }
+}
@@ -0,0 +1,177 @@
+//! Help the lines typed in the console be edited and recalled before they are executed.
+
+/// The key opening or closing the console.
+pub const TOGGLE_KEY: &str = "`";
+
+/// The most output lines kept.
+const MAX_OUTPUT_COUNT: usize = 200;
+/// The most lines of the scrollback drawn above the line typed.
+const VISIBLE_LINE_COUNT: usize = 12;
+/// The most characters of a line drawn, the rest cut off.
+const VISIBLE_COLUMN_COUNT: usize = 80;
+
+/// The line being typed, the lines executed and their output.
+pub struct Console {
+    pub vnode_id: u64,
+    pub is_open: bool,
+    line: String,
+    history_v: Vec<String>,
+    /// The line of the history being recalled, or `None` for the new line.
+    history_index_op: Option<usize>,
+    output_v: Vec<String>,
+}
+
+impl Console {
+    pub fn new(vnode_id: u64) -> Self {
+        Self {
+            vnode_id,
+            is_open: false,
+            line: String::new(),
+            history_v: Vec::new(),
+            history_index_op: None,
+            output_v: Vec::new(),
+        }
+    }
+
+    /// Let the key typed edit the line, named like `enter` or `arrowup` if it types nothing.
+    ///
+    /// called => the result = the line to be executed, if the key is enter
+    pub fn type_key(&mut self, key: &str) -> Option<String> {
+        match key {
+            "enter" | "\r" | "\n" => {
+                let line = std::mem::take(&mut self.line);
+                self.history_index_op = None;
+                if line.trim().is_empty() {
+                    return None;
+                }
+
+                if self.history_v.last() != Some(&line) {
+                    self.history_v.push(line.clone());
+                }
+                Some(line)
+            }
+            "backspace" | "\u{8}" => {
+                self.line.pop();
+                None
+            }
+            "arrowup" => {
+                let index = match self.history_index_op {
+                    Some(index) => index.saturating_sub(1),
+                    None => self.history_v.len().checked_sub(1)?,
+                };
+                self.recall(Some(index));
+                None
+            }
+            "arrowdown" => {
+                let index_op = self
+                    .history_index_op
+                    .map(|index| index + 1)
+                    .filter(|index| *index < self.history_v.len());
+                self.recall(index_op);
+                None
+            }
+            _ => {
+                // Named keys like `shift` type nothing.
+                if key.chars().count() == 1 && !key.chars().any(char::is_control) {
+                    self.line.push_str(key);
+                }
+                None
+            }
+        }
+    }
+
+    /// Let the line of the history, or an empty line, be the line typed.
+    fn recall(&mut self, index_op: Option<usize>) {
+        self.history_index_op = index_op;
+        self.line = index_op
+            .and_then(|index| self.history_v.get(index))
+            .cloned()
+            .unwrap_or_default();
+    }
+
+    /// Let the output of a line executed be kept.
+    pub fn push_output(&mut self, output: String) {
+        self.output_v.push(output);
+
+        if self.output_v.len() > MAX_OUTPUT_COUNT {
+            self.output_v.remove(0);
+        }
+    }
+
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    pub fn output_v(&self) -> &[String] {
+        &self.output_v
+    }
+
+    /// called => the result = the lines to draw, the last of the scrollback above the line typed
+    /// with a cursor, each cut to the columns drawn
+    pub fn overlay_line_v(&self) -> Vec<String> {
+        let scrollback_v = self
+            .output_v
+            .iter()
+            .flat_map(|output| output.lines())
+            .collect::<Vec<&str>>();
+        let skip = scrollback_v.len().saturating_sub(VISIBLE_LINE_COUNT);
+
+        scrollback_v[skip..]
+            .iter()
+            .map(|line| line.to_string())
+            .chain([format!("> {}_", self.line)])
+            .map(|line| line.chars().take(VISIBLE_COLUMN_COUNT).collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_type_key() {
+        let mut console = super::Console::new(0);
+
+        for key in ["a", "b", "shift", "backspace", "c"] {
+            assert_eq!(console.type_key(key), None);
+        }
+        assert_eq!(console.line(), "ac");
+        assert_eq!(console.type_key("enter").as_deref(), Some("ac"));
+        assert_eq!(console.type_key("enter"), None);
+
+        console.type_key("d");
+        console.type_key("\r");
+
+        console.type_key("arrowup");
+        assert_eq!(console.line(), "d");
+        console.type_key("arrowup");
+        assert_eq!(console.line(), "ac");
+        console.type_key("arrowdown");
+        assert_eq!(console.line(), "d");
+        console.type_key("arrowdown");
+        assert_eq!(console.line(), "");
+    }
+
+    #[test]
+    fn test_overlay_line_v() {
+        let mut console = super::Console::new(0);
+        for i in 0..super::VISIBLE_LINE_COUNT {
+            console.push_output(format!("{i}"));
+        }
+        console.push_output("a\nb".to_string());
+        console.type_key("c");
+
+        let line_v = console.overlay_line_v();
+        assert_eq!(line_v.len(), super::VISIBLE_LINE_COUNT + 1);
+        assert_eq!(line_v[0], "2");
+        assert_eq!(line_v[super::VISIBLE_LINE_COUNT - 1], "b");
+        assert_eq!(line_v.last().unwrap(), "> c_");
+
+        for _ in 0..super::VISIBLE_COLUMN_COUNT {
+            console.type_key("x");
+        }
+        assert_eq!(
+            console.overlay_line_v().last().unwrap().chars().count(),
+            super::VISIBLE_COLUMN_COUNT
+        );
+    }
+}
@@ -1,5 +1,7 @@
 //! Help to make your context constain the dependenices for moon_world.
 
+#[cfg(feature = "physics")]
 pub use rapier3d;
+#[cfg(feature = "audio")]
 pub use rodio;
 pub use winit;
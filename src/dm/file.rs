@@ -0,0 +1,279 @@
+//! Help small games keep their class data in a JSON file that can be edited by hand.
+
+use std::{
+    path::{Path, PathBuf},
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+use error_stack::ResultExt;
+use moon_class::{AsClassManager, Fu};
+
+use super::MemoryDataManager;
+use crate::{err, util::prop};
+
+/// A data manager keeping the class data in memory and writing it to a JSON file on each
+/// change, as `{"<source>": {"<class>": ["<item>", ...]}}`.
+///
+/// The file is written to a temporary file beside it first, then renamed over it, so a crash
+/// never leaves it half written. A change is applied to a copy of the data written, and kept
+/// only once written, so a failed write leaves the data as in the file.
+///
+/// With a save interval, the changes within the interval of the last write are only kept in
+/// memory, and written by the next change after it, [FileDm::flush] or the drop.
+pub struct FileDm {
+    path: PathBuf,
+    dm: MemoryDataManager,
+    save_interval: Duration,
+    last_save_time: Option<Instant>,
+    /// Whether the data has changes not written yet.
+    is_dirty: bool,
+}
+
+impl FileDm {
+    /// called => the result = a [FileDm] of the data in the file, or of no data if there is no
+    /// file yet
+    pub fn open(path: impl AsRef<Path>) -> err::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut dm = MemoryDataManager::new();
+
+        if path.exists() {
+            let s = std::fs::read_to_string(&path)
+                .change_context(err::Error::Other)
+                .attach_printable_lazy(|| format!("failed to read {}", path.display()))?;
+            let object = json::parse(&s)
+                .change_context(err::Error::Other)
+                .attach_printable_lazy(|| format!("{} is not JSON", path.display()))?;
+
+            for (source, class_mp) in object.entries() {
+                for (class, item_v) in class_mp.entries() {
+                    dm.append_item_v(
+                        class,
+                        source,
+                        // Numbers and booleans typed by hand are read as their text.
+                        item_v.members().filter_map(prop::string_of).collect(),
+                    );
+                }
+            }
+        }
+
+        Ok(Self {
+            path,
+            dm,
+            save_interval: Duration::ZERO,
+            last_save_time: None,
+            is_dirty: false,
+        })
+    }
+
+    /// Let the file be written at most once in the interval, the changes between kept in memory.
+    pub fn with_save_interval(mut self, save_interval: Duration) -> Self {
+        self.save_interval = save_interval;
+        self
+    }
+
+    /// called => the result = the data as written to the file
+    pub fn to_json(&self) -> json::JsonValue {
+        json_of(&self.dm)
+    }
+
+    /// Let the data be written to the file.
+    pub fn save(&mut self) -> err::Result<()> {
+        write(&self.path, &self.dm)?;
+        self.last_save_time = Some(Instant::now());
+        self.is_dirty = false;
+
+        Ok(())
+    }
+
+    /// Let the changes not written yet for the save interval be written to the file.
+    pub fn flush(&mut self) -> err::Result<()> {
+        if self.is_dirty {
+            self.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Let the change be applied to the data, and written if the save interval is over.
+    fn change(&mut self, f: impl FnOnce(&mut MemoryDataManager)) -> moon_class::err::Result<()> {
+        let is_due = self
+            .last_save_time
+            .map(|time| time.elapsed() >= self.save_interval)
+            .unwrap_or(true);
+        if !is_due {
+            f(&mut self.dm);
+            self.is_dirty = true;
+            return Ok(());
+        }
+
+        let mut dm = self.dm.clone();
+        f(&mut dm);
+        write(&self.path, &dm).change_context(moon_class::err::Error::NotFound)?;
+
+        self.dm = dm;
+        self.last_save_time = Some(Instant::now());
+        self.is_dirty = false;
+
+        Ok(())
+    }
+}
+
+impl Drop for FileDm {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            log::error!("failed to flush {}: {e:?}", self.path.display());
+        }
+    }
+}
+
+/// called => the result = the data as written to a file
+fn json_of(dm: &MemoryDataManager) -> json::JsonValue {
+    let mut key_v = dm.item_mp.keys().collect::<Vec<&(String, String)>>();
+    key_v.sort();
+
+    let mut object = json::object! {};
+    for key in key_v {
+        let (source, class) = key;
+
+        if !object.has_key(source) {
+            object[source.as_str()] = json::object! {};
+        }
+        object[source.as_str()][class.as_str()] = dm.item_mp[key].clone().into();
+    }
+
+    object
+}
+
+/// Let the data be written to the file, through a temporary file beside it.
+fn write(path: &Path, dm: &MemoryDataManager) -> err::Result<()> {
+    let mut tmp_path = path.to_path_buf().into_os_string();
+    tmp_path.push(".tmp");
+
+    std::fs::write(&tmp_path, json_of(dm).pretty(2))
+        .change_context(err::Error::Other)
+        .attach_printable_lazy(|| format!("failed to write {tmp_path:?}"))?;
+    std::fs::rename(&tmp_path, path)
+        .change_context(err::Error::Other)
+        .attach_printable_lazy(|| format!("failed to replace {}", path.display()))
+}
+
+impl AsClassManager for FileDm {
+    fn append<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        let result = self.change(|dm| dm.append_item_v(class, source, item_v));
+
+        Box::pin(async { result })
+    }
+
+    fn remove<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        let result = self.change(|dm| dm.remove_item_v(class, source, item_v));
+
+        Box::pin(async { result })
+    }
+
+    fn get<'a, 'a1, 'a2, 'f>(
+        &'a self,
+        class: &'a1 str,
+        source: &'a2 str,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<Vec<String>>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        self.dm.get(class, source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moon_class::AsClassManager;
+
+    #[tokio::test]
+    async fn test_save_open() {
+        let path =
+            std::env::temp_dir().join(format!("moon_world_file_dm_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut dm = super::FileDm::open(&path).unwrap();
+        dm.append("hp", "player", vec!["10".to_string()])
+            .await
+            .unwrap();
+        dm.append("name", "player", vec!["moon".to_string()])
+            .await
+            .unwrap();
+
+        let dm = super::FileDm::open(&path).unwrap();
+        assert_eq!(dm.get("hp", "player").await.unwrap(), ["10"]);
+        assert_eq!(dm.to_json()["player"]["name"][0], "moon");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_save_failed() {
+        // No directory to write the file in.
+        let path = std::env::temp_dir()
+            .join(format!("moon_world_file_dm_missing_{}", std::process::id()))
+            .join("data.json");
+
+        let mut dm = super::FileDm::open(&path).unwrap();
+        assert!(dm
+            .append("hp", "player", vec!["10".to_string()])
+            .await
+            .is_err());
+        assert!(dm.get("hp", "player").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_save_interval() {
+        let path = std::env::temp_dir().join(format!(
+            "moon_world_file_dm_interval_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut dm = super::FileDm::open(&path)
+            .unwrap()
+            .with_save_interval(std::time::Duration::from_secs(60));
+        dm.append("hp", "player", vec!["10".to_string()])
+            .await
+            .unwrap();
+        // Within the interval of the write before.
+        dm.append("hp", "player", vec!["9".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(
+            super::FileDm::open(&path).unwrap().to_json()["player"]["hp"].len(),
+            1
+        );
+
+        drop(dm);
+        assert_eq!(
+            super::FileDm::open(&path).unwrap().to_json()["player"]["hp"].len(),
+            2
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+//! Help the gets, appends and removes of a data manager be observed or changed on their way, for
+//! logging, validation or metrics.
+
+use std::pin::Pin;
+
+use moon_class::{AsClassManager, Fu};
+
+/// An append or a remove.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WriteOp {
+    Append,
+    Remove,
+}
+
+/// An interceptor of the class data going in and out of a data manager, see [Layered].
+///
+/// Each default passes the items on unchanged.
+pub trait DmMiddleware {
+    /// called => the result = the items to be written instead, or an error to refuse the write
+    fn on_write(
+        &mut self,
+        _op: WriteOp,
+        _class: &str,
+        _source: &str,
+        item_v: Vec<String>,
+    ) -> moon_class::err::Result<Vec<String>> {
+        Ok(item_v)
+    }
+
+    /// called => the result = the items to be got instead
+    fn on_get(
+        &self,
+        _class: &str,
+        _source: &str,
+        item_v: Vec<String>,
+    ) -> moon_class::err::Result<Vec<String>> {
+        Ok(item_v)
+    }
+}
+
+/// A data manager passing everything through the middleware to the inner one.
+///
+/// Layers compose, the outermost seeing a write first and a get last.
+pub struct Layered<D: AsClassManager + ?Sized = dyn AsClassManager> {
+    inner: Box<D>,
+    middleware: Box<dyn DmMiddleware + Send>,
+}
+
+impl<D: AsClassManager + ?Sized> Layered<D> {
+    pub fn new(inner: Box<D>, middleware: impl DmMiddleware + Send + 'static) -> Self {
+        Self {
+            inner,
+            middleware: Box::new(middleware),
+        }
+    }
+
+    pub fn into_inner(self) -> Box<D> {
+        self.inner
+    }
+}
+
+impl<D: AsClassManager + ?Sized> AsClassManager for Layered<D> {
+    fn append<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            let item_v = self
+                .middleware
+                .on_write(WriteOp::Append, class, source, item_v)?;
+
+            self.inner.append(class, source, item_v).await
+        })
+    }
+
+    fn remove<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            let item_v = self
+                .middleware
+                .on_write(WriteOp::Remove, class, source, item_v)?;
+
+            self.inner.remove(class, source, item_v).await
+        })
+    }
+
+    fn get<'a, 'a1, 'a2, 'f>(
+        &'a self,
+        class: &'a1 str,
+        source: &'a2 str,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<Vec<String>>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            let item_v = self.inner.get(class, source).await?;
+
+            self.middleware.on_get(class, source, item_v)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use error_stack::ResultExt;
+    use moon_class::AsClassManager;
+
+    use crate::dm::MemoryDataManager;
+
+    /// Refuses negative hp.
+    struct HpGuard;
+
+    impl super::DmMiddleware for HpGuard {
+        fn on_write(
+            &mut self,
+            op: super::WriteOp,
+            class: &str,
+            _source: &str,
+            item_v: Vec<String>,
+        ) -> moon_class::err::Result<Vec<String>> {
+            if op == super::WriteOp::Append
+                && class == "hp"
+                && item_v.iter().any(|hp| hp.starts_with('-'))
+            {
+                return Err(moon_class::err::Error::NotFound).attach_printable("negative hp");
+            }
+
+            Ok(item_v)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_layered() {
+        let mut dm = super::Layered::new(Box::new(MemoryDataManager::new()), HpGuard);
+
+        dm.append("hp", "player", vec!["10".to_string()])
+            .await
+            .unwrap();
+        assert!(dm
+            .append("hp", "player", vec!["-1".to_string()])
+            .await
+            .is_err());
+        assert_eq!(dm.get("hp", "player").await.unwrap(), ["10"]);
+    }
+}
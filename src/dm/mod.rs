@@ -0,0 +1,392 @@
+//! Help the examples and the tests keep the class data in memory, with no database.
+
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    sync::Mutex,
+};
+
+use moon_class::{AsClassManager, Fu};
+
+pub mod file;
+pub mod middleware;
+#[cfg(feature = "remote-dm")]
+pub mod remote;
+
+/// A character of a pattern of [is_like].
+#[derive(Clone, Copy, PartialEq)]
+enum PatternChar {
+    /// `%`, any run of characters.
+    Any,
+    /// `_`, any one character.
+    One,
+    Literal(char),
+}
+
+/// called => the result = the characters of the pattern, a `\` making the character after it
+/// literal
+fn pattern_char_v_of(pattern: &str) -> Vec<PatternChar> {
+    let mut pattern_char_v = Vec::new();
+    let mut char_iter = pattern.chars();
+
+    while let Some(c) = char_iter.next() {
+        pattern_char_v.push(match c {
+            '%' => PatternChar::Any,
+            '_' => PatternChar::One,
+            '\\' => PatternChar::Literal(char_iter.next().unwrap_or('\\')),
+            c => PatternChar::Literal(c),
+        });
+    }
+
+    pattern_char_v
+}
+
+/// called => the result = whether the text matches the pattern of SQL `LIKE`, where `%` is any
+/// run of characters and `_` is any one, unless escaped by `\`, as in `@new\_%`
+pub fn is_like(pattern: &str, text: &str) -> bool {
+    let pattern = pattern_char_v_of(pattern);
+    let text = text.chars().collect::<Vec<char>>();
+    // The pattern index and the text index to go back to on a mismatch after a `%`.
+    let mut back_op: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == PatternChar::Any {
+            p += 1;
+            back_op = Some((p, t));
+        } else if p < pattern.len()
+            && (pattern[p] == PatternChar::One || pattern[p] == PatternChar::Literal(text[t]))
+        {
+            p += 1;
+            t += 1;
+        } else if let Some((back_p, back_t)) = back_op {
+            p = back_p;
+            t = back_t + 1;
+            back_op = Some((back_p, back_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == PatternChar::Any)
+}
+
+/// A data manager keeping the items of each class of each source in memory.
+///
+/// The items are indexed by `(source, class)` and by the item itself as a target, so the
+/// sources pointing to a target are found without a scan.
+#[derive(Clone, Default)]
+pub struct MemoryDataManager {
+    item_mp: HashMap<(String, String), Vec<String>>,
+    /// The `(class, source)` pairs holding each item.
+    target_mp: HashMap<String, HashSet<(String, String)>>,
+}
+
+impl MemoryDataManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// called => the result = the sources whose class holds the target, sorted
+    pub fn source_v(&self, class: &str, target: &str) -> Vec<String> {
+        let mut source_v = self
+            .target_mp
+            .get(target)
+            .into_iter()
+            .flatten()
+            .filter(|(item_class, _)| item_class == class)
+            .map(|(_, source)| source.clone())
+            .collect::<Vec<String>>();
+        source_v.sort();
+
+        source_v
+    }
+
+    /// called => the result = the items of the classes matching the pattern of [is_like] of the
+    /// source, class by class in the order of their names
+    pub fn like_item_v(&self, pattern: &str, source: &str) -> Vec<String> {
+        let mut key_v = self
+            .item_mp
+            .keys()
+            .filter(|(item_source, class)| item_source == source && is_like(pattern, class))
+            .collect::<Vec<&(String, String)>>();
+        key_v.sort();
+
+        key_v
+            .into_iter()
+            .flat_map(|key| self.item_mp[key].iter().cloned())
+            .collect()
+    }
+
+    /// called => the result = the items of the class of the source, in the order appended
+    pub fn item_v(&self, class: &str, source: &str) -> &[String] {
+        self.item_mp
+            .get(&(source.to_string(), class.to_string()))
+            .map(|item_v| item_v.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn append_item_v(&mut self, class: &str, source: &str, item_v: Vec<String>) {
+        for item in &item_v {
+            self.target_mp
+                .entry(item.clone())
+                .or_default()
+                .insert((class.to_string(), source.to_string()));
+        }
+
+        self.item_mp
+            .entry((source.to_string(), class.to_string()))
+            .or_default()
+            .extend(item_v);
+    }
+
+    /// Let the first occurrence of each item be removed.
+    fn remove_item_v(&mut self, class: &str, source: &str, item_v: Vec<String>) {
+        let key = (source.to_string(), class.to_string());
+        let current_v = match self.item_mp.get_mut(&key) {
+            Some(current_v) => current_v,
+            None => return,
+        };
+
+        for item in item_v {
+            if let Some(index) = current_v.iter().position(|current| *current == item) {
+                current_v.remove(index);
+            }
+
+            if !current_v.contains(&item) {
+                if let Some(pair_set) = self.target_mp.get_mut(&item) {
+                    pair_set.remove(&(class.to_string(), source.to_string()));
+
+                    if pair_set.is_empty() {
+                        self.target_mp.remove(&item);
+                    }
+                }
+            }
+        }
+
+        if current_v.is_empty() {
+            self.item_mp.remove(&key);
+        }
+    }
+}
+
+impl AsClassManager for MemoryDataManager {
+    fn append<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        self.append_item_v(class, source, item_v);
+
+        Box::pin(async { Ok(()) })
+    }
+
+    fn remove<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        self.remove_item_v(class, source, item_v);
+
+        Box::pin(async { Ok(()) })
+    }
+
+    fn get<'a, 'a1, 'a2, 'f>(
+        &'a self,
+        class: &'a1 str,
+        source: &'a2 str,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<Vec<String>>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        // A class with `%` is a pattern of the family of classes, like `item_%`.
+        let item_v = if class.contains('%') {
+            self.like_item_v(class, source)
+        } else {
+            self.item_v(class, source).to_vec()
+        };
+
+        Box::pin(async move { Ok(item_v) })
+    }
+}
+
+/// A data manager remembering the items got from another, until they are appended or removed
+/// through it.
+///
+/// The gets of patterns like `item_%` are not remembered, as an append to one class of the
+/// family would not forget them.
+pub struct CachedDm<D: AsClassManager> {
+    dm: D,
+    /// The items got by `(class, source)`.
+    cache: Mutex<HashMap<(String, String), Vec<String>>>,
+}
+
+impl<D: AsClassManager> CachedDm<D> {
+    pub fn new(dm: D) -> Self {
+        Self {
+            dm,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Let everything remembered be forgotten, for the data changed by others.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    pub fn into_inner(self) -> D {
+        self.dm
+    }
+
+    fn forget(&self, class: &str, source: &str) {
+        self.cache
+            .lock()
+            .unwrap()
+            .remove(&(class.to_string(), source.to_string()));
+    }
+}
+
+impl<D: AsClassManager> AsClassManager for CachedDm<D> {
+    fn append<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        self.forget(class, source);
+
+        self.dm.append(class, source, item_v)
+    }
+
+    fn remove<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        self.forget(class, source);
+
+        self.dm.remove(class, source, item_v)
+    }
+
+    fn get<'a, 'a1, 'a2, 'f>(
+        &'a self,
+        class: &'a1 str,
+        source: &'a2 str,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<Vec<String>>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            if class.contains('%') {
+                return self.dm.get(class, source).await;
+            }
+
+            let key = (class.to_string(), source.to_string());
+            if let Some(item_v) = self.cache.lock().unwrap().get(&key) {
+                return Ok(item_v.clone());
+            }
+
+            let item_v = self.dm.get(class, source).await?;
+            self.cache.lock().unwrap().insert(key, item_v.clone());
+
+            Ok(item_v)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moon_class::AsClassManager;
+
+    #[test]
+    fn test_is_like() {
+        assert!(super::is_like("item_%", "item_sword"));
+        assert!(super::is_like("item_%", "item_"));
+        assert!(super::is_like("%_hp", "max_hp"));
+        assert!(super::is_like("a%c%e", "abcde"));
+        assert!(!super::is_like("item_%", "item"));
+        assert!(!super::is_like("item", "items"));
+
+        // Escaped, `_` and `%` are only themselves.
+        assert!(super::is_like(r"@new\_%", "@new_size"));
+        assert!(!super::is_like(r"@new\_%", "@newXsize"));
+        assert!(super::is_like(r"100\%", "100%"));
+        assert!(!super::is_like(r"100\%", "1000"));
+        assert!(super::is_like(r"a\\b", r"a\b"));
+    }
+
+    #[tokio::test]
+    async fn test_append_remove() {
+        let mut dm = super::MemoryDataManager::new();
+
+        dm.append("child", "root", vec!["a".to_string(), "b".to_string()])
+            .await
+            .unwrap();
+        dm.append("child", "other", vec!["a".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(dm.get("child", "root").await.unwrap(), ["a", "b"]);
+        assert_eq!(dm.source_v("child", "a"), ["other", "root"]);
+
+        dm.remove("child", "root", vec!["a".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(dm.get("child", "root").await.unwrap(), ["b"]);
+        assert_eq!(dm.source_v("child", "a"), ["other"]);
+        assert!(dm.get("child", "none").await.unwrap().is_empty());
+
+        dm.append("item_b", "root", vec!["2".to_string()])
+            .await
+            .unwrap();
+        dm.append("item_a", "root", vec!["1".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(dm.get("item_%", "root").await.unwrap(), ["1", "2"]);
+    }
+
+    #[tokio::test]
+    async fn test_cached_dm() {
+        let mut dm = super::CachedDm::new(super::MemoryDataManager::new());
+
+        dm.append("hp", "player", vec!["10".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(dm.get("hp", "player").await.unwrap(), ["10"]);
+        assert_eq!(dm.cache.lock().unwrap().len(), 1);
+
+        dm.remove("hp", "player", vec!["10".to_string()])
+            .await
+            .unwrap();
+        dm.append("hp", "player", vec!["9".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(dm.get("hp", "player").await.unwrap(), ["9"]);
+    }
+}
@@ -0,0 +1,322 @@
+//! Help thin clients share the class data of one server over a WebSocket.
+//!
+//! A request is `{$id, $op, $class, $source, $item_v}` with `$op` one of `get`, `append` and
+//! `remove`, and is answered by `{$id, $item_v}` or `{$id, $error}`.
+
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use error_stack::ResultExt;
+use futures_util::{SinkExt, StreamExt};
+use moon_class::{AsClassManager, Fu};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+    sync::Mutex,
+};
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::err;
+
+/// A get, append or remove of the class data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub op: String,
+    pub class: String,
+    pub source: String,
+    pub item_v: Vec<String>,
+}
+
+impl Request {
+    pub fn new(op: &str, class: &str, source: &str, item_v: Vec<String>) -> Self {
+        Self {
+            op: op.to_string(),
+            class: class.to_string(),
+            source: source.to_string(),
+            item_v,
+        }
+    }
+
+    pub fn dump(&self, id: u64) -> String {
+        json::object! {
+            "$id": id,
+            "$op": self.op.as_str(),
+            "$class": self.class.as_str(),
+            "$source": self.source.as_str(),
+            "$item_v": self.item_v.clone(),
+        }
+        .dump()
+    }
+
+    /// called => the result = the id and the request dumped by [Request::dump]
+    pub fn parse(s: &str) -> err::Result<(u64, Self)> {
+        let object = json::parse(s)
+            .change_context(err::Error::Other)
+            .attach_printable("a request is not JSON")?;
+
+        match (
+            object["$id"].as_u64(),
+            object["$op"].as_str(),
+            object["$class"].as_str(),
+            object["$source"].as_str(),
+        ) {
+            (Some(id), Some(op), Some(class), Some(source)) => Ok((
+                id,
+                Self::new(op, class, source, string_v_of(&object["$item_v"])),
+            )),
+            _ => Err(err::Error::NotFound)
+                .attach_printable_lazy(|| format!("no $id, $op, $class or $source in {object}")),
+        }
+    }
+}
+
+fn string_v_of(array: &json::JsonValue) -> Vec<String> {
+    array
+        .members()
+        .filter_map(|item| item.as_str().map(|item| item.to_string()))
+        .collect()
+}
+
+/// called => the result = the response to the request of the id, the items got or the error
+pub fn dump_response(id: u64, result: &Result<Vec<String>, String>) -> String {
+    match result {
+        Ok(item_v) => json::object! { "$id": id, "$item_v": item_v.clone() },
+        Err(message) => json::object! { "$id": id, "$error": message.as_str() },
+    }
+    .dump()
+}
+
+/// called => the result = the id and the result of the response dumped by [dump_response]
+pub fn parse_response(s: &str) -> err::Result<(u64, Result<Vec<String>, String>)> {
+    let object = json::parse(s)
+        .change_context(err::Error::Other)
+        .attach_printable("a response is not JSON")?;
+    let id = object["$id"]
+        .as_u64()
+        .ok_or(err::Error::NotFound)
+        .attach_printable_lazy(|| format!("no $id in {object}"))?;
+
+    match object["$error"].as_str() {
+        Some(message) => Ok((id, Err(message.to_string()))),
+        None => Ok((id, Ok(string_v_of(&object["$item_v"])))),
+    }
+}
+
+/// A data manager sending each get, append and remove to the one of a server, see [serve].
+pub struct RemoteDm {
+    stream: Mutex<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    next_id: AtomicU64,
+}
+
+impl RemoteDm {
+    /// called => the result = a [RemoteDm] connected to the server at the url, like
+    /// `ws://127.0.0.1:8080`
+    pub async fn connect(url: &str) -> err::Result<Self> {
+        let (stream, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .change_context(err::Error::Other)
+            .attach_printable_lazy(|| format!("failed to connect to {url}"))?;
+
+        Ok(Self {
+            stream: Mutex::new(stream),
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// called => the result = the items of the response to the request
+    async fn request(&self, request: Request) -> moon_class::err::Result<Vec<String>> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut stream = self.stream.lock().await;
+
+        stream
+            .send(Message::Text(request.dump(id)))
+            .await
+            .change_context(moon_class::err::Error::NotFound)
+            .attach_printable("failed to send a request")?;
+
+        while let Some(message) = stream.next().await {
+            let text = match message
+                .change_context(moon_class::err::Error::NotFound)
+                .attach_printable("failed to receive a response")?
+            {
+                Message::Text(text) => text,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+
+            let (response_id, result) =
+                parse_response(&text).change_context(moon_class::err::Error::NotFound)?;
+            // A response to a request given up on before.
+            if response_id != id {
+                continue;
+            }
+
+            return result
+                .map_err(|_| moon_class::err::Error::NotFound)
+                .attach_printable_lazy(|| {
+                    format!(
+                        "the server failed to {} {}({})",
+                        request.op, request.class, request.source
+                    )
+                });
+        }
+
+        Err(moon_class::err::Error::NotFound).attach_printable("the server closed")
+    }
+}
+
+impl AsClassManager for RemoteDm {
+    fn append<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            self.request(Request::new("append", class, source, item_v))
+                .await
+                .map(|_| ())
+        })
+    }
+
+    fn remove<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            self.request(Request::new("remove", class, source, item_v))
+                .await
+                .map(|_| ())
+        })
+    }
+
+    fn get<'a, 'a1, 'a2, 'f>(
+        &'a self,
+        class: &'a1 str,
+        source: &'a2 str,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<Vec<String>>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            self.request(Request::new("get", class, source, Vec::new()))
+                .await
+        })
+    }
+}
+
+/// Let the requests of a client on the stream be answered by the data manager, until the client
+/// closes.
+///
+/// The data manager is shared by the connections and locked for one request at a time. A request
+/// `is_allowed` refuses is answered with an error. A client may get, append or remove any class
+/// it is allowed to, so the server must not be exposed to untrusted peers without an `is_allowed`
+/// telling them apart, like by the sources of the player of the connection.
+pub async fn serve<S, D, F>(
+    mut stream: WebSocketStream<S>,
+    dm: Arc<Mutex<D>>,
+    is_allowed: F,
+) -> err::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    D: AsClassManager + ?Sized,
+    F: Fn(&Request) -> bool,
+{
+    while let Some(message) = stream.next().await {
+        let text = match message.change_context(err::Error::Other)? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let (id, request) = match Request::parse(&text) {
+            Ok(request) => request,
+            Err(e) => {
+                log::warn!("serve: {e:?}");
+                continue;
+            }
+        };
+        if !is_allowed(&request) {
+            log::warn!(
+                "serve: refused to {} {}({})",
+                request.op,
+                request.class,
+                request.source
+            );
+            stream
+                .send(Message::Text(dump_response(
+                    id,
+                    &Err("refused".to_string()),
+                )))
+                .await
+                .change_context(err::Error::Other)?;
+            continue;
+        }
+
+        let mut dm = dm.lock().await;
+        let result = match request.op.as_str() {
+            "get" => dm.get(&request.class, &request.source).await,
+            "append" => dm
+                .append(&request.class, &request.source, request.item_v)
+                .await
+                .map(|_| Vec::new()),
+            "remove" => dm
+                .remove(&request.class, &request.source, request.item_v)
+                .await
+                .map(|_| Vec::new()),
+            op => Err(moon_class::err::Error::NotFound)
+                .attach_printable_lazy(|| format!("unknown op {op}")),
+        }
+        .map_err(|e| err::describe(&e));
+        drop(dm);
+
+        stream
+            .send(Message::Text(dump_response(id, &result)))
+            .await
+            .change_context(err::Error::Other)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_parse() {
+        let request = super::Request::new("append", "hp", "player", vec!["10".to_string()]);
+        assert_eq!(
+            super::Request::parse(&request.dump(7)).unwrap(),
+            (7, request)
+        );
+        assert!(super::Request::parse(r#"{"$id":1}"#).is_err());
+
+        let result = Err("not found".to_string());
+        assert_eq!(
+            super::parse_response(&super::dump_response(3, &result)).unwrap(),
+            (3, result)
+        );
+        assert_eq!(
+            super::parse_response(r#"{"$id":4,"$item_v":["a"]}"#).unwrap(),
+            (4, Ok(vec!["a".to_string()]))
+        );
+    }
+}
@@ -6,6 +6,16 @@ use error_stack::Context;
 pub enum Error {
     Other,
     NotFound,
+    /// A class of element no provider knows, like `Physics:foo`.
+    UnsupportedElement,
+    /// A prop of an element malformed or out of its range.
+    InvalidProp,
+    /// A failure of the surface, the adapter, the device or a render.
+    Gpu,
+    /// A body or a collider the physics engine can not take.
+    Physics,
+    /// A failure of the data manager.
+    Data,
 }
 
 impl Display for Error {
@@ -17,3 +27,23 @@ impl Display for Error {
 impl Context for Error {}
 
 pub type Result<T> = error_stack::Result<T, Error>;
+
+/// called => the result = the messages attached to the report, the outermost first
+pub fn describe<C: Context>(report: &error_stack::Report<C>) -> String {
+    let message_v = report
+        .frames()
+        .filter_map(|frame| {
+            frame.downcast_ref::<String>().cloned().or_else(|| {
+                frame
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+            })
+        })
+        .collect::<Vec<String>>();
+
+    if message_v.is_empty() {
+        report.current_context().to_string()
+    } else {
+        message_v.join(": ")
+    }
+}
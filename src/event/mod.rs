@@ -0,0 +1,144 @@
+//! Help an embedder give the input to the [crate::Engine] as typed events.
+
+/// An input for [crate::Engine::push_event], handled at the start of the next step.
+///
+/// Positions are normalized to [0, 1] by the size of the window.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EngineEvent {
+    /// The text typed by the key, or the name of the key like `escape` if it types nothing.
+    KeyDown {
+        key: String,
+        repeat: bool,
+    },
+    KeyUp {
+        key: String,
+    },
+    MouseMove {
+        x: f64,
+        y: f64,
+    },
+    /// The motion of the mouse itself, in heights of the window, for looking around.
+    MouseMotion {
+        dx: f64,
+        dy: f64,
+    },
+    /// `left`, `right`, `middle`, `back`, `forward` or the number of another button.
+    MouseDown {
+        button: String,
+    },
+    MouseUp {
+        button: String,
+    },
+    /// In lines.
+    Scroll {
+        x: f64,
+        y: f64,
+    },
+    TouchStart {
+        id: u64,
+        x: f64,
+        y: f64,
+    },
+    TouchMove {
+        id: u64,
+        x: f64,
+        y: f64,
+    },
+    TouchEnd {
+        id: u64,
+        x: f64,
+        y: f64,
+    },
+    TouchCancel {
+        id: u64,
+        x: f64,
+        y: f64,
+    },
+    Resize {
+        width: u32,
+        height: u32,
+    },
+    Close,
+    /// Any other entry of [crate::Engine::event_handler].
+    Custom {
+        entry_name: String,
+        data: json::JsonValue,
+    },
+}
+
+impl EngineEvent {
+    /// called => the result = the entry name and the data of the event for
+    /// [crate::Engine::event_handler]
+    pub fn to_entry(&self) -> (String, json::JsonValue) {
+        let (entry_name, data) = match self {
+            EngineEvent::KeyDown { key, repeat } => (
+                "$onkeydown",
+                json::object! { "$key": key.as_str(), "$repeat": *repeat },
+            ),
+            EngineEvent::KeyUp { key } => ("$onkeyup", json::object! { "$key": key.as_str() }),
+            EngineEvent::MouseMove { x, y } => {
+                ("$onmousemove", json::object! { "$x": *x, "$y": *y })
+            }
+            EngineEvent::MouseMotion { dx, dy } => {
+                ("$cursormoved", json::object! { "$x": *dx, "$y": *dy })
+            }
+            EngineEvent::MouseDown { button } => {
+                ("$onmousedown", json::object! { "$button": button.as_str() })
+            }
+            EngineEvent::MouseUp { button } => {
+                ("$onmouseup", json::object! { "$button": button.as_str() })
+            }
+            EngineEvent::Scroll { x, y } => ("$onscroll", json::object! { "$x": *x, "$y": *y }),
+            EngineEvent::TouchStart { id, x, y } => (
+                "$ontouchstart",
+                json::object! { "$id": *id, "$x": *x, "$y": *y },
+            ),
+            EngineEvent::TouchMove { id, x, y } => (
+                "$ontouchmove",
+                json::object! { "$id": *id, "$x": *x, "$y": *y },
+            ),
+            EngineEvent::TouchEnd { id, x, y } => (
+                "$ontouchend",
+                json::object! { "$id": *id, "$x": *x, "$y": *y },
+            ),
+            EngineEvent::TouchCancel { id, x, y } => (
+                "$ontouchcancel",
+                json::object! { "$id": *id, "$x": *x, "$y": *y },
+            ),
+            EngineEvent::Resize { width, height } => (
+                "$onresize",
+                json::object! { "$width": *width, "$height": *height },
+            ),
+            EngineEvent::Close => ("$onclose", json::object! {}),
+            EngineEvent::Custom { entry_name, data } => {
+                return (entry_name.clone(), data.clone());
+            }
+        };
+
+        (entry_name.to_string(), data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EngineEvent;
+
+    #[test]
+    fn test_to_entry() {
+        let (entry_name, data) = EngineEvent::KeyDown {
+            key: "w".to_string(),
+            repeat: false,
+        }
+        .to_entry();
+        assert_eq!(entry_name, "$onkeydown");
+        assert_eq!(data, json::object! { "$key": "w", "$repeat": false });
+
+        assert_eq!(EngineEvent::Close.to_entry().0, "$onclose");
+
+        let custom = EngineEvent::Custom {
+            entry_name: "$onping".to_string(),
+            data: json::object! { "$n": 1 },
+        };
+        assert_eq!(custom.to_entry().1["$n"], 1);
+    }
+}
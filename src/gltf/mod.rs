@@ -0,0 +1,250 @@
+//! Help the scenes be inspected or edited in Blender and other DCC tools, as glTF.
+
+use std::path::Path;
+
+use drawer::structs::Point3Input;
+use error_stack::ResultExt;
+use nalgebra::{UnitQuaternion, Vector3, Vector4};
+
+use crate::err;
+
+/// `componentType` of `f32`.
+const FLOAT: u32 = 5126;
+/// `target` of a vertex buffer.
+const ARRAY_BUFFER: u32 = 34962;
+
+/// A scene of meshes and lights to be written as a `.gltf` file and a `.bin` file beside it.
+///
+/// Each mesh keeps the triangles it is drawn with, colored by their vertices, and the nodes of
+/// the same triangles share it.
+#[derive(Default)]
+pub struct GltfScene {
+    node_v: Vec<json::JsonValue>,
+    mesh_v: Vec<Vec<Point3Input>>,
+    light_v: Vec<json::JsonValue>,
+}
+
+impl GltfScene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let a mesh of the triangles be put at the transform.
+    pub fn push_mesh(
+        &mut self,
+        name: &str,
+        transform: (Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>),
+        vertex_v: &[Point3Input],
+    ) {
+        let bytes = bytemuck::cast_slice::<Point3Input, u8>(vertex_v);
+        let mesh = match self
+            .mesh_v
+            .iter()
+            .position(|mesh| bytemuck::cast_slice::<Point3Input, u8>(mesh) == bytes)
+        {
+            Some(mesh) => mesh,
+            None => {
+                self.mesh_v.push(vertex_v.to_vec());
+                self.mesh_v.len() - 1
+            }
+        };
+
+        let mut node = node_of(name, transform);
+        node["mesh"] = mesh.into();
+        self.node_v.push(node);
+    }
+
+    /// Let a directional light in the color and the intensity be put at the transform, shining
+    /// along its -Z.
+    pub fn push_light(
+        &mut self,
+        name: &str,
+        transform: (Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>),
+        color: Vector4<f32>,
+        intensity: f32,
+    ) {
+        let mut node = node_of(name, transform);
+        node["extensions"] = json::object! {
+            "KHR_lights_punctual": { "light": self.light_v.len() },
+        };
+        self.node_v.push(node);
+        self.light_v.push(json::object! {
+            "name": name,
+            "type": "directional",
+            "color": [color.x, color.y, color.z],
+            "intensity": intensity,
+        });
+    }
+
+    /// called => the result = the glTF document and the binary buffer it refers to by the uri
+    pub fn to_gltf(&self, bin_uri: &str) -> (json::JsonValue, Vec<u8>) {
+        let mut gltf = json::object! {
+            "asset": { "version": "2.0", "generator": "moon_world" },
+            "scene": 0,
+            "scenes": [{ "nodes": (0..self.node_v.len()).collect::<Vec<usize>>() }],
+            "nodes": self.node_v.clone(),
+        };
+        let mut bin = Vec::new();
+
+        if !self.mesh_v.is_empty() {
+            let mut buffer_view_v = Vec::new();
+            let mut accessor_v = Vec::new();
+            let mut mesh_v = Vec::new();
+
+            for vertex_v in &self.mesh_v {
+                let count = vertex_v.len();
+                let bound = |i: usize, f: fn(f32, f32) -> f32, start: f32| {
+                    vertex_v
+                        .iter()
+                        .map(|vertex| vertex.position[i])
+                        .fold(start, f)
+                };
+                let min = [0, 1, 2].map(|i| bound(i, f32::min, f32::MAX));
+                let max = [0, 1, 2].map(|i| bound(i, f32::max, f32::MIN));
+
+                // The positions, the normals and the colors, each a view and an accessor.
+                let attribute_v: [(fn(&Point3Input) -> &[f32], &str); 3] = [
+                    (|vertex| &vertex.position[..3], "VEC3"),
+                    (|vertex| &vertex.normal[..3], "VEC3"),
+                    (|vertex| &vertex.color[..], "VEC4"),
+                ];
+                for (i, (attribute_of, kind)) in attribute_v.into_iter().enumerate() {
+                    let offset = bin.len();
+                    for vertex in vertex_v {
+                        bin.extend(attribute_of(vertex).iter().flat_map(|n| n.to_le_bytes()));
+                    }
+
+                    let mut accessor = json::object! {
+                        "bufferView": buffer_view_v.len(),
+                        "componentType": FLOAT,
+                        "count": count,
+                        "type": kind,
+                    };
+                    // The bounds glTF asks of the positions.
+                    if i == 0 {
+                        accessor["min"] = min.to_vec().into();
+                        accessor["max"] = max.to_vec().into();
+                    }
+                    accessor_v.push(accessor);
+                    buffer_view_v.push(json::object! {
+                        "buffer": 0,
+                        "byteOffset": offset,
+                        "byteLength": bin.len() - offset,
+                        "target": ARRAY_BUFFER,
+                    });
+                }
+
+                let position = accessor_v.len() - 3;
+                mesh_v.push(json::object! {
+                    "primitives": [{
+                        "attributes": {
+                            "POSITION": position,
+                            "NORMAL": position + 1,
+                            "COLOR_0": position + 2,
+                        },
+                        "material": 0,
+                    }],
+                });
+            }
+
+            gltf["buffers"] = json::array![{ "uri": bin_uri, "byteLength": bin.len() }];
+            gltf["bufferViews"] = buffer_view_v.into();
+            gltf["accessors"] = accessor_v.into();
+            // The colors are of the vertices, the material only lets them be seen.
+            gltf["materials"] = json::array![{
+                "pbrMetallicRoughness": { "metallicFactor": 0.0 },
+                // The winding of the meshes is not the one glTF takes for the front.
+                "doubleSided": true,
+            }];
+            gltf["meshes"] = mesh_v.into();
+        }
+
+        if !self.light_v.is_empty() {
+            gltf["extensionsUsed"] = json::array!["KHR_lights_punctual"];
+            gltf["extensions"] = json::object! {
+                "KHR_lights_punctual": { "lights": self.light_v.clone() },
+            };
+        }
+
+        (gltf, bin)
+    }
+
+    /// Let the scene be written to the path, like `scene.gltf`, and its buffer to `scene.bin`
+    /// beside it.
+    pub fn save(&self, path: &Path) -> err::Result<()> {
+        let bin_path = path.with_extension("bin");
+        let bin_uri = bin_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(err::Error::Other)
+            .attach_printable_lazy(|| format!("{} is not a file path", path.display()))?;
+        let (gltf, bin) = self.to_gltf(bin_uri);
+
+        if !bin.is_empty() {
+            std::fs::write(&bin_path, bin)
+                .change_context(err::Error::Other)
+                .attach_printable_lazy(|| format!("failed to write {}", bin_path.display()))?;
+        }
+        std::fs::write(path, gltf.pretty(2))
+            .change_context(err::Error::Other)
+            .attach_printable_lazy(|| format!("failed to write {}", path.display()))
+    }
+}
+
+fn node_of(
+    name: &str,
+    (pos, rotation, scale): (Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>),
+) -> json::JsonValue {
+    json::object! {
+        "name": name,
+        "translation": [pos.x, pos.y, pos.z],
+        "rotation": [rotation.i, rotation.j, rotation.k, rotation.w],
+        "scale": [scale.x, scale.y, scale.z],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use drawer::structs::Point3InputArray;
+    use nalgebra::{vector, UnitQuaternion};
+
+    #[test]
+    fn test_to_gltf() {
+        let mut scene = super::GltfScene::new();
+        let transform = (
+            vector![1.0, 2.0, 3.0],
+            UnitQuaternion::identity(),
+            vector![1.0, 1.0, 1.0],
+        );
+        let red = Point3InputArray::cube(vector![1.0, 0.0, 0.0, 1.0]);
+        let label = Point3InputArray::text(vector![1.0, 1.0, 1.0, 1.0], "a");
+
+        scene.push_mesh("a", transform, red.vertex_v());
+        scene.push_mesh("b", transform, red.vertex_v());
+        scene.push_light("sun", transform, vector![1.0, 1.0, 1.0, 1.0], 2.0);
+        scene.push_mesh("label", transform, label.vertex_v());
+
+        let (gltf, bin) = scene.to_gltf("scene.bin");
+        assert_eq!(gltf["scenes"][0]["nodes"].len(), 4);
+        assert_eq!(gltf["meshes"].len(), 2);
+        assert_eq!(gltf["nodes"][1]["mesh"], 0);
+        assert_eq!(gltf["nodes"][3]["mesh"], 1);
+        assert_eq!(
+            gltf["meshes"][1]["primitives"][0]["attributes"]["COLOR_0"],
+            5
+        );
+        assert_eq!(gltf["accessors"][3]["count"], label.vertex_v().len());
+        assert_eq!(gltf["nodes"][0]["translation"][2], 3.0);
+        assert_eq!(
+            gltf["nodes"][2]["extensions"]["KHR_lights_punctual"]["light"],
+            0
+        );
+        assert_eq!(
+            gltf["extensions"]["KHR_lights_punctual"]["lights"][0]["intensity"],
+            2.0
+        );
+        assert_eq!(gltf["buffers"][0]["byteLength"], bin.len());
+        let vertex_count = red.vertex_v().len() + label.vertex_v().len();
+        assert_eq!(bin.len(), vertex_count * (12 + 12 + 16));
+    }
+}
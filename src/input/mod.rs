@@ -0,0 +1,536 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// The longest time between two clicks of a double click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+/// The farthest distance between two clicks of a double click.
+const DOUBLE_CLICK_DISTANCE: f32 = 0.02;
+/// The distance a press must move to start a drag.
+const DRAG_DISTANCE: f32 = 0.01;
+/// The time a press must be held still to be a long press.
+const LONG_PRESS_DURATION: Duration = Duration::from_millis(600);
+
+/// The modifier keys of a chord, in the order they are written.
+const MODIFIER_V: [&str; 4] = ["control", "alt", "shift", "super"];
+
+/// called => the result = the chord like `control+s` made by pressing the key while the held
+/// modifiers are down, or `None` if no modifier is held or the key is a modifier itself
+pub fn chord_of(key: &str, is_held: impl Fn(&str) -> bool) -> Option<String> {
+    if MODIFIER_V.contains(&key) {
+        return None;
+    }
+
+    let mut part_v = MODIFIER_V
+        .iter()
+        .filter(|modifier| is_held(modifier))
+        .copied()
+        .collect::<Vec<&str>>();
+    if part_v.is_empty() {
+        return None;
+    }
+    part_v.push(key);
+
+    Some(part_v.join("+"))
+}
+
+/// called => the result = the text typed by the key named `name`
+pub fn key_of(name: &str) -> String {
+    match name {
+        "space" => " ".to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// The raw inputs an action can be bound to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InputSource {
+    /// A key told by the text it types.
+    Key(String),
+    Mouse(String),
+    Gamepad(String),
+    GamepadAxis(String),
+}
+
+impl InputSource {
+    /// called => the result = the input and its value carried by the raw event, or `None` if the
+    /// event carries no input
+    pub fn from_event(entry_name: &str, data: &json::JsonValue) -> Option<(Self, f32)> {
+        let (source, value) = match entry_name {
+            "$onkeydown" => (InputSource::Key(data["$key"].as_str()?.to_string()), 1.0),
+            "$onkeyup" => (InputSource::Key(data["$key"].as_str()?.to_string()), 0.0),
+            "$onmousedown" => (
+                InputSource::Mouse(data["$button"].as_str()?.to_string()),
+                1.0,
+            ),
+            "$onmouseup" => (
+                InputSource::Mouse(data["$button"].as_str()?.to_string()),
+                0.0,
+            ),
+            "$ongamepaddown" => (
+                InputSource::Gamepad(data["$button"].as_str()?.to_string()),
+                1.0,
+            ),
+            "$ongamepadup" => (
+                InputSource::Gamepad(data["$button"].as_str()?.to_string()),
+                0.0,
+            ),
+            "$ongamepadaxis" => (
+                InputSource::GamepadAxis(data["$axis"].as_str()?.to_string()),
+                data["$value"].as_f32()?,
+            ),
+            _ => return None,
+        };
+
+        Some((source, value))
+    }
+}
+
+/// An input bound to an action, scaled before added to the value of the action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub source: InputSource,
+    pub scale: f32,
+}
+
+impl Binding {
+    /// called => the result = the binding told by the text like `key:d`, `key:a:-1`,
+    /// `mouse:left`, `gamepad:south` or `axis:left_x`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut part_iter = text.splitn(3, ':');
+        let device = part_iter.next()?;
+        let name = part_iter.next()?.to_string();
+        let scale = match part_iter.next() {
+            Some(scale) => scale.parse().ok()?,
+            None => 1.0,
+        };
+
+        let source = match device {
+            "key" => InputSource::Key(key_of(&name)),
+            "mouse" => InputSource::Mouse(name),
+            "gamepad" => InputSource::Gamepad(name),
+            "axis" => InputSource::GamepadAxis(name),
+            _ => return None,
+        };
+
+        Some(Self { source, scale })
+    }
+}
+
+/// Named actions driven by the inputs bound to them.
+///
+/// The value of an action = the sum of its scaled inputs, clamped to [-1, 1].
+pub struct ActionMap {
+    binding_mp: HashMap<String, Vec<Binding>>,
+    input_mp: HashMap<InputSource, f32>,
+    value_mp: HashMap<String, f32>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self {
+            binding_mp: HashMap::new(),
+            input_mp: HashMap::new(),
+            value_mp: HashMap::new(),
+        }
+    }
+
+    /// Let the action be driven by the bindings besides its old ones.
+    pub fn bind(&mut self, action: &str, binding_v: Vec<Binding>) {
+        self.binding_mp
+            .entry(action.to_string())
+            .or_default()
+            .extend(binding_v);
+    }
+
+    pub fn is_bound(&self, action: &str) -> bool {
+        self.binding_mp.contains_key(action)
+    }
+
+    /// Let the action be forgotten.
+    pub fn unbind(&mut self, action: &str) {
+        self.binding_mp.remove(action);
+        self.value_mp.remove(action);
+    }
+
+    pub fn value(&self, action: &str) -> f32 {
+        self.value_mp.get(action).copied().unwrap_or(0.0)
+    }
+
+    /// Let the input be at the value, the result = the actions changed by it with their new
+    /// values, or `None` if no action is bound to the input.
+    pub fn feed(&mut self, source: InputSource, value: f32) -> Option<Vec<(String, f32)>> {
+        let action_v = self
+            .binding_mp
+            .iter()
+            .filter(|(_, binding_v)| binding_v.iter().any(|binding| binding.source == source))
+            .map(|(action, _)| action.clone())
+            .collect::<Vec<String>>();
+        if action_v.is_empty() {
+            return None;
+        }
+
+        if value == 0.0 {
+            self.input_mp.remove(&source);
+        } else {
+            self.input_mp.insert(source, value);
+        }
+
+        let mut changed_v = Vec::new();
+        for action in action_v {
+            let value = self.binding_mp[&action]
+                .iter()
+                .map(|binding| {
+                    self.input_mp.get(&binding.source).copied().unwrap_or(0.0) * binding.scale
+                })
+                .sum::<f32>()
+                .clamp(-1.0, 1.0);
+
+            if self.value(&action) != value {
+                self.value_mp.insert(action.clone(), value);
+                changed_v.push((action, value));
+            }
+        }
+
+        Some(changed_v)
+    }
+}
+
+/// Let held keys repeat after a delay at a rate, once configured to.
+pub struct KeyRepeater {
+    delay: Duration,
+    /// The time between two repeats, or `None` if keys do not repeat.
+    interval_op: Option<Duration>,
+    /// When each held key repeats next.
+    next_mp: HashMap<String, Instant>,
+}
+
+impl KeyRepeater {
+    pub fn new() -> Self {
+        Self {
+            delay: Duration::from_millis(500),
+            interval_op: None,
+            next_mp: HashMap::new(),
+        }
+    }
+
+    /// Let keys repeat `rate` times a second after held for the delay, or never if `rate` is not
+    /// positive and finite or too low for an interval.
+    pub fn configure(&mut self, delay: Duration, rate: f32) {
+        self.delay = delay;
+        self.interval_op = if rate > 0.0 && rate.is_finite() {
+            Duration::try_from_secs_f32(1.0 / rate).ok()
+        } else {
+            None
+        };
+    }
+
+    /// called => whether keys repeat, in place of the repeats of the OS
+    pub fn is_enabled(&self) -> bool {
+        self.interval_op.is_some()
+    }
+
+    pub fn press(&mut self, key: &str, now: Instant) {
+        self.next_mp
+            .entry(key.to_string())
+            .or_insert(now + self.delay);
+    }
+
+    pub fn release(&mut self, key: &str) {
+        self.next_mp.remove(key);
+    }
+
+    /// called => the result = the keys repeating until now
+    pub fn poll(&mut self, now: Instant) -> Vec<String> {
+        let interval = match self.interval_op {
+            Some(interval) => interval,
+            None => return Vec::new(),
+        };
+
+        let mut key_v = Vec::new();
+        for (key, next) in &mut self.next_mp {
+            if *next <= now {
+                key_v.push(key.clone());
+                // A late poll repeats once rather than catching up.
+                *next = (*next + interval).max(now);
+            }
+        }
+
+        key_v
+    }
+}
+
+/// called => whether the pointer is a finger, named `touch` and its id
+fn is_touch(pointer: &str) -> bool {
+    pointer.starts_with("touch")
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+fn gesture(entry_name: &'static str, pointer: &str, pos: [f32; 2], delta: [f32; 2]) -> Gesture {
+    (
+        entry_name,
+        json::object! {
+            "$pointer": pointer,
+            "$x": pos[0],
+            "$y": pos[1],
+            "$dx": delta[0],
+            "$dy": delta[1],
+        },
+    )
+}
+
+/// The entry name and data of a gesture event.
+pub type Gesture = (&'static str, json::JsonValue);
+
+struct Press {
+    pointer: String,
+    start: Instant,
+    start_pos: [f32; 2],
+    pos: [f32; 2],
+    is_dragging: bool,
+    is_long_pressed: bool,
+}
+
+/// Let raw presses and moves of a pointer be made into gestures.
+///
+/// A pointer is a mouse button or a touch, only one of which is followed at a time.
+pub struct GestureDetector {
+    press_op: Option<Press>,
+    /// The pointer, time and position of the last click.
+    last_click_op: Option<(String, Instant, [f32; 2])>,
+}
+
+impl GestureDetector {
+    pub fn new() -> Self {
+        Self {
+            press_op: None,
+            last_click_op: None,
+        }
+    }
+
+    /// Let the pointer be pressed at the position.
+    pub fn press(&mut self, pointer: &str, pos: [f32; 2], now: Instant) {
+        if self.press_op.is_some() {
+            return;
+        }
+
+        self.press_op = Some(Press {
+            pointer: pointer.to_string(),
+            start: now,
+            start_pos: pos,
+            pos,
+            is_dragging: false,
+            is_long_pressed: false,
+        });
+    }
+
+    /// Let the pressed pointer be moved to the position, the result = the gestures made.
+    ///
+    /// The move of a pointer not pressed, like a second finger, is ignored; the mouse moves any
+    /// of its buttons pressed.
+    pub fn move_to(&mut self, pointer: &str, pos: [f32; 2]) -> Vec<Gesture> {
+        let press = match &mut self.press_op {
+            Some(press)
+                if press.pointer == pointer || !is_touch(pointer) && !is_touch(&press.pointer) =>
+            {
+                press
+            }
+            _ => return Vec::new(),
+        };
+
+        let mut gesture_v = Vec::new();
+        if !press.is_dragging {
+            if distance(pos, press.start_pos) < DRAG_DISTANCE {
+                return gesture_v;
+            }
+
+            press.is_dragging = true;
+            gesture_v.push(gesture(
+                "$ondragstart",
+                &press.pointer,
+                press.start_pos,
+                [0.0, 0.0],
+            ));
+        }
+
+        gesture_v.push(gesture(
+            "$ondragmove",
+            &press.pointer,
+            pos,
+            [pos[0] - press.pos[0], pos[1] - press.pos[1]],
+        ));
+        press.pos = pos;
+
+        gesture_v
+    }
+
+    /// Let the pointer be released at the position, the result = the gestures made.
+    pub fn release(&mut self, pointer: &str, pos: [f32; 2], now: Instant) -> Vec<Gesture> {
+        let press = match self.press_op.take() {
+            Some(press) if press.pointer == pointer => press,
+            press_op => {
+                self.press_op = press_op;
+
+                return Vec::new();
+            }
+        };
+
+        if press.is_dragging {
+            return vec![gesture(
+                "$ondragend",
+                pointer,
+                pos,
+                [pos[0] - press.start_pos[0], pos[1] - press.start_pos[1]],
+            )];
+        }
+        if press.is_long_pressed {
+            return Vec::new();
+        }
+
+        match self.last_click_op.take() {
+            Some((last_pointer, last, last_pos))
+                if last_pointer == pointer
+                    && now - last <= DOUBLE_CLICK_INTERVAL
+                    && distance(pos, last_pos) <= DOUBLE_CLICK_DISTANCE =>
+            {
+                vec![gesture("$ondblclick", pointer, pos, [0.0, 0.0])]
+            }
+            _ => {
+                self.last_click_op = Some((pointer.to_string(), now, pos));
+
+                Vec::new()
+            }
+        }
+    }
+
+    /// called => the result = the gestures made by holding the pointer until now
+    pub fn poll(&mut self, now: Instant) -> Vec<Gesture> {
+        match &mut self.press_op {
+            Some(press)
+                if !press.is_dragging
+                    && !press.is_long_pressed
+                    && now - press.start >= LONG_PRESS_DURATION =>
+            {
+                press.is_long_pressed = true;
+
+                vec![gesture(
+                    "$onlongpress",
+                    &press.pointer,
+                    press.pos,
+                    [0.0, 0.0],
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{chord_of, ActionMap, Binding, GestureDetector, InputSource, KeyRepeater};
+
+    #[test]
+    fn test_chord_of() {
+        let held = |key: &str| key == "control" || key == "shift";
+
+        assert_eq!(chord_of("s", held), Some("control+shift+s".to_string()));
+        assert_eq!(chord_of("control", held), None);
+        assert_eq!(chord_of("s", |_| false), None);
+    }
+
+    #[test]
+    fn test_key_repeater() {
+        let now = Instant::now();
+        let mut key_repeater = KeyRepeater::new();
+        assert!(!key_repeater.is_enabled());
+        key_repeater.configure(Duration::from_millis(500), 10.0);
+
+        key_repeater.press("w", now);
+        assert!(key_repeater.poll(now).is_empty());
+        assert_eq!(
+            key_repeater.poll(now + Duration::from_millis(500)),
+            vec!["w"]
+        );
+        assert!(key_repeater
+            .poll(now + Duration::from_millis(550))
+            .is_empty());
+
+        key_repeater.release("w");
+        assert!(key_repeater.poll(now + Duration::from_secs(1)).is_empty());
+
+        key_repeater.configure(Duration::ZERO, f32::INFINITY);
+        key_repeater.press("w", now);
+        assert!(key_repeater.poll(now + Duration::from_secs(1)).is_empty());
+    }
+
+    #[test]
+    fn test_gesture() {
+        let now = Instant::now();
+        let mut detector = GestureDetector::new();
+
+        detector.press("left", [0.5, 0.5], now);
+        assert!(detector.release("left", [0.5, 0.5], now).is_empty());
+        detector.press("left", [0.5, 0.5], now);
+        let gesture_v = detector.release("left", [0.5, 0.5], now + Duration::from_millis(100));
+        assert_eq!(gesture_v[0].0, "$ondblclick");
+
+        detector.press("left", [0.5, 0.5], now);
+        let gesture_v = detector.move_to("", [0.6, 0.5]);
+        assert_eq!(gesture_v[0].0, "$ondragstart");
+        assert_eq!(gesture_v[1].0, "$ondragmove");
+        assert_eq!(detector.release("left", [0.6, 0.5], now)[0].0, "$ondragend");
+
+        // The second finger does not drag the first.
+        detector.press("touch0", [0.5, 0.5], now);
+        assert!(detector.move_to("touch1", [0.9, 0.5]).is_empty());
+        assert!(detector.release("touch1", [0.9, 0.5], now).is_empty());
+        assert_eq!(detector.move_to("touch0", [0.6, 0.5])[0].0, "$ondragstart");
+        assert_eq!(
+            detector.release("touch0", [0.6, 0.5], now)[0].0,
+            "$ondragend"
+        );
+
+        detector.press("touch0", [0.5, 0.5], now);
+        assert!(detector.poll(now).is_empty());
+        assert_eq!(
+            detector.poll(now + Duration::from_secs(1))[0].0,
+            "$onlongpress"
+        );
+        assert!(detector.release("touch0", [0.5, 0.5], now).is_empty());
+    }
+
+    #[test]
+    fn test_action_map() {
+        let mut action_map = ActionMap::new();
+        action_map.bind(
+            "move_x",
+            vec![
+                Binding::parse("key:d").unwrap(),
+                Binding::parse("key:a:-1").unwrap(),
+            ],
+        );
+
+        assert_eq!(
+            action_map.feed(InputSource::Key("a".to_string()), 1.0),
+            Some(vec![("move_x".to_string(), -1.0)])
+        );
+        assert_eq!(
+            action_map.feed(InputSource::Key("d".to_string()), 1.0),
+            Some(vec![("move_x".to_string(), 0.0)])
+        );
+        assert_eq!(
+            action_map.feed(InputSource::Key("a".to_string()), 0.0),
+            Some(vec![("move_x".to_string(), 1.0)])
+        );
+        assert_eq!(
+            action_map.feed(InputSource::Key("w".to_string()), 1.0),
+            None
+        );
+    }
+}
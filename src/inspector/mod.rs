@@ -0,0 +1,132 @@
+//! Help the vnode tree be inspected while the engine runs.
+
+use std::collections::HashMap;
+
+use view_manager::VNode;
+
+use super::AtomElement;
+
+/// The key toggling the inspector.
+pub const TOGGLE_KEY: &str = "f12";
+
+/// The lines of the inspector drawn over the window, the rest being counted in the last.
+const MAX_LINE_COUNT: usize = 40;
+
+/// The characters of a line drawn over the window.
+const MAX_LINE_LEN: usize = 96;
+
+/// called => the result = the kind of the element
+pub fn kind_of(atom_element_op: Option<&AtomElement>) -> &'static str {
+    match atom_element_op {
+        #[cfg(feature = "audio")]
+        Some(AtomElement::Audio(_)) => "audio",
+        #[cfg(feature = "physics")]
+        Some(AtomElement::Physics(_)) => "physics",
+        Some(AtomElement::Vision(_)) => "vision",
+        Some(AtomElement::Input(_)) => "input",
+        None => "-",
+    }
+}
+
+/// called => the result = a line per vnode under the roots, depth first and indented by depth,
+/// with its id, class, element kind and props
+pub fn line_v(
+    vnode_mp: &HashMap<u64, VNode>,
+    element_mp: &HashMap<u64, AtomElement>,
+    root_v: &[u64],
+) -> Vec<String> {
+    let mut line_v = Vec::new();
+    let mut stack = root_v
+        .iter()
+        .rev()
+        .map(|root| (*root, 0))
+        .collect::<Vec<(u64, usize)>>();
+
+    while let Some((id, depth)) = stack.pop() {
+        let vnode = match vnode_mp.get(&id) {
+            Some(vnode) => vnode,
+            None => continue,
+        };
+
+        line_v.push(format!(
+            "{}{id} {} [{}] {}",
+            "  ".repeat(depth),
+            vnode.view_props.class,
+            kind_of(element_mp.get(&id)),
+            vnode.view_props.props.dump()
+        ));
+
+        for child in vnode.embeded_child_v.iter().rev() {
+            stack.push((*child, depth + 1));
+        }
+        if vnode.inner_node.data != 0 {
+            stack.push((vnode.inner_node.data, depth + 1));
+        }
+    }
+
+    line_v
+}
+
+/// called => the result = the lines cut to be drawn over the window, no more than
+/// [MAX_LINE_COUNT] lines of [MAX_LINE_LEN] characters
+pub fn overlay_line_v(line_v: &[String]) -> Vec<String> {
+    let mut overlay_line_v = line_v
+        .iter()
+        .take(MAX_LINE_COUNT)
+        .map(|line| match line.char_indices().nth(MAX_LINE_LEN - 3) {
+            Some((end, _)) if line.chars().count() > MAX_LINE_LEN => format!("{}...", &line[..end]),
+            _ => line.clone(),
+        })
+        .collect::<Vec<String>>();
+
+    if line_v.len() > MAX_LINE_COUNT {
+        overlay_line_v.pop();
+        overlay_line_v.push(format!("... {} more", line_v.len() - MAX_LINE_COUNT + 1));
+    }
+
+    overlay_line_v
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use view_manager::VNode;
+
+    use crate::AtomElement;
+
+    #[test]
+    fn test_line_v() {
+        let mut root = VNode::new(0);
+        root.view_props.class = "Main".to_string();
+        root.embeded_child_v = vec![1, 2];
+        let mut cube = VNode::new(0);
+        cube.view_props.class = "Vision:cube3".to_string();
+        cube.view_props.props = json::object! { "$color": [1, 0, 0] };
+
+        let vnode_mp = HashMap::from([(0, root), (1, cube), (2, VNode::new(0))]);
+        let element_mp = HashMap::from([(1, AtomElement::Vision(1))]);
+
+        let line_v = super::line_v(&vnode_mp, &element_mp, &[0]);
+
+        assert_eq!(line_v.len(), 3);
+        assert!(line_v[0].starts_with("0 Main [-]"));
+        assert_eq!(line_v[1], r#"  1 Vision:cube3 [vision] {"$color":[1,0,0]}"#);
+        assert!(line_v[2].starts_with("  2 "));
+    }
+
+    #[test]
+    fn test_overlay_line_v() {
+        let line_v = (0..50)
+            .map(|i| i.to_string().repeat(60))
+            .collect::<Vec<String>>();
+
+        let overlay_line_v = super::overlay_line_v(&line_v);
+
+        assert_eq!(overlay_line_v.len(), super::MAX_LINE_COUNT);
+        assert_eq!(overlay_line_v[0], "0".repeat(60));
+        assert_eq!(overlay_line_v[10].chars().count(), super::MAX_LINE_LEN);
+        assert!(overlay_line_v[10].ends_with("..."));
+        assert_eq!(overlay_line_v.last().unwrap(), "... 11 more");
+    }
+}
@@ -1,27 +1,121 @@
 //! imported => [Engine] = avaliable to render
 
+use drawer::{camera::SAFE_FRAC_PI_2, Ambient, ThreeLook};
 use error_stack::ResultExt;
-use moon_class::{util::rs_2_str, AsClassManager, Fu};
+use moon_class::{util::executor::ClassExecutor, AsClassManager, Fu};
+#[cfg(feature = "physics")]
+use nalgebra::Point3;
+use nalgebra::{point, vector, UnitQuaternion, Vector3};
+#[cfg(feature = "physics")]
 use rapier3d::prelude::{IntegrationParameters, RigidBodyHandle};
 use view_manager::{AsElementProvider, AsViewManager, VNode, ViewProps};
 
-use std::{collections::HashMap, pin::Pin};
+#[cfg(feature = "physics")]
+use std::num::NonZeroUsize;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::Path,
+    pin::Pin,
+    sync::{mpsc::Sender, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 use wgpu::{Instance, Surface};
 
 use winit::{dpi::PhysicalSize, window::Window};
 
+use crate::{
+    dm::middleware::{DmMiddleware, WriteOp},
+    util::{cadence::Cadence, prop},
+};
+
+mod asset;
+#[cfg(feature = "audio")]
+mod audio;
+mod auth;
+mod console;
+mod event;
+mod gltf;
+mod input;
+mod inspector;
+#[cfg(feature = "physics")]
 mod physics;
 mod res;
+mod simulation;
+mod snapshot;
+mod tree;
+mod world;
 mod inner {
     use std::collections::HashMap;
 
+    use winit::dpi::PhysicalSize;
+
     use error_stack::ResultExt;
+    use moon_class::util::rs_2_str;
     use view_manager::VNode;
 
-    use crate::err;
+    use crate::{err, util::prop};
 
     use super::{res::RenderPass, AtomElement};
 
+    /// called => the result = the configuration the surface is configured with
+    pub fn surface_config_of(
+        surface: &wgpu::Surface,
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+    ) -> err::Result<wgpu::SurfaceConfiguration> {
+        let surface_caps = surface.get_capabilities(adapter);
+
+        // Shader code in this tutorial assumes an sRGB surface texture. Using a different
+        // one will result all the colors coming out darker. If you want to support non
+        // sRGB surfaces, you'll need to account for that when drawing to the frame.
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .filter(|f| f.is_srgb())
+            .next()
+            .ok_or(err::Error::Gpu)?;
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(device, &config);
+
+        Ok(config)
+    }
+
+    /// called => the result = the size of `{"$width", "$height"}` in the items
+    pub fn size_of(item_v: &Vec<String>) -> moon_class::err::Result<PhysicalSize<u32>> {
+        let data = object_of(item_v)?;
+
+        Ok(PhysicalSize::new(
+            number_of(&data, "$width")? as u32,
+            number_of(&data, "$height")? as u32,
+        ))
+    }
+
+    /// called => the result = the JSON object dumped in the items
+    pub fn object_of(item_v: &Vec<String>) -> moon_class::err::Result<json::JsonValue> {
+        json::parse(&rs_2_str(item_v))
+            .change_context(moon_class::err::Error::NotFound)
+            .attach_printable_lazy(|| format!("{item_v:?} is not a JSON object"))
+    }
+
+    /// called => the result = the number of the key in the data
+    pub fn number_of(data: &json::JsonValue, key: &str) -> moon_class::err::Result<f32> {
+        prop::number(data, key)
+            .ok_or(moon_class::err::Error::NotFound)
+            .attach_printable_lazy(|| format!("no number for {key} in {data}"))
+    }
+
     /// Let vnode be rendered.
     pub fn render_vnode(
         vnode_mp: &HashMap<u64, VNode>,
@@ -29,7 +123,10 @@ mod inner {
         rp: &mut RenderPass,
         vnode_id: u64,
     ) -> err::Result<()> {
-        let vnode = vnode_mp.get(&vnode_id).unwrap();
+        let vnode = vnode_mp
+            .get(&vnode_id)
+            .ok_or(err::Error::NotFound)
+            .attach_printable_lazy(|| format!("vnode {vnode_id} not found"))?;
         if vnode.inner_node.data != 0 {
             // Let virtual container be rendered.
             render_vnode(vnode_mp, element_mp, rp, vnode.inner_node.data)
@@ -42,16 +139,9 @@ mod inner {
                     }
                 }
                 _ => {
-                    let ele = element_mp
-                        .get(&vnode_id)
-                        .ok_or(err::Error::NotFound)
-                        .attach_printable("element with specified vnode_id not found!")?;
-                    match ele {
-                        super::AtomElement::Audio(_) => (),
-                        super::AtomElement::Vision(id) => {
-                            rp.push_element(*id);
-                        }
-                        _ => (),
+                    // A vnode skipped for malformed props has no element.
+                    if let Some(super::AtomElement::Vision(id)) = element_mp.get(&vnode_id) {
+                        rp.push_element(*id);
                     }
                 }
             }
@@ -61,29 +151,112 @@ mod inner {
     }
 }
 mod camera {
+    use std::{
+        f32::consts::TAU,
+        time::{Duration, Instant},
+    };
+
     use drawer::camera::{CameraState, SAFE_FRAC_PI_2};
-    use nalgebra::Vector3;
+    use nalgebra::{Point3, Vector3};
+
+    /// The closest the orbit camera gets to its target.
+    const MIN_ORBIT_DISTANCE: f32 = 0.1;
+
+    #[derive(Debug)]
+    pub enum CameraMode {
+        /// The camera flies freely.
+        Fly,
+        /// The camera circles around the target, always looking at it.
+        Orbit {
+            target: Point3<f32>,
+            distance: f32,
+            azimuth: f32,
+            elevation: f32,
+        },
+        /// The camera sits in the tracked body, looking around with the mouse.
+        FirstPerson {
+            /// How high the eyes are above the center of the body.
+            eye_height: f32,
+        },
+        /// The camera chases the tracked body, looking at where it heads.
+        Follow {
+            offset: Vector3<f32>,
+            /// How many seconds ahead of the body along its velocity to look.
+            look_ahead: f32,
+            /// How fast the camera catches up, in 1/s.
+            smoothing: f32,
+        },
+    }
+
+    /// A shake fading out over its duration.
+    #[derive(Debug)]
+    struct Shake {
+        amplitude: f32,
+        /// How many times a second the camera jolts.
+        frequency: f32,
+        start: Instant,
+        duration: Duration,
+    }
+
+    impl Shake {
+        /// called => the result = the offset of the camera at the moment, or `None` if the shake
+        /// is over
+        fn offset(&self, now: Instant) -> Option<Vector3<f32>> {
+            let elapsed = now - self.start;
+            if elapsed >= self.duration {
+                return None;
+            }
+
+            let elapsed = elapsed.as_secs_f32();
+            let decay = (1.0 - elapsed / self.duration.as_secs_f32()).powi(2);
+            let phase = elapsed * self.frequency * TAU;
+            // Two sines of unrelated periods per axis make a cheap noise.
+            let noise = |seed: f32| (phase + seed).sin() * (phase * 0.37 + seed * 2.3).sin();
+
+            Some(Vector3::new(noise(0.0), noise(1.7), noise(4.1)) * self.amplitude * decay)
+        }
+    }
 
     #[derive(Debug)]
     pub struct CameraController {
+        mode: CameraMode,
+        /// The position and velocity of the body to follow.
+        track_op: Option<(Point3<f32>, Vector3<f32>)>,
+        last_update_op: Option<Instant>,
+        shake_op: Option<Shake>,
+        /// The offset of the shake applied at the last update.
+        shake_offset: Vector3<f32>,
         amount_x: f32,
         amount_y: f32,
         amount_z: f32,
         rotate_horizontal: f32,
         rotate_vertical: f32,
+        rotate_roll: f32,
         sensitivity: f32,
+        /// Whether moving the mouse up looks down.
+        invert_y: bool,
+        /// The scale of the translation of the fly camera.
+        speed: f32,
         scroll: f32,
     }
 
     impl CameraController {
         pub fn new(sensitivity: f32) -> Self {
             Self {
+                mode: CameraMode::Fly,
+                track_op: None,
+                last_update_op: None,
+                shake_op: None,
+                shake_offset: Vector3::zeros(),
                 amount_x: 0.0,
                 amount_y: 0.0,
                 amount_z: 0.0,
                 rotate_horizontal: 0.0,
                 rotate_vertical: 0.0,
+                rotate_roll: 0.0,
                 sensitivity,
+                invert_y: false,
+                speed: 1.0,
                 scroll: 0.0,
             }
         }
@@ -108,59 +281,285 @@ mod camera {
 
         pub fn rorate(&mut self, mouse_dx: f32, mouse_dy: f32) {
             self.rotate_horizontal += -mouse_dy;
-            self.rotate_vertical += mouse_dx;
+            if self.invert_y {
+                self.rotate_vertical -= mouse_dx;
+            } else {
+                self.rotate_vertical += mouse_dx;
+            }
+        }
+
+        pub fn sensitivity(&self) -> f32 {
+            self.sensitivity
+        }
+
+        pub fn set_sensitivity(&mut self, sensitivity: f32) {
+            self.sensitivity = sensitivity;
+        }
+
+        pub fn is_y_inverted(&self) -> bool {
+            self.invert_y
+        }
+
+        pub fn set_y_inverted(&mut self, invert_y: bool) {
+            self.invert_y = invert_y;
+        }
+
+        pub fn speed(&self) -> f32 {
+            self.speed
+        }
+
+        pub fn set_speed(&mut self, speed: f32) {
+            self.speed = speed;
+        }
+
+        /// Let the fly camera bank rightward by the amount, or leftward if it is negative.
+        pub fn roll(&mut self, amount: f32) {
+            self.rotate_roll += amount;
+        }
+
+        /// Let the camera be zoomed in by the amount, or out if it is negative.
+        pub fn scroll(&mut self, amount: f32) {
+            self.scroll += amount;
+        }
+
+        /// Let the camera fly freely from where it is.
+        pub fn fly(&mut self) {
+            self.mode = CameraMode::Fly;
+        }
+
+        /// Let the camera circle around the target from where it is, or at the distance if
+        /// given.
+        pub fn orbit(
+            &mut self,
+            target: Point3<f32>,
+            distance_op: Option<f32>,
+            camera_state: &CameraState,
+        ) {
+            let offset = camera_state.position() - target;
+            let distance = distance_op.unwrap_or(offset.norm()).max(MIN_ORBIT_DISTANCE);
+
+            self.mode = CameraMode::Orbit {
+                target,
+                distance,
+                azimuth: offset.x.atan2(offset.z),
+                elevation: (offset.y / offset.norm().max(f32::EPSILON))
+                    .clamp(-1.0, 1.0)
+                    .asin(),
+            };
+        }
+
+        /// Let the camera sit in the tracked body with the eyes at the height.
+        #[cfg_attr(not(feature = "physics"), allow(dead_code))]
+        pub fn first_person(&mut self, eye_height: f32) {
+            self.mode = CameraMode::FirstPerson { eye_height };
+        }
+
+        /// Let the camera chase the tracked body from the offset.
+        pub fn follow(&mut self, offset: Vector3<f32>, look_ahead: f32, smoothing: f32) {
+            self.mode = CameraMode::Follow {
+                offset,
+                look_ahead,
+                smoothing,
+            };
+        }
+
+        /// called => the result = the point the camera turns around in orbit or follow mode
+        #[cfg_attr(not(feature = "physics"), allow(dead_code))]
+        pub fn pivot_op(&self) -> Option<Point3<f32>> {
+            match self.mode {
+                CameraMode::Fly | CameraMode::FirstPerson { .. } => None,
+                CameraMode::Orbit { target, .. } => Some(target),
+                CameraMode::Follow { .. } => self.track_op.map(|(position, _)| position),
+            }
+        }
+
+        /// Let the body to follow be at the position with the velocity, or be gone if
+        /// `track_op` is `None`.
+        #[cfg_attr(not(feature = "physics"), allow(dead_code))]
+        pub fn track(&mut self, track_op: Option<(Point3<f32>, Vector3<f32>)>) {
+            self.track_op = track_op;
+        }
+
+        /// Let the camera shake by the amplitude, fading out over the duration.
+        pub fn shake(&mut self, amplitude: f32, frequency: f32, duration: Duration) {
+            self.shake_op = Some(Shake {
+                amplitude,
+                frequency,
+                start: Instant::now(),
+                duration,
+            });
         }
 
         pub fn update_camera(&mut self, camera_state: &mut CameraState) {
+            let now = Instant::now();
+            let dt = self
+                .last_update_op
+                .map(|last_update| (now - last_update).as_secs_f32())
+                .unwrap_or(0.0);
+            self.last_update_op = Some(now);
+
+            // Let the modes work on the camera without the shake.
+            *camera_state.position_mut() -= self.shake_offset;
+
+            match self.mode {
+                CameraMode::Fly => self.update_fly(camera_state),
+                CameraMode::Orbit { .. } => self.update_orbit(camera_state),
+                CameraMode::Follow { .. } => self.update_follow(camera_state, dt),
+                CameraMode::FirstPerson { eye_height } => {
+                    self.update_first_person(camera_state, eye_height)
+                }
+            }
+
+            self.shake_offset = match self.shake_op.as_ref().and_then(|shake| shake.offset(now)) {
+                Some(offset) => offset,
+                None => {
+                    self.shake_op = None;
+
+                    Vector3::zeros()
+                }
+            };
+            *camera_state.position_mut() += self.shake_offset;
+
+            // If process_mouse isn't called every frame, these values
+            // will not get set to zero, and the camera will rotate
+            // when moving in a non cardinal direction.
+            self.rotate_horizontal = 0.0;
+            self.rotate_vertical = 0.0;
+            self.rotate_roll = 0.0;
+            self.scroll = 0.0;
+        }
+
+        /// Let the camera circle around the target, the moves pan the target.
+        fn update_orbit(&mut self, camera_state: &mut CameraState) {
+            let (yaw_sin, yaw_cos) = camera_state.yaw().sin_cos();
+            let forward = Vector3::new(yaw_sin, 0.0, yaw_cos).normalize();
+            let right = Vector3::new(yaw_cos, 0.0, -yaw_sin).normalize();
+
+            if let CameraMode::Orbit {
+                target,
+                distance,
+                azimuth,
+                elevation,
+            } = &mut self.mode
+            {
+                *target += forward * self.amount_z + right * self.amount_x;
+                target.y += self.amount_y;
+
+                *distance = (*distance - self.scroll * self.sensitivity).max(MIN_ORBIT_DISTANCE);
+                *azimuth += self.rotate_horizontal * self.sensitivity;
+                *elevation = (*elevation + self.rotate_vertical * self.sensitivity)
+                    .clamp(-SAFE_FRAC_PI_2, SAFE_FRAC_PI_2);
+
+                let (azimuth_sin, azimuth_cos) = azimuth.sin_cos();
+                let (elevation_sin, elevation_cos) = elevation.sin_cos();
+                *camera_state.position_mut() = *target
+                    + Vector3::new(
+                        elevation_cos * azimuth_sin,
+                        elevation_sin,
+                        elevation_cos * azimuth_cos,
+                    ) * *distance;
+
+                // Look back at the target, see [CameraState::calc_matrix].
+                *camera_state.yaw_mut() = *azimuth;
+                *camera_state.pitch_mut() = -*elevation;
+            }
+        }
+
+        fn update_first_person(&mut self, camera_state: &mut CameraState, eye_height: f32) {
+            if let Some((position, _)) = self.track_op {
+                *camera_state.position_mut() = position + Vector3::new(0.0, eye_height, 0.0);
+            }
+
+            *camera_state.yaw_mut() += self.rotate_horizontal * self.sensitivity;
+            *camera_state.pitch_mut() = (camera_state.pitch()
+                - self.rotate_vertical * self.sensitivity)
+                .clamp(-SAFE_FRAC_PI_2, SAFE_FRAC_PI_2);
+        }
+
+        /// Let the camera ease toward the offset from the tracked body.
+        fn update_follow(&mut self, camera_state: &mut CameraState, dt: f32) {
+            let (offset, look_ahead, smoothing) = match self.mode {
+                CameraMode::Follow {
+                    offset,
+                    look_ahead,
+                    smoothing,
+                } => (offset, look_ahead, smoothing),
+                _ => return,
+            };
+            let (position, velocity) = match self.track_op {
+                Some(track) => track,
+                None => return,
+            };
+
+            let look_at = position + velocity * look_ahead;
+            // Exponential smoothing, the same whatever the step rate is.
+            let t = 1.0 - (-smoothing * dt).exp();
+            let camera_position =
+                camera_state.position() + (look_at + offset - camera_state.position()) * t;
+            *camera_state.position_mut() = camera_position;
+
+            camera_state.look_at(&look_at);
+        }
+
+        fn update_fly(&mut self, camera_state: &mut CameraState) {
             // Move forward/backward and left/right
             let (yaw_sin, yaw_cos) = camera_state.yaw().sin_cos();
             let forward = Vector3::new(yaw_sin, 0.0, yaw_cos).normalize();
             let right = Vector3::new(yaw_cos, 0.0, -yaw_sin).normalize();
 
-            *camera_state.position_mut() += forward * self.amount_z;
-            *camera_state.position_mut() += right * self.amount_x;
-            // Move up/down. Since we don't use roll, we can just
+            *camera_state.position_mut() += forward * self.amount_z * self.speed;
+            *camera_state.position_mut() += right * self.amount_x * self.speed;
+            // Move up/down. Whatever the roll, we can just
             // modify the y coordinate directly.
-            camera_state.position_mut().y += self.amount_y;
+            camera_state.position_mut().y += self.amount_y * self.speed;
 
             // Move in/out (aka. "zoom")
             // Note: this isn't an actual zoom. The camera's position
             // changes when zooming. I've added this to make it easier
             // to get closer to an object you want to focus on.
-            let (pitch_sin, pitch_cos) = camera_state.pitch().sin_cos();
-            let scrollward =
-                Vector3::new(pitch_cos * yaw_cos, pitch_sin, pitch_cos * yaw_sin).normalize();
+            let scrollward = camera_state.forward();
             *camera_state.position_mut() += scrollward * self.scroll * self.sensitivity;
-            self.scroll = 0.0;
 
             // Rotate
             *camera_state.yaw_mut() += self.rotate_horizontal * self.sensitivity;
-            *camera_state.pitch_mut() += -self.rotate_vertical * self.sensitivity;
-
-            // If process_mouse isn't called every frame, these values
-            // will not get set to zero, and the camera will rotate
-            // when moving in a non cardinal direction.
-            self.rotate_horizontal = 0.0;
-            self.rotate_vertical = 0.0;
-            // Keep the camera's angle from going too high/low.
-            if camera_state.pitch() < -SAFE_FRAC_PI_2 {
-                *camera_state.pitch_mut() = -SAFE_FRAC_PI_2;
-            } else if camera_state.pitch() > SAFE_FRAC_PI_2 {
-                *camera_state.pitch_mut() = SAFE_FRAC_PI_2;
-            }
+            // Keep the camera's angle from going too high/low, before it is written, as a pitch
+            // over the top reads back as a turn around.
+            *camera_state.pitch_mut() = (camera_state.pitch()
+                - self.rotate_vertical * self.sensitivity)
+                .clamp(-SAFE_FRAC_PI_2, SAFE_FRAC_PI_2);
+            *camera_state.roll_mut() += self.rotate_roll * self.sensitivity;
         }
     }
 }
 
 pub mod dep;
+pub mod dm;
 pub mod err;
+pub mod net;
+pub mod replay;
+pub mod runner;
+pub mod stats;
 pub mod util;
+pub mod views;
+
+pub use auth::Auth;
+pub use event::EngineEvent;
+pub use simulation::SimulationEngine;
+pub use snapshot::EngineSnapshot;
 
 /// built => the result = a new [Engine]
 pub struct EngineBuilder {
+    window: &'static Window,
     instance: Instance,
     surface: Surface<'static>,
     size: PhysicalSize<u32>,
+    #[cfg(feature = "audio")]
+    audio_device_op: Option<String>,
+    perspective: res::Perspective,
+    #[cfg(feature = "physics")]
+    integration_parameters: IntegrationParameters,
+    /// The windows other than the main one, with the entries of their views.
+    window_v: Vec<(Surface<'static>, PhysicalSize<u32>, ViewProps)>,
 }
 
 impl EngineBuilder {
@@ -175,15 +574,96 @@ impl EngineBuilder {
 
         let surface = instance
             .create_surface(window)
-            .change_context(err::Error::Other)?;
+            .change_context(err::Error::Gpu)?;
 
         Ok(Self {
+            window,
             instance,
             surface,
             size,
+            #[cfg(feature = "audio")]
+            audio_device_op: None,
+            perspective: res::Perspective::default(),
+            #[cfg(feature = "physics")]
+            integration_parameters: IntegrationParameters::default(),
+            window_v: Vec::new(),
         })
     }
 
+    /// Let the camera see with the vertical field of view in degrees, between the near and far
+    /// planes.
+    pub fn with_projection(mut self, fov: f32, znear: f32, zfar: f32) -> Self {
+        self.perspective = res::Perspective {
+            fovy: fov.to_radians(),
+            znear,
+            zfar,
+        };
+        self
+    }
+
+    /// Let each physics step advance the world by `dt` seconds, unless a physics frequency is
+    /// set by [Engine::set_frequency].
+    #[cfg(feature = "physics")]
+    pub fn with_physics_dt(mut self, dt: f32) -> Self {
+        if dt > 0.0 {
+            self.integration_parameters.dt = dt;
+        }
+        self
+    }
+
+    /// Let the solver iterate `count` times a step, steadier stacks for more time.
+    #[cfg(feature = "physics")]
+    pub fn with_solver_iterations(mut self, count: usize) -> Self {
+        if let Some(count) = NonZeroUsize::new(count) {
+            self.integration_parameters.num_solver_iterations = count;
+        }
+        self
+    }
+
+    /// Let the contacts be damped by the ratio.
+    #[cfg(feature = "physics")]
+    pub fn with_contact_damping(mut self, ratio: f32) -> Self {
+        self.integration_parameters.contact_damping_ratio = ratio;
+        self
+    }
+
+    /// Let the bodies sink into each other by the length in meters before being pushed apart,
+    /// less jitter for less accuracy.
+    #[cfg(feature = "physics")]
+    pub fn with_allowed_penetration(mut self, length: f32) -> Self {
+        self.integration_parameters.normalized_allowed_linear_error =
+            length / self.integration_parameters.length_unit;
+        self
+    }
+
+    /// called => the result = the names of the audio output devices to choose from
+    #[cfg(feature = "audio")]
+    pub fn audio_device_name_v() -> Vec<String> {
+        audio::output_device_name_v()
+    }
+
+    /// Let the audio be played on the output device named `name` rather than the default one.
+    #[cfg(feature = "audio")]
+    pub fn with_audio_device(mut self, name: &str) -> Self {
+        self.audio_device_op = Some(name.to_string());
+        self
+    }
+
+    /// Let another window show the view of the entry, seen through the camera3 whose `$window`
+    /// is the index of the window, 1 for the first window added.
+    ///
+    /// The input comes from the main window only.
+    pub fn add_window(mut self, window: &'static Window, entry: ViewProps) -> err::Result<Self> {
+        let surface = self
+            .instance
+            .create_surface(window)
+            .change_context(err::Error::Gpu)?;
+
+        self.window_v.push((surface, window.inner_size(), entry));
+
+        Ok(self)
+    }
+
     /// called => the [EngineBuilder] = built
     pub async fn build(self, dm: Box<dyn AsClassManager>) -> err::Result<Engine> {
         let adapter = self
@@ -194,7 +674,7 @@ impl EngineBuilder {
                 force_fallback_adapter: false,
             })
             .await
-            .ok_or(err::Error::NotFound)?;
+            .ok_or(err::Error::Gpu)?;
 
         let (device, queue) = adapter
             .request_device(
@@ -210,51 +690,89 @@ impl EngineBuilder {
                 None, // Trace path
             )
             .await
-            .change_context(err::Error::Other)?;
+            .change_context(err::Error::Gpu)?;
 
         log::debug!("found device: {:?}", device);
 
-        let config = {
-            let surface_caps = self.surface.get_capabilities(&adapter);
-
-            // Shader code in this tutorial assumes an sRGB surface texture. Using a different
-            // one will result all the colors coming out darker. If you want to support non
-            // sRGB surfaces, you'll need to account for that when drawing to the frame.
-            let surface_format = surface_caps
-                .formats
-                .iter()
-                .copied()
-                .filter(|f| f.is_srgb())
-                .next()
-                .ok_or(err::Error::NotFound)?;
-
-            let config = wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: surface_format,
-                width: self.size.width,
-                height: self.size.height,
-                present_mode: surface_caps.present_modes[0],
-                alpha_mode: surface_caps.alpha_modes[0],
-                view_formats: vec![],
-                desired_maximum_frame_latency: 2,
-            };
-            self.surface.configure(&device, &config);
+        let config = inner::surface_config_of(&self.surface, &adapter, &device, self.size)?;
+        log::info!("prepared surface: {:?}", config);
+
+        let mut vision_manager =
+            res::VisionElementProvider::new(self.surface, device, queue, config, self.perspective);
+        let mut window_entry_v = Vec::new();
+        for (surface, size, entry) in self.window_v {
+            let config =
+                inner::surface_config_of(&surface, &adapter, &vision_manager.device, size)?;
+            log::info!(
+                "prepared surface of window {}: {:?}",
+                window_entry_v.len() + 1,
+                config
+            );
+
+            vision_manager.add_window(surface, config);
+            window_entry_v.push(entry);
+        }
 
-            log::info!("prepared surface: {:?}", config);
+        let mut engine = Engine::new(
+            dm,
+            #[cfg(feature = "physics")]
+            res::PhysicsElementProvider::new(self.integration_parameters),
+            vision_manager,
+            #[cfg(feature = "audio")]
+            res::AudioElementProvider::new(self.audio_device_op.as_deref()),
+            res::InputProvider::new(),
+        );
+        engine.window_entry_v = window_entry_v;
 
-            config
-        };
+        Ok(engine)
+    }
+}
 
-        Ok(Engine::new(
-            dm,
-            res::PhysicsElementProvider::new(IntegrationParameters::default()),
-            res::VisionElementProvider::new(self.surface, device, queue, config),
-        ))
+/// A first-person character driven by the `move_x`, `move_z` and `jump` actions.
+#[cfg(feature = "physics")]
+struct FpsController {
+    vnode_id: u64,
+    /// The walking speed in m/s.
+    speed: f32,
+    /// The vertical speed of a jump in m/s.
+    jump_speed: f32,
+}
+
+#[cfg(feature = "physics")]
+impl FpsController {
+    fn new(vnode_id: u64, props: &json::JsonValue) -> Self {
+        Self {
+            vnode_id,
+            speed: prop::number(props, "$speed").unwrap_or(4.0),
+            jump_speed: prop::number(props, "$jump_speed").unwrap_or(5.0),
+        }
     }
 }
 
+/// The principal of the lines executed in the console, see [Engine::set_auth].
+pub const CONSOLE_PRINCIPAL: &str = "@console";
+
+/// The most entries of `@log` kept.
+pub const MAX_LOG_COUNT: usize = 100;
+const LOG_CLASS: &str = "@log";
+const LOG_SOURCE: &str = "@moon_world";
+/// Where [Engine::autosave] writes, as `@autosave(@moon_world)`.
+const AUTOSAVE_CLASS: &str = "@autosave";
+const AUTOSAVE_SOURCE: &str = "@moon_world";
+/// How often the view directory is scanned for changes, when hot reloading.
+const VIEW_SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A function of the host exposed as a class, called with the source and the items appended, or
+/// no items for a get.
+///
+/// called => the result = the items got, ignored for an append
+pub type ClassFn =
+    Box<dyn Fn(&str, Vec<String>) -> moon_class::err::Result<Vec<String>> + Send + Sync>;
+
 pub enum AtomElement {
-    Audio(()),
+    #[cfg(feature = "audio")]
+    Audio(u64),
+    #[cfg(feature = "physics")]
     Physics(RigidBodyHandle),
     Vision(u64),
     Input(u64),
@@ -264,14 +782,87 @@ pub enum AtomElement {
 ///
 /// rendered => frame = next frame
 pub struct Engine {
-    unique_id: u64,
-    vnode_mp: HashMap<u64, VNode>,
+    world: world::World,
+    #[cfg(feature = "physics")]
     watcher_binding_body_id: u64,
     element_mp: HashMap<u64, AtomElement>,
+    is_paused: bool,
+    /// The cursor normalized to [0, 1] from the top left of the window.
+    cursor_op: Option<[f32; 2]>,
+    /// The vnode whose body is under the cursor.
+    hover_op: Option<u64>,
+    #[cfg(feature = "physics")]
+    fps_op: Option<FpsController>,
+    /// The vnodes spawned at runtime under each parent, oldest first.
+    spawn_mp: HashMap<u64, Vec<u64>>,
+    last_simulate_op: Option<Instant>,
+    last_render_op: Option<Instant>,
+    /// The time a step simulates instead of the time elapsed, while replaying.
+    fixed_elapsed_op: Option<Duration>,
+    physics_cadence: Cadence,
+    /// The cadence of `$onstep`.
+    script_cadence: Cadence,
+    render_cadence: Cadence,
+    /// The root vnodes of the scenes, the active one last.
+    scene_v: Vec<u64>,
+    /// The scene each element is created in.
+    scene_of_mp: HashMap<u64, u64>,
+    /// The functions of the host exposed as classes.
+    class_fn_mp: HashMap<String, ClassFn>,
+    /// The entries of the views of the windows other than the main one, until initialized.
+    window_entry_v: Vec<ViewProps>,
+    /// The root vnodes of the windows other than the main one, the window 1 first.
+    window_root_v: Vec<u64>,
+    is_inspector_open: bool,
+    /// When the inspector was last drawn.
+    last_inspector_op: Option<Instant>,
+    console_op: Option<console::Console>,
+    frame_stats: stats::FrameStats,
+    /// Whether the entries of `@log` are also logged by [log].
+    is_log_mirrored: bool,
+    /// When the HUD of `$debug_hud` was last reported.
+    last_hud_op: Option<Instant>,
+    /// How often the world is saved by [Engine::autosave], or `None` for never.
+    autosave_interval_op: Option<Duration>,
+    last_autosave_op: Option<Instant>,
+    /// The directory the views are reloaded from when changed, see [Engine::load_views].
+    view_dir_op: Option<views::ViewDir>,
+    last_view_scan_op: Option<Instant>,
+    /// The system clipboard, or `None` if there is none, like on a server.
+    #[cfg(feature = "clipboard")]
+    clipboard_op: Option<Mutex<arboard::Clipboard>>,
+    /// The changes of the window asked by the view, until drained by the runner.
+    window_command_v: Vec<runner::WindowCommand>,
+    /// The player of this client and where its input is forwarded to.
+    input_forward_op: Option<(String, Sender<String>)>,
+    /// The replay being recorded.
+    recorder_op: Option<replay::Replay>,
+    /// The events pushed, until handled by the next step.
+    event_queue: VecDeque<EngineEvent>,
+    /// The class data got from the data manager while a vnode is applied, as
+    /// `(class, source)`.
+    watch_read_op: Mutex<Option<HashSet<(String, String)>>>,
+    /// The context and the class data read of each vnode applied, to apply it again when the
+    /// data changes.
+    watch_mp: HashMap<u64, (u64, HashSet<(String, String)>)>,
+    /// The class data appended or removed through the engine since the last step.
+    changed_data_set: HashSet<(String, String)>,
+    /// The classes appended through the engine with each vnode as the source, to be removed
+    /// when it is despawned.
+    vnode_class_mp: HashMap<u64, HashSet<String>>,
+    /// What each principal may read and write.
+    auth_mp: HashMap<String, Auth>,
+    /// The principal the gets, appends and removes are of, or `None` for the host.
+    principal_op: Option<String>,
 
     data_manager: Box<dyn AsClassManager>,
+    /// The middleware of the gets, appends and removes through the engine, the outermost last.
+    dm_middleware_v: Vec<Box<dyn DmMiddleware + Send>>,
+    #[cfg(feature = "physics")]
     physics_manager: res::PhysicsElementProvider,
     vision_manager: res::VisionElementProvider,
+    #[cfg(feature = "audio")]
+    audio_manager: res::AudioElementProvider,
     input_provider: res::InputProvider,
 
     cc: camera::CameraController,
@@ -281,176 +872,2267 @@ impl Engine {
     /// called => the result = a new [Engine]
     pub fn new(
         dm: Box<dyn AsClassManager>,
-        physics_manager: res::PhysicsElementProvider,
+        #[cfg(feature = "physics")] physics_manager: res::PhysicsElementProvider,
         vision_manager: res::VisionElementProvider,
+        #[cfg(feature = "audio")] audio_manager: res::AudioElementProvider,
+        input_provider: res::InputProvider,
     ) -> Self {
         Self {
-            unique_id: 0,
-            vnode_mp: HashMap::new(),
+            world: world::World::new(),
+            #[cfg(feature = "physics")]
             watcher_binding_body_id: 0,
             element_mp: HashMap::new(),
+            is_paused: false,
+            cursor_op: None,
+            hover_op: None,
+            #[cfg(feature = "physics")]
+            fps_op: None,
+            spawn_mp: HashMap::new(),
+            last_simulate_op: None,
+            last_render_op: None,
+            fixed_elapsed_op: None,
+            physics_cadence: Cadence::default(),
+            script_cadence: Cadence::default(),
+            render_cadence: Cadence::default(),
+            scene_v: Vec::new(),
+            scene_of_mp: HashMap::new(),
+            class_fn_mp: util::vec_math::class_fn_mp(),
+            input_forward_op: None,
+            recorder_op: None,
+            event_queue: VecDeque::new(),
+            watch_read_op: Mutex::new(None),
+            watch_mp: HashMap::new(),
+            changed_data_set: HashSet::new(),
+            vnode_class_mp: HashMap::new(),
+            view_dir_op: None,
+            last_view_scan_op: None,
+            auth_mp: HashMap::from([(CONSOLE_PRINCIPAL.to_string(), Auth::allow_all())]),
+            principal_op: None,
+            window_command_v: Vec::new(),
+            is_inspector_open: false,
+            last_inspector_op: None,
+            console_op: None,
+            frame_stats: stats::FrameStats::new(),
+            is_log_mirrored: true,
+            last_hud_op: None,
+            autosave_interval_op: None,
+            last_autosave_op: None,
+            #[cfg(feature = "clipboard")]
+            clipboard_op: arboard::Clipboard::new()
+                .map_err(|e| log::warn!("no clipboard: {e}"))
+                .ok()
+                .map(Mutex::new),
+            window_entry_v: Vec::new(),
+            window_root_v: Vec::new(),
             data_manager: dm,
+            dm_middleware_v: Vec::new(),
+            #[cfg(feature = "physics")]
             physics_manager,
             vision_manager,
-            input_provider: res::InputProvider::new(),
+            #[cfg(feature = "audio")]
+            audio_manager,
+            input_provider,
             cc: camera::CameraController::new(1.0),
         }
     }
 
-    pub async fn init(&mut self, entry: ViewProps) {
-        let root_id = self.new_vnode(0);
-        self.apply_props(root_id, &entry, 0, true).await.unwrap();
+    /// Let the function be called for the gets and appends of the class, like `@path` for a
+    /// pathfinding of the host, unless the class is one of the engine.
+    ///
+    /// A function registered with the same name is replaced.
+    pub fn register_class_fn(
+        &mut self,
+        name: &str,
+        class_fn: impl Fn(&str, Vec<String>) -> moon_class::err::Result<Vec<String>>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.class_fn_mp
+            .insert(name.to_string(), Box::new(class_fn));
     }
 
-    /// called => the event = handled[]
-    pub async fn event_handler(
-        &mut self,
-        entry_name: &str,
-        data: &json::JsonValue,
-    ) -> err::Result<()> {
-        for id in self
-            .element_mp
-            .iter()
-            .filter(|(_, ele)| {
-                if let AtomElement::Input(_) = ele {
-                    return true;
-                }
-                false
-            })
-            .map(|(id, _)| *id)
-            .collect::<Vec<u64>>()
-        {
-            let _ = self
-                .event_entry(id, entry_name, data)
-                .await
-                .change_context(err::Error::Other)?;
+    /// Let the entry be applied to a new scene, and the entry of each extra window to its root.
+    pub async fn init(&mut self, entry: ViewProps) -> err::Result<()> {
+        let root_id = self.new_vnode(0);
+        self.scene_v.push(root_id);
+        self.apply_watched(root_id, &entry, 0, true).await?;
+
+        for entry in std::mem::take(&mut self.window_entry_v) {
+            let root_id = self.new_vnode(0);
+            self.window_root_v.push(root_id);
+            self.apply_watched(root_id, &entry, 0, true).await?;
         }
 
         Ok(())
     }
 
-    /// called => the engine = stepped
-    pub async fn step(&mut self) -> err::Result<()> {
-        self.physics_manager.step();
-
-        for id in self
-            .element_mp
-            .iter()
-            .filter(|(_, ele)| {
-                if let AtomElement::Physics(h) = ele {
-                    if let Some(body) = self.physics_manager.physics_engine.rigid_body_set.get(*h) {
-                        return body.is_dynamic();
-                    }
-                }
-                false
-            })
-            .map(|(id, _)| *id)
-            .collect::<Vec<u64>>()
-        {
-            let _ = self.event_entry(id, "$onstep", &json::Null).await;
-        }
+    /// called => the result = the changes of the window asked since the last call, for the
+    /// thread of the event loop to apply
+    pub fn drain_window_command_v(&mut self) -> Vec<runner::WindowCommand> {
+        std::mem::take(&mut self.window_command_v)
+    }
 
-        self.cc
-            .update_camera(self.vision_manager.camera_state_mut());
+    /// called => the result = the text in the clipboard
+    #[cfg(feature = "clipboard")]
+    pub fn clipboard_text(&self) -> err::Result<String> {
+        self.clipboard_op
+            .as_ref()
+            .ok_or(err::Error::NotFound)
+            .attach_printable("no clipboard")?
+            .lock()
+            .map_err(|_| err::Error::Other)
+            .attach_printable("the clipboard is poisoned")?
+            .get_text()
+            .change_context(err::Error::NotFound)
+            .attach_printable("no text in the clipboard")
+    }
 
-        Ok(())
+    /// Let the text be put in the clipboard.
+    #[cfg(feature = "clipboard")]
+    pub fn set_clipboard_text(&self, text: &str) -> err::Result<()> {
+        self.clipboard_op
+            .as_ref()
+            .ok_or(err::Error::NotFound)
+            .attach_printable("no clipboard")?
+            .lock()
+            .map_err(|_| err::Error::Other)
+            .attach_printable("the clipboard is poisoned")?
+            .set_text(text)
+            .change_context(err::Error::Other)
     }
 
-    /// called => the engine = rendered
-    pub fn render(&mut self) -> err::Result<()> {
-        let mut rp = self.vision_manager.render_pass()?;
+    /// Let the line be executed as a script against the engine, its output kept by the console
+    /// and sent as `$onoutput` with `$line` and `$output` to the console vnode.
+    pub async fn execute_console_line(&mut self, line: &str) {
+        let principal_op = self.principal_op.replace(CONSOLE_PRINCIPAL.to_string());
+        let output = {
+            let mut ce = ClassExecutor::new(self);
 
-        inner::render_vnode(&self.vnode_mp, &self.element_mp, &mut rp, 0)?;
+            match ce.execute_script(line).await {
+                Ok(rs) => format!("{rs:?}"),
+                Err(e) => format!("error: {e:?}"),
+            }
+        };
+        self.principal_op = principal_op;
 
-        rp.render()
+        let vnode_id = match &mut self.console_op {
+            Some(console) => {
+                console.push_output(format!("> {line}"));
+                console.push_output(output.clone());
+
+                console.vnode_id
+            }
+            None => return,
+        };
+        self.refresh_console();
+        let _ = self
+            .event_entry(
+                vnode_id,
+                "$onoutput",
+                &json::object! { "$line": line, "$output": output },
+            )
+            .await;
     }
-}
 
-impl AsClassManager for Engine {
-    fn append<'a, 'a1, 'a2, 'f>(
-        &'a mut self,
-        class: &'a1 str,
-        source: &'a2 str,
-        item_v: Vec<String>,
-    ) -> std::pin::Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
-    where
-        'a: 'f,
-        'a1: 'f,
-        'a2: 'f,
-    {
-        Box::pin(async move {
-            if class == "@new_size" && source == "@window" {
-                let data = json::parse(&rs_2_str(&item_v)).unwrap();
+    /// Let an entry of `{$level, $message, $time}` be stored as `@log(@moon_world)` in the data
+    /// manager, the oldest ones dropped beyond [MAX_LOG_COUNT], and logged by [log] if mirrored.
+    ///
+    /// `$time` is in milliseconds since the Unix epoch.
+    pub async fn log(&mut self, level: log::Level, message: &str) -> err::Result<()> {
+        if self.is_log_mirrored {
+            log::log!(level, "{message}");
+        }
 
-                self.vision_manager.resize(PhysicalSize {
-                    width: data["$width"][0].as_str().unwrap().parse().unwrap(),
-                    height: data["$height"][0].as_str().unwrap().parse().unwrap(),
-                });
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_millis() as u64)
+            .unwrap_or(0);
+        let entry = json::object! {
+            "$level": level.as_str().to_lowercase(),
+            "$message": message,
+            "$time": time,
+        };
 
-                Ok(())
-            } else if class == "@new_acc" && source == "@camera" {
-                let data = json::parse(&rs_2_str(&item_v)).unwrap();
+        self.changed_data_set
+            .insert((LOG_CLASS.to_string(), LOG_SOURCE.to_string()));
+        self.data_manager
+            .append(LOG_CLASS, LOG_SOURCE, vec![entry.dump()])
+            .await
+            .change_context(err::Error::Data)?;
 
-                self.cc.amount_translation(
-                    data["$x"][0].as_str().unwrap().parse::<f32>().unwrap(),
-                    data["$y"][0].as_str().unwrap().parse::<f32>().unwrap(),
-                    data["$z"][0].as_str().unwrap().parse::<f32>().unwrap(),
-                );
+        let entry_v = self
+            .data_manager
+            .get(LOG_CLASS, LOG_SOURCE)
+            .await
+            .change_context(err::Error::Data)?;
+        if entry_v.len() > MAX_LOG_COUNT {
+            self.data_manager
+                .remove(
+                    LOG_CLASS,
+                    LOG_SOURCE,
+                    entry_v[..entry_v.len() - MAX_LOG_COUNT].to_vec(),
+                )
+                .await
+                .change_context(err::Error::Data)?;
+        }
 
-                Ok(())
-            } else if class == "@new_rotation" && source == "@camera" {
-                let data = json::parse(&rs_2_str(&item_v)).unwrap();
+        Ok(())
+    }
 
-                self.cc.rorate(
-                    data["$x"][0].as_str().unwrap().parse::<f32>().unwrap(),
-                    data["$y"][0].as_str().unwrap().parse::<f32>().unwrap(),
-                );
+    pub fn frame_stats(&self) -> &stats::FrameStats {
+        &self.frame_stats
+    }
 
-                Ok(())
-            } else {
-                self.data_manager.append(class, source, item_v).await
+    /// Let the frame stats be reported each second as `$onhud` to the active scene, and drawn at
+    /// the bottom left of the main window, if its root has `$debug_hud` = true.
+    async fn report_hud(&mut self) {
+        let root_id = match self.scene_v.last() {
+            Some(root_id) => *root_id,
+            None => return,
+        };
+        let is_hud_on = self
+            .world
+            .tree
+            .vnode_mp
+            .get(&root_id)
+            .and_then(|root| prop::boolean(&root.view_props.props, "$debug_hud"))
+            == Some(true);
+        if !is_hud_on {
+            if self.last_hud_op.take().is_some() {
+                self.vision_manager.set_overlay("hud", None);
             }
-        })
+            return;
+        }
+
+        let now = Instant::now();
+        if self
+            .last_hud_op
+            .is_some_and(|last| now.duration_since(last) < Duration::from_secs(1))
+        {
+            return;
+        }
+        self.last_hud_op = Some(now);
+
+        self.vision_manager.set_overlay(
+            "hud",
+            Some((
+                &self.frame_stats.hud_image(),
+                res::OverlayCorner::BottomLeft,
+            )),
+        );
+        let hud = self.frame_stats.to_json();
+        let _ = self.event_entry(root_id, "$onhud", &hud).await;
     }
 
-    fn remove<'a, 'a1, 'a2, 'f>(
-        &'a mut self,
-        class: &'a1 str,
-        source: &'a2 str,
-        item_v: Vec<String>,
-    ) -> std::pin::Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
-    where
-        'a: 'f,
-        'a1: 'f,
-        'a2: 'f,
-    {
-        self.data_manager.remove(class, source, item_v)
+    /// called => the result = the translation, the rotation and the scale of the element of the
+    /// vnode, a body taking its scale from the `$scale` prop
+    pub fn transform(
+        &self,
+        vnode_id: u64,
+    ) -> Option<(Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>)> {
+        match self.element_mp.get(&vnode_id)? {
+            #[cfg(feature = "physics")]
+            AtomElement::Physics(h) => {
+                let body = self.physics_manager.physics_engine.rigid_body_set.get(*h)?;
+                let scale = self
+                    .world
+                    .props_mp
+                    .get(&vnode_id)
+                    .and_then(|props| res::scale_of(props).ok().flatten())
+                    .unwrap_or(vector![1.0, 1.0, 1.0]);
+
+                Some((*body.translation(), *body.rotation(), scale))
+            }
+            AtomElement::Vision(id) => self.vision_manager.transform(*id),
+            _ => None,
+        }
     }
 
-    fn get<'a, 'a1, 'a2, 'f>(
-        &'a self,
-        class: &'a1 str,
-        source: &'a2 str,
-    ) -> std::pin::Pin<Box<dyn Fu<Output = moon_class::err::Result<Vec<String>>> + 'f>>
-    where
-        'a: 'f,
-        'a1: 'f,
-        'a2: 'f,
-    {
-        Box::pin(async move {
-            match class {
-                "@moon_world_pos" => {
-                    let vnode_id = source.parse::<u64>().unwrap();
-
-                    let ele = self.element_mp.get(&vnode_id).unwrap();
-                    if let AtomElement::Physics(h) = ele {
-                        let pos = self
-                            .physics_manager
-                            .physics_engine
-                            .rigid_body_set
-                            .get(*h)
-                            .unwrap()
-                            .translation();
+    /// Let the bodies and the lights seen be written to the path, like `scene.gltf`, with their
+    /// buffer to `scene.bin` beside it, for the scene to be inspected or edited in Blender and
+    /// other DCC tools.
+    ///
+    /// Each body is written with the triangles it is drawn with, a body of no mesh skipped with
+    /// a warning.
+    pub fn export_gltf(&self, path: &Path) -> err::Result<()> {
+        let mut id_v = self.element_mp.keys().copied().collect::<Vec<u64>>();
+        id_v.sort();
+
+        let mut scene = gltf::GltfScene::new();
+        for vnode_id in id_v {
+            let look = match self.element_mp[&vnode_id] {
+                AtomElement::Vision(id) => match self.vision_manager.body_mp.get(&id) {
+                    Some(look) => look,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let class = self
+                .world
+                .tree
+                .vnode_mp
+                .get(&vnode_id)
+                .map(|vnode| vnode.view_props.class.as_str())
+                .unwrap_or_default();
+            let name = format!("{class}_{vnode_id}");
+
+            match look {
+                ThreeLook::Body(body) => {
+                    let props = self.world.props_mp.get(&vnode_id).unwrap_or(&json::Null);
+
+                    match res::vertex_array_of(class.trim_start_matches("Vision:"), props) {
+                        Ok(Some(array)) => scene.push_mesh(
+                            &name,
+                            res::transform_of(&body.model_m),
+                            array.vertex_v(),
+                        ),
+                        Ok(None) => log::warn!("export_gltf: skipped {name}, a body of no mesh"),
+                        Err(e) => log::warn!("export_gltf: skipped {name}: {e:?}"),
+                    }
+                }
+                ThreeLook::Light(light) => {
+                    // The view looks along -Z, as a glTF light shines.
+                    let model_m = match light.view.try_inverse() {
+                        Some(model_m) => model_m,
+                        None => continue,
+                    };
+
+                    scene.push_light(
+                        &name,
+                        res::transform_of(&model_m),
+                        light.color,
+                        light.intensity,
+                    );
+                }
+            }
+        }
+
+        scene.save(path)
+    }
+
+    /// Let the world be saved by [Engine::autosave] every interval, or never if `None`.
+    pub fn set_autosave_interval(&mut self, interval_op: Option<Duration>) {
+        self.autosave_interval_op = interval_op.filter(|interval| !interval.is_zero());
+        self.last_autosave_op = None;
+    }
+
+    /// Let the transforms and the velocities of the bodies be written to the data manager as the
+    /// only item of `@autosave(@moon_world)`, replacing the one before, so a crash loses only
+    /// what happened since.
+    ///
+    /// The item is `{"<vnode>": {$position, $rotation, $linvel, $angvel}}`, the rotation being a
+    /// quaternion `[x, y, z, w]`.
+    pub async fn autosave(&mut self) -> err::Result<()> {
+        let save = self.body_save();
+
+        let old_v = self
+            .data_manager
+            .get(AUTOSAVE_CLASS, AUTOSAVE_SOURCE)
+            .await
+            .change_context(err::Error::Data)?;
+        if !old_v.is_empty() {
+            self.data_manager
+                .remove(AUTOSAVE_CLASS, AUTOSAVE_SOURCE, old_v)
+                .await
+                .change_context(err::Error::Data)?;
+        }
+        self.data_manager
+            .append(AUTOSAVE_CLASS, AUTOSAVE_SOURCE, vec![save.dump()])
+            .await
+            .change_context(err::Error::Data)
+    }
+
+    /// called => the result = the item of [Engine::autosave]
+    #[cfg(feature = "physics")]
+    fn body_save(&self) -> json::JsonValue {
+        let mut save = json::object! {};
+        for (id, ele) in &self.element_mp {
+            let body = match ele {
+                AtomElement::Physics(h) => {
+                    match self.physics_manager.physics_engine.rigid_body_set.get(*h) {
+                        Some(body) => body,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            let (pos, rot, linvel, angvel) = (
+                body.translation(),
+                body.rotation(),
+                body.linvel(),
+                body.angvel(),
+            );
+
+            save[id.to_string()] = json::object! {
+                "$position": [pos.x, pos.y, pos.z],
+                "$rotation": [rot.i, rot.j, rot.k, rot.w],
+                "$linvel": [linvel.x, linvel.y, linvel.z],
+                "$angvel": [angvel.x, angvel.y, angvel.z],
+            };
+        }
+
+        save
+    }
+
+    /// called => the result = the item of [Engine::autosave], with no bodies to save
+    #[cfg(not(feature = "physics"))]
+    fn body_save(&self) -> json::JsonValue {
+        json::object! {}
+    }
+
+    /// Let the world be saved if the autosave interval has passed.
+    async fn autosave_if_due(&mut self) {
+        let interval = match self.autosave_interval_op {
+            Some(interval) => interval,
+            None => return,
+        };
+
+        let now = Instant::now();
+        match self.last_autosave_op {
+            Some(last) if now.duration_since(last) < interval => return,
+            // The first interval starts now.
+            None => {
+                self.last_autosave_op = Some(now);
+                return;
+            }
+            _ => (),
+        }
+        self.last_autosave_op = Some(now);
+
+        if let Err(e) = self.autosave().await {
+            log::error!("failed to autosave: {e:?}");
+        }
+    }
+
+    /// Let the views in the directory be set in the data manager, then, if hot, be set again
+    /// whenever their files change, the vnodes of their classes being applied again.
+    pub async fn load_views(
+        &mut self,
+        mut view_dir: views::ViewDir,
+        is_hot: bool,
+    ) -> err::Result<()> {
+        let class_v = view_dir.load(self).await?;
+        log::info!("loaded the views of {class_v:?}");
+
+        if is_hot {
+            self.view_dir_op = Some(view_dir);
+            self.last_view_scan_op = Some(Instant::now());
+        }
+
+        Ok(())
+    }
+
+    /// Let the view directory be scanned if the scan interval has passed.
+    async fn reload_views_if_due(&mut self) {
+        let now = Instant::now();
+        if let Some(last) = self.last_view_scan_op {
+            if now.duration_since(last) < VIEW_SCAN_INTERVAL {
+                return;
+            }
+        }
+        let mut view_dir = match self.view_dir_op.take() {
+            Some(view_dir) => view_dir,
+            None => return,
+        };
+        self.last_view_scan_op = Some(now);
+
+        match view_dir.load(self).await {
+            Ok(class_v) if !class_v.is_empty() => log::info!("reloaded the views of {class_v:?}"),
+            Ok(_) => (),
+            Err(e) => log::error!("failed to reload the views: {e:?}"),
+        }
+
+        self.view_dir_op = Some(view_dir);
+    }
+
+    /// Let the inspector be opened or closed, the vnode tree being drawn at the top left of the
+    /// main window while it is open.
+    pub fn set_inspector_open(&mut self, is_open: bool) {
+        self.is_inspector_open = is_open;
+        self.last_inspector_op = None;
+
+        if is_open {
+            self.refresh_inspector_if_due();
+        } else {
+            self.vision_manager.set_overlay("inspector", None);
+        }
+    }
+
+    /// Let the console be drawn at the bottom right of the main window while it is open, the
+    /// last of its scrollback above the line typed.
+    fn refresh_console(&mut self) {
+        match &self.console_op {
+            Some(console) if console.is_open => {
+                let image = drawer::overlay::OverlayImage::of_line_v(
+                    &console.overlay_line_v(),
+                    [255, 255, 255, 255],
+                );
+                self.vision_manager
+                    .set_overlay("console", Some((&image, res::OverlayCorner::BottomRight)));
+            }
+            _ => self.vision_manager.set_overlay("console", None),
+        }
+    }
+
+    /// Let the vnode tree be drawn again each second while the inspector is open.
+    fn refresh_inspector_if_due(&mut self) {
+        if !self.is_inspector_open {
+            return;
+        }
+
+        let now = Instant::now();
+        if self
+            .last_inspector_op
+            .is_some_and(|last| now.duration_since(last) < Duration::from_secs(1))
+        {
+            return;
+        }
+        self.last_inspector_op = Some(now);
+
+        let image = drawer::overlay::OverlayImage::of_line_v(
+            &inspector::overlay_line_v(&self.inspector_line_v()),
+            [255, 255, 255, 255],
+        );
+        self.vision_manager
+            .set_overlay("inspector", Some((&image, res::OverlayCorner::TopLeft)));
+    }
+
+    pub fn is_inspector_open(&self) -> bool {
+        self.is_inspector_open
+    }
+
+    /// called => the result = a line per vnode of the scenes and windows, depth first, with its
+    /// id, class, element kind and props
+    pub fn inspector_line_v(&self) -> Vec<String> {
+        let root_v = self
+            .scene_v
+            .iter()
+            .chain(self.window_root_v.iter())
+            .copied()
+            .collect::<Vec<u64>>();
+
+        inspector::line_v(&self.world.tree.vnode_mp, &self.element_mp, &root_v)
+    }
+
+    /// Let a prop of the vnode be edited live, its element updated by the props edited.
+    pub fn inspect_prop(
+        &mut self,
+        vnode_id: u64,
+        key: &str,
+        value: json::JsonValue,
+    ) -> err::Result<()> {
+        let vnode = self
+            .world
+            .tree
+            .vnode_mp
+            .get_mut(&vnode_id)
+            .ok_or(err::Error::NotFound)
+            .attach_printable_lazy(|| format!("no vnode {vnode_id} to inspect"))?;
+        vnode.view_props.props[key] = value.clone();
+        let class = vnode.view_props.class.clone();
+
+        if let Some(props) = self.world.props_mp.get(&vnode_id) {
+            let mut props = props.clone();
+            props[key] = value;
+
+            self.update_element(vnode_id, &class, &props);
+        }
+
+        Ok(())
+    }
+
+    /// Let the window of the index be resized, the main one being 0.
+    pub fn resize_window(&mut self, window: usize, new_size: PhysicalSize<u32>) {
+        self.vision_manager.resize_window(window, new_size);
+    }
+
+    /// Let a scene of the view class be pushed over the active one, which keeps its state but
+    /// is neither stepped nor rendered until the new scene is popped.
+    ///
+    /// called => the result = the root vnode of the new scene
+    pub async fn scene_push(&mut self, class: &str, props: json::JsonValue) -> err::Result<u64> {
+        if let Some(scene) = self.scene_v.last() {
+            self.set_scene_enabled(*scene, false);
+        }
+
+        let root_id = self.new_vnode(0);
+        self.scene_v.push(root_id);
+        self.apply_watched(
+            root_id,
+            &ViewProps {
+                class: class.to_string(),
+                props,
+            },
+            0,
+            true,
+        )
+        .await
+        .change_context(err::Error::Other)
+        .attach_printable_lazy(|| format!("failed to push scene {class}"))?;
+
+        Ok(root_id)
+    }
+
+    /// Let the active scene be removed and the one under it resumed.
+    pub async fn scene_pop(&mut self) -> err::Result<()> {
+        if self.scene_v.len() < 2 {
+            return Err(err::Error::NotFound).attach_printable("no scene under the active one");
+        }
+
+        let root_id = self.scene_v.pop().unwrap();
+        self.despawn(root_id).await?;
+        self.set_scene_enabled(*self.scene_v.last().unwrap(), true);
+
+        Ok(())
+    }
+
+    /// Let the bodies of the scene take part in the simulation or not.
+    fn set_scene_enabled(&mut self, scene: u64, is_enabled: bool) {
+        #[cfg(feature = "physics")]
+        for (id, _) in self.scene_of_mp.iter().filter(|(_, s)| **s == scene) {
+            if let Some(AtomElement::Physics(h)) = self.element_mp.get(id) {
+                self.physics_manager.set_enabled(*h, is_enabled);
+            }
+        }
+        #[cfg(not(feature = "physics"))]
+        let _ = (scene, is_enabled);
+    }
+
+    /// called => the result = whether the element is in the active scene
+    fn is_in_active_scene(&self, id: u64) -> bool {
+        self.scene_of_mp.get(&id) == self.scene_v.last()
+    }
+
+    /// Let an instance of the view class be spawned as a child of the parent vnode.
+    ///
+    /// called => the result = the id of the new vnode
+    pub async fn spawn(
+        &mut self,
+        parent_id: u64,
+        class: &str,
+        props: json::JsonValue,
+    ) -> err::Result<u64> {
+        if !self.world.tree.vnode_mp.contains_key(&parent_id) {
+            return Err(err::Error::NotFound)
+                .attach_printable_lazy(|| format!("no vnode {parent_id} to spawn {class} in"));
+        }
+
+        let vnode_id = self.new_vnode(parent_id);
+        self.apply_watched(
+            vnode_id,
+            &ViewProps {
+                class: class.to_string(),
+                props,
+            },
+            parent_id,
+            true,
+        )
+        .await
+        .change_context(err::Error::Other)
+        .attach_printable_lazy(|| format!("failed to spawn {class} in {parent_id}"))?;
+
+        // Let the new vnode be rendered with its siblings.
+        if let Some(parent) = self.get_vnode_mut(&parent_id) {
+            parent.embeded_child_v.push(vnode_id);
+        }
+        self.spawn_mp.entry(parent_id).or_default().push(vnode_id);
+
+        Ok(vnode_id)
+    }
+
+    /// Let the vnode and its subtree be removed with their elements and their class data, as if
+    /// never spawned.
+    ///
+    /// The root of a scene or a window is refused; a scene is removed by [Engine::scene_pop].
+    pub async fn despawn(&mut self, vnode_id: u64) -> err::Result<()> {
+        let context = match self.get_vnode(&vnode_id) {
+            Some(vnode) => vnode.context,
+            None => {
+                return Err(err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("no vnode {vnode_id} to despawn"))
+            }
+        };
+        if self.scene_v.contains(&vnode_id) || self.window_root_v.contains(&vnode_id) {
+            return Err(err::Error::NotFound)
+                .attach_printable_lazy(|| format!("vnode {vnode_id} is the root of a view"));
+        }
+
+        let mut id_v = vec![vnode_id];
+        while let Some(id) = id_v.pop() {
+            if let Some(vnode) = self.rm_vnode(id) {
+                if vnode.inner_node.data != 0 {
+                    id_v.push(vnode.inner_node.data);
+                }
+                id_v.extend(vnode.embeded_child_v);
+            }
+
+            self.delete_element(id);
+            self.spawn_mp.remove(&id);
+            if self.hover_op == Some(id) {
+                self.hover_op = None;
+            }
+            #[cfg(feature = "physics")]
+            if self.watcher_binding_body_id == id {
+                self.watcher_binding_body_id = 0;
+            }
+
+            let source = id.to_string();
+            for class in self.vnode_class_mp.remove(&id).unwrap_or_default() {
+                snapshot::remember_origin(
+                    &*self.data_manager,
+                    &mut self.world.origin_data_mp,
+                    &class,
+                    &source,
+                )
+                .await
+                .change_context(err::Error::Data)?;
+                let item_v = self
+                    .data_manager
+                    .get(&class, &source)
+                    .await
+                    .change_context(err::Error::Data)?;
+                self.data_manager
+                    .remove(&class, &source, item_v)
+                    .await
+                    .change_context(err::Error::Data)
+                    .attach_printable_lazy(|| format!("failed to remove {class} of {source}"))?;
+                self.changed_data_set.insert((class, source.clone()));
+            }
+        }
+
+        // Let the context forget the vnode.
+        if let Some(vnode) = self.get_vnode_mut(&context) {
+            vnode.embeded_child_v.retain(|id| *id != vnode_id);
+            if vnode.inner_node.data == vnode_id {
+                vnode.inner_node.data = 0;
+            }
+        }
+        if let Some(id_v) = self.spawn_mp.get_mut(&context) {
+            id_v.retain(|id| *id != vnode_id);
+        }
+
+        Ok(())
+    }
+
+    /// called => the result = the state of the vnode tree, the props of the elements, the class
+    /// data changed, the physics and the generator of `@random`, for [Engine::restore]
+    pub async fn snapshot(&self) -> err::Result<EngineSnapshot> {
+        Ok(EngineSnapshot {
+            vnode_id_set: self.world.tree.vnode_mp.keys().copied().collect(),
+            props_mp: self.world.props_mp.clone(),
+            spawn_mp: self.spawn_mp.clone(),
+            data_mp: snapshot::data_of(&*self.data_manager, &self.world.origin_data_mp).await?,
+            #[cfg(feature = "physics")]
+            physics_state: self.physics_manager.physics_engine.snapshot(),
+            step_index: self.world.step_index,
+            random: *self.world.random.lock().unwrap(),
+            is_paused: self.is_paused,
+        })
+    }
+
+    /// Let the engine be back to the state of the snapshot.
+    ///
+    /// The scenes pushed and the vnodes spawned since are removed, the elements get their props
+    /// back and the class data its items. A vnode removed since can not be brought back, so the
+    /// snapshot is refused.
+    pub async fn restore(&mut self, snapshot: &EngineSnapshot) -> err::Result<()> {
+        if let Some(id) = snapshot
+            .vnode_id_set
+            .iter()
+            .find(|id| !self.world.tree.vnode_mp.contains_key(id))
+        {
+            return Err(err::Error::NotFound)
+                .attach_printable_lazy(|| format!("vnode {id} is removed since the snapshot"));
+        }
+
+        while self
+            .scene_v
+            .last()
+            .is_some_and(|scene| !snapshot.vnode_id_set.contains(scene))
+        {
+            self.scene_pop().await?;
+        }
+        for id in self
+            .spawn_mp
+            .values()
+            .flatten()
+            .filter(|id| !snapshot.vnode_id_set.contains(id))
+            .copied()
+            .collect::<Vec<u64>>()
+        {
+            // Already gone with a spawned ancestor.
+            if self.world.tree.vnode_mp.contains_key(&id) {
+                self.despawn(id).await?;
+            }
+        }
+
+        for (id, props) in &snapshot.props_mp {
+            if self.world.props_mp.get(id) == Some(props) {
+                continue;
+            }
+
+            if let Some(class) = self
+                .world
+                .tree
+                .vnode_mp
+                .get(id)
+                .map(|vnode| vnode.view_props.class.clone())
+            {
+                self.update_element(*id, &class, props);
+            }
+        }
+
+        // After the props, which would move the bodies where they were spawned.
+        #[cfg(feature = "physics")]
+        self.physics_manager.restore(
+            &snapshot.physics_state,
+            self.element_mp.iter().filter_map(|(id, ele)| match ele {
+                AtomElement::Physics(h) => Some((*h, self.world.props_mp.get(id)?)),
+                _ => None,
+            }),
+        );
+
+        let changed_v = snapshot::restore_data(
+            &mut *self.data_manager,
+            &mut self.world.origin_data_mp,
+            &snapshot.data_mp,
+        )
+        .await?;
+        self.changed_data_set.extend(changed_v);
+
+        self.spawn_mp = snapshot.spawn_mp.clone();
+        self.world.step_index = snapshot.step_index;
+        *self.world.random.get_mut().unwrap() = snapshot.random;
+        self.set_paused(snapshot.is_paused);
+
+        Ok(())
+    }
+
+    /// Let the cached assets no element uses, like decoded sounds or vertex buffers, be dropped.
+    ///
+    /// called => the result = how many assets are dropped
+    pub fn evict_unused_assets(&mut self) -> usize {
+        let count = self.vision_manager.evict_unused();
+        #[cfg(feature = "audio")]
+        let count = count + self.audio_manager.audio_engine.evict_unused();
+
+        count
+    }
+
+    /// Let the physics, `$onstep` and the rendering run at their own frequencies in Hz, like
+    /// 60 Hz physics, 20 Hz scripts and an uncapped rendering.
+    ///
+    /// A `None` frequency runs once per [Engine::step] or [Engine::render], as by default.
+    pub fn set_frequency(
+        &mut self,
+        physics_hz_op: Option<f32>,
+        script_hz_op: Option<f32>,
+        render_hz_op: Option<f32>,
+    ) {
+        self.physics_cadence = Cadence::new(physics_hz_op);
+        self.script_cadence = Cadence::new(script_hz_op);
+        self.render_cadence = Cadence::new(render_hz_op);
+
+        #[cfg(feature = "physics")]
+        if let Some(period) = self.physics_cadence.period_op() {
+            self.physics_manager.set_dt(period.as_secs_f32());
+        }
+    }
+
+    /// Let each input handled be forwarded as an [net::InputMessage] of the player, dumped,
+    /// for the server to play it by [Engine::dispatch_player_input].
+    pub fn forward_input(&mut self, player: &str, tx: Sender<String>) {
+        self.input_forward_op = Some((player.to_string(), tx));
+    }
+
+    /// Let the input of a player be dispatched to the vnodes of the player, the ones whose
+    /// `$owner` prop is the player, with `$player` added to the data.
+    ///
+    /// The handlers read and write as the player, see [Engine::set_auth], nothing if the player
+    /// has no [Auth].
+    pub async fn dispatch_player_input(&mut self, message: &net::InputMessage) {
+        let data = message.tagged_data();
+        let principal_op = self.principal_op.replace(message.player.clone());
+
+        for id in net::owned_vnode_v(&self.world.tree.vnode_mp, &message.player) {
+            let _ = self.event_entry(id, &message.entry_name, &data).await;
+        }
+
+        self.principal_op = principal_op;
+    }
+
+    /// Let the gets, appends and removes through the engine pass through the middleware, outside
+    /// the ones added before.
+    ///
+    /// The middleware sees the classes of the engine, like `@physics_params`, as well as the ones
+    /// of the data manager, but not what the engine does to the data manager for itself, like
+    /// removing the classes of a vnode despawned or restoring a snapshot.
+    pub fn add_dm_middleware(&mut self, middleware: impl DmMiddleware + Send + 'static) {
+        self.dm_middleware_v.push(Box::new(middleware));
+    }
+
+    /// called => the result = the items to be written, as passed on by the middleware from the
+    /// outermost in
+    fn write_through_middleware(
+        &mut self,
+        op: WriteOp,
+        class: &str,
+        source: &str,
+        mut item_v: Vec<String>,
+    ) -> moon_class::err::Result<Vec<String>> {
+        for middleware in self.dm_middleware_v.iter_mut().rev() {
+            item_v = middleware.on_write(op, class, source, item_v)?;
+        }
+
+        Ok(item_v)
+    }
+
+    /// Let the principal, a player or `@console` for the lines of the console, read and write
+    /// only what the [Auth] allows.
+    ///
+    /// A principal with no [Auth] may do nothing, but `@console`, allowed all until set.
+    pub fn set_auth(&mut self, principal: &str, auth: Auth) {
+        self.auth_mp.insert(principal.to_string(), auth);
+    }
+
+    /// Let the gets, appends and removes from now on be of the principal, or of the host, which
+    /// may do anything, if `None`.
+    pub fn set_principal(&mut self, principal_op: Option<&str>) {
+        self.principal_op = principal_op.map(|principal| principal.to_string());
+    }
+
+    /// called => the result = an error if the principal may not read or write the class of the
+    /// source, the host only allowed anything
+    fn check_access(
+        &self,
+        class: &str,
+        source: &str,
+        is_write: bool,
+    ) -> moon_class::err::Result<()> {
+        let principal = match &self.principal_op {
+            Some(principal) => principal,
+            None => return Ok(()),
+        };
+
+        let is_allowed = match self.auth_mp.get(principal) {
+            Some(auth) if is_write => auth.can_write(class, source),
+            Some(auth) => auth.can_read(class, source),
+            None => false,
+        };
+        if is_allowed {
+            Ok(())
+        } else {
+            Err(moon_class::err::Error::NotFound).attach_printable_lazy(|| {
+                format!(
+                    "{principal} may not {} {class}({source})",
+                    if is_write { "write" } else { "read" }
+                )
+            })
+        }
+    }
+
+    /// Let the events handled and the steps be recorded as a [replay::Replay] of the entry.
+    ///
+    /// The class data of each `(class, source)` is recorded now as the data to start from, so
+    /// this is called before [Engine::init] with the same entry.
+    pub async fn start_recording(
+        &mut self,
+        entry: &ViewProps,
+        key_v: &[(&str, &str)],
+    ) -> err::Result<()> {
+        let dt = self
+            .physics_cadence
+            .period_op()
+            .map(|period| period.as_secs_f32())
+            .unwrap_or(replay::DEFAULT_DT);
+        let mut recorder = replay::Replay::new(entry, dt);
+
+        for (class, source) in key_v {
+            let item_v = self
+                .data_manager
+                .get(class, source)
+                .await
+                .change_context(err::Error::Data)?;
+
+            recorder.record_data(class, source, item_v);
+        }
+
+        self.recorder_op = Some(recorder);
+
+        Ok(())
+    }
+
+    /// called => the result = the replay recorded since [Engine::start_recording], if any
+    pub fn stop_recording(&mut self) -> Option<replay::Replay> {
+        let mut recorder = self.recorder_op.take()?;
+        recorder.finish();
+
+        Some(recorder)
+    }
+
+    /// Let the replay in the file be played by this engine, not initialized yet: the class data
+    /// appended, the entry initialized, and each step run after its events with the fixed
+    /// timestep of the replay, so a run is reproduced exactly.
+    pub async fn replay(&mut self, path: &Path) -> err::Result<()> {
+        let replay = replay::Replay::load(path)?;
+
+        for (class, source, item_v) in &replay.data_v {
+            self.data_manager
+                .append(class, source, item_v.clone())
+                .await
+                .change_context(err::Error::Data)?;
+        }
+
+        let hz = 1.0 / replay.dt;
+        self.set_frequency(Some(hz), Some(hz), None);
+        // One period per step, so each step ticks the physics and `$onstep` once.
+        self.fixed_elapsed_op = self.physics_cadence.period_op();
+
+        self.init(replay.entry()).await?;
+
+        for event_v in &replay.step_v {
+            for (entry_name, data) in event_v {
+                self.event_handler(entry_name, data).await?;
+            }
+
+            self.step().await?;
+            self.render()?;
+        }
+
+        Ok(())
+    }
+
+    /// Let the engine be shut down in order:
+    /// 1. the roots of the scenes and the windows get `$onshutdown`, to persist their data through
+    ///    the data manager;
+    /// 2. the sounds are stopped;
+    /// 3. the GPU finishes its work and the surfaces are dropped.
+    pub async fn shutdown(mut self) {
+        for root in self
+            .scene_v
+            .iter()
+            .rev()
+            .chain(self.window_root_v.iter())
+            .copied()
+            .collect::<Vec<u64>>()
+        {
+            if let Err(e) = self.event_entry(root, "$onshutdown", &json::Null).await {
+                log::error!("failed to shut down vnode {root}: {e:?}");
+            }
+        }
+
+        #[cfg(feature = "audio")]
+        self.audio_manager.stop_all();
+        self.vision_manager.shutdown();
+    }
+
+    /// Let the engine be paused or resumed.
+    ///
+    /// While paused, the physics, `$onstep` and the audio halt, but the engine still renders and
+    /// handles input.
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.is_paused = is_paused;
+        // The time paused is not a step of the world.
+        self.last_simulate_op = None;
+
+        #[cfg(feature = "audio")]
+        self.audio_manager.set_paused(is_paused);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    /// called => the event = handled[]
+    ///
+    /// A click on a body is propagated as `$onclick` from that body, see [world::propagate], and
+    /// goes no further if handled. An input bound to actions is handled as `$onaction` of the
+    /// changed actions. Any other input is propagated from the input elements subscribing to it,
+    /// see [Engine::dispatch_input].
+    ///
+    /// `$cursormoved` is for looking around, so it is dropped unless the cursor is grabbed.
+    pub async fn event_handler(
+        &mut self,
+        entry_name: &str,
+        data: &json::JsonValue,
+    ) -> err::Result<()> {
+        if let Some(recorder) = &mut self.recorder_op {
+            recorder.record_event(entry_name, data);
+        }
+        if let Some((player, tx)) = &self.input_forward_op {
+            let message = net::InputMessage::new(player, entry_name, data).dump();
+            if tx.send(message).is_err() {
+                log::warn!("input of {player} is not forwarded, the receiver is gone");
+            }
+        }
+
+        match entry_name {
+            "$onkeydown" => {
+                // Keys are repeated by the engine as `$onkeyrepeat` instead, once `@key_repeat`
+                // is set.
+                if data["$repeat"].as_bool() == Some(true)
+                    && self.input_provider.key_repeater.is_enabled()
+                {
+                    return Ok(());
+                }
+
+                if data["$key"].as_str() == Some(inspector::TOGGLE_KEY) {
+                    self.set_inspector_open(!self.is_inspector_open);
+
+                    return Ok(());
+                }
+
+                // The keys typed in the open console are for it only.
+                if let (Some(console), Some(key)) = (&mut self.console_op, data["$key"].as_str()) {
+                    if key == console::TOGGLE_KEY {
+                        console.is_open = !console.is_open;
+                        self.refresh_console();
+
+                        return Ok(());
+                    }
+                    if console.is_open {
+                        match console.type_key(key) {
+                            Some(line) => self.execute_console_line(&line).await,
+                            None => self.refresh_console(),
+                        }
+
+                        return Ok(());
+                    }
+                }
+            }
+            "$cursormoved" => {
+                if !self.input_provider.is_cursor_grabbed() {
+                    return Ok(());
+                }
+
+                // The first-person controller looks around by itself.
+                #[cfg(feature = "physics")]
+                if self.fps_op.is_some() {
+                    if let (Some(x), Some(y)) = (data["$x"].as_f32(), data["$y"].as_f32()) {
+                        self.cc.rorate(y, x);
+                    }
+                }
+            }
+            "$onmousemove" => {
+                if let (Some(x), Some(y)) = (data["$x"].as_f32(), data["$y"].as_f32()) {
+                    self.cursor_op = Some([x, y]);
+                }
+            }
+            "$ontouchstart" | "$ontouchmove" | "$ontouchend" | "$ontouchcancel" => {
+                if let (Some(touch_id), Some(x), Some(y)) = (
+                    data["$id"].as_u64(),
+                    data["$x"].as_f32(),
+                    data["$y"].as_f32(),
+                ) {
+                    // The touch points at what it touches, like a cursor.
+                    if matches!(entry_name, "$ontouchend" | "$ontouchcancel") {
+                        self.input_provider.set_touch(touch_id, None);
+                        self.cursor_op = None;
+                    } else {
+                        self.input_provider.set_touch(touch_id, Some([x, y]));
+                        self.cursor_op = Some([x, y]);
+                    }
+                }
+            }
+            _ => (),
+        }
+
+        for (gesture_name, gesture_data) in self.detect_gesture(entry_name, data) {
+            self.dispatch_input(gesture_name, &gesture_data).await;
+        }
+
+        if matches!(entry_name, "$onmousedown" | "$ontouchstart") {
+            if let Some(id) = self.pick() {
+                if world::propagate(self, id, "$onclick", data).await {
+                    return Ok(());
+                }
+            }
+        }
+
+        if let Some((source, value)) = input::InputSource::from_event(entry_name, data) {
+            self.input_provider.set_input(&source, value);
+
+            if entry_name == "$onkeydown" {
+                if let Some(chord) = data["$key"].as_str().and_then(|key| {
+                    input::chord_of(key, |modifier| self.input_provider.is_key_held(modifier))
+                }) {
+                    self.dispatch_input("$onchord", &json::object! { "$chord": chord })
+                        .await;
+                }
+            }
+            if let Some(changed_v) = self.input_provider.action_map.feed(source, value) {
+                for (action, value) in changed_v {
+                    self.dispatch_input(
+                        "$onaction",
+                        &json::object! {
+                            "$action": action,
+                            "$value": value,
+                        },
+                    )
+                    .await;
+                }
+
+                return Ok(());
+            }
+        }
+
+        self.dispatch_input(entry_name, data).await;
+
+        Ok(())
+    }
+
+    /// called => the result = the gestures made by the raw event
+    fn detect_gesture(&mut self, entry_name: &str, data: &json::JsonValue) -> Vec<input::Gesture> {
+        // A touch carries its own position, while a mouse button is at the cursor.
+        let (pointer, pos) = if entry_name.starts_with("$ontouch") {
+            match (
+                data["$id"].as_u64(),
+                data["$x"].as_f32(),
+                data["$y"].as_f32(),
+            ) {
+                (Some(touch_id), Some(x), Some(y)) => (format!("touch{touch_id}"), [x, y]),
+                _ => return Vec::new(),
+            }
+        } else {
+            match self.cursor_op {
+                Some(pos) => (
+                    data["$button"].as_str().unwrap_or_default().to_string(),
+                    pos,
+                ),
+                None => return Vec::new(),
+            }
+        };
+
+        let detector = &mut self.input_provider.gesture_detector;
+        match entry_name {
+            "$onmousedown" | "$ontouchstart" => {
+                detector.press(&pointer, pos, Instant::now());
+
+                Vec::new()
+            }
+            "$onmousemove" | "$ontouchmove" => detector.move_to(&pointer, pos),
+            "$onmouseup" | "$ontouchend" | "$ontouchcancel" => {
+                detector.release(&pointer, pos, Instant::now())
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// called => the result = the vnode id of the body under the cursor
+    #[cfg(feature = "physics")]
+    fn pick(&self) -> Option<u64> {
+        let (origin, dir) = self.vision_manager.cursor_ray(self.cursor_op?)?;
+
+        self.physics_manager.pick(origin, dir)
+    }
+
+    /// called => the result = `None`, there being no bodies to pick
+    #[cfg(not(feature = "physics"))]
+    fn pick(&self) -> Option<u64> {
+        None
+    }
+
+    /// Let the event be propagated from the input elements subscribing to it, in the order of
+    /// their ids, see [world::propagate_from].
+    async fn dispatch_input(&mut self, entry_name: &str, data: &json::JsonValue) {
+        let mut target_v = self.input_provider.subscriber_v(entry_name);
+        target_v.sort_unstable();
+
+        world::propagate_from(self, &target_v, entry_name, data).await;
+    }
+
+    /// Let the world be simulated for the time since the last call, stepping the physics and
+    /// dispatching `$onstep` to the dynamic bodies as often as their cadences tick.
+    ///
+    /// `$onstep` carries `$dt`, the seconds the step stands for, and `$step`, the index of the
+    /// step.
+    async fn simulate(&mut self) {
+        let now = Instant::now();
+        let elapsed = self.fixed_elapsed_op.unwrap_or_else(|| {
+            self.last_simulate_op
+                .map(|last| now.duration_since(last))
+                .unwrap_or(Duration::ZERO)
+        });
+        self.last_simulate_op = Some(now);
+
+        #[cfg(feature = "physics")]
+        for _ in 0..self.physics_cadence.advance(elapsed) {
+            self.simulate_physics();
+        }
+
+        let dt = self.script_cadence.dt(elapsed);
+        for _ in 0..self.script_cadence.advance(elapsed) {
+            world::dispatch_step(self, dt).await;
+        }
+    }
+
+    /// called => the result = whether the element is a dynamic body
+    #[cfg(feature = "physics")]
+    fn is_dynamic(&self, ele: &AtomElement) -> bool {
+        match ele {
+            AtomElement::Physics(h) => self.physics_manager.is_dynamic(*h),
+            _ => false,
+        }
+    }
+
+    /// called => the result = `false`, there being no bodies
+    #[cfg(not(feature = "physics"))]
+    fn is_dynamic(&self, _ele: &AtomElement) -> bool {
+        false
+    }
+
+    /// Let the physics be stepped once, walking the fps controller and playing the collision
+    /// sounds.
+    #[cfg(feature = "physics")]
+    fn simulate_physics(&mut self) {
+        if let Some(fps) = &self.fps_op {
+            if let Some(AtomElement::Physics(h)) = self.element_mp.get(&fps.vnode_id) {
+                let action_map = &self.input_provider.action_map;
+                // The same axes as the fly camera, so `move_z` < 0 walks forward.
+                let (yaw_sin, yaw_cos) = self.vision_manager.camera_state().yaw().sin_cos();
+                let forward = vector![yaw_sin, 0.0, yaw_cos];
+                let right = vector![yaw_cos, 0.0, -yaw_sin];
+                let mut velocity =
+                    forward * action_map.value("move_z") + right * action_map.value("move_x");
+                if velocity.norm() > 1.0 {
+                    velocity.normalize_mut();
+                }
+
+                self.physics_manager.walk(
+                    *h,
+                    velocity * fps.speed,
+                    if action_map.value("jump") > 0.0 {
+                        Some(fps.jump_speed)
+                    } else {
+                        None
+                    },
+                );
+            }
+        }
+
+        let start = Instant::now();
+        self.physics_manager.step();
+        self.frame_stats.record_physics_step(start.elapsed());
+
+        #[cfg(feature = "audio")]
+        for (src, volume, position) in self.physics_manager.drain_collision_sound_v() {
+            self.audio_manager.play_oneshot(&src, volume, position);
+        }
+        #[cfg(not(feature = "audio"))]
+        self.physics_manager.drain_collision_sound_v();
+    }
+
+    /// Let the props be applied to the vnode while the class data it reads is watched, for
+    /// [Engine::refresh_watched] to apply them again when that data changes.
+    async fn apply_watched(
+        &mut self,
+        id: u64,
+        view_props: &ViewProps,
+        context: u64,
+        is_new: bool,
+    ) -> err::Result<()> {
+        // A vnode spawned while another is applied is watched on its own.
+        let outer_op = self.watch_read_op.lock().unwrap().replace(HashSet::new());
+
+        let result = self
+            .apply_props(id, view_props, context, is_new)
+            .await
+            .change_context(err::Error::Other);
+
+        let read_set = std::mem::replace(&mut *self.watch_read_op.lock().unwrap(), outer_op)
+            .unwrap_or_default();
+        if read_set.is_empty() {
+            self.watch_mp.remove(&id);
+        } else {
+            self.watch_mp.insert(id, (context, read_set));
+        }
+
+        result
+    }
+
+    /// Let the vnodes reading the class data changed since the last step be applied again.
+    ///
+    /// The data changed while they are applied again does not apply them once more, so a view
+    /// writing what it reads is not applied every step.
+    async fn refresh_watched(&mut self) {
+        if self.changed_data_set.is_empty() {
+            return;
+        }
+        let changed_data_set = std::mem::take(&mut self.changed_data_set);
+
+        self.watch_mp
+            .retain(|id, _| self.world.tree.vnode_mp.contains_key(id));
+        let mut id_v = self
+            .watch_mp
+            .iter()
+            .filter(|(_, (_, read_set))| !read_set.is_disjoint(&changed_data_set))
+            .map(|(id, _)| *id)
+            .collect::<Vec<u64>>();
+        id_v.sort();
+
+        for id in id_v {
+            let (context, view_props) =
+                match (self.watch_mp.get(&id), self.world.tree.vnode_mp.get(&id)) {
+                    (Some((context, _)), Some(vnode)) => (*context, vnode.view_props.clone()),
+                    _ => continue,
+                };
+
+            if let Err(e) = self.apply_watched(id, &view_props, context, false).await {
+                log::error!("failed to apply vnode {id} again: {e:?}");
+            }
+        }
+
+        self.changed_data_set.clear();
+    }
+
+    /// Let the event be handled by [Engine::event_handler] at the start of the next step.
+    pub fn push_event(&mut self, event: EngineEvent) {
+        self.event_queue.push_back(event);
+    }
+
+    /// called => the engine = stepped
+    ///
+    /// The events pushed are handled first, in order, then the views changed on disk are reloaded
+    /// and the vnodes reading the class data changed are applied again.
+    /// An element skipped for malformed props gets `$onerror` with `$message` here, propagated
+    /// from its vnode.
+    pub async fn step(&mut self) -> err::Result<()> {
+        while let Some(event) = self.event_queue.pop_front() {
+            let (entry_name, data) = event.to_entry();
+
+            self.event_handler(&entry_name, &data).await?;
+        }
+        if let Some(recorder) = &mut self.recorder_op {
+            recorder.record_step();
+        }
+        self.reload_views_if_due().await;
+        self.refresh_watched().await;
+
+        world::propagate_error_v(self).await;
+
+        // Only the world halts, so menus keep working while paused.
+        if !self.is_paused {
+            self.simulate().await;
+        }
+        self.report_hud().await;
+        self.refresh_inspector_if_due();
+        self.autosave_if_due().await;
+
+        #[cfg(feature = "physics")]
+        let watcher_op = match self.element_mp.get(&self.watcher_binding_body_id) {
+            Some(AtomElement::Physics(h)) => Some(*h),
+            _ => None,
+        };
+        #[cfg(feature = "physics")]
+        self.cc.track(watcher_op.and_then(|h| {
+            self.physics_manager
+                .physics_engine
+                .rigid_body_set
+                .get(h)
+                .map(|body| (Point3::from(*body.translation()), *body.linvel()))
+        }));
+        if let Some(id) = self.vision_manager.advance_path(Instant::now()) {
+            let _ = self.event_entry(id, "$onpathend", &json::Null).await;
+        }
+        // A camera3 or camera_path in the view tree takes the camera from the controller.
+        if !self.vision_manager.has_active_camera() {
+            self.cc
+                .update_camera(self.vision_manager.camera_state_mut());
+
+            // Let walls never hide what the camera turns around.
+            #[cfg(feature = "physics")]
+            if let Some(pivot) = self.cc.pivot_op() {
+                let camera_state = self.vision_manager.camera_state_mut();
+
+                if let Some(position) =
+                    self.physics_manager
+                        .unobstruct(pivot, *camera_state.position(), watcher_op)
+                {
+                    *camera_state.position_mut() = position;
+                }
+            }
+        }
+
+        let now = Instant::now();
+        for (gesture_name, gesture_data) in self.input_provider.gesture_detector.poll(now) {
+            self.dispatch_input(gesture_name, &gesture_data).await;
+        }
+        for key in self.input_provider.key_repeater.poll(now) {
+            self.dispatch_input("$onkeyrepeat", &json::object! { "$key": key })
+                .await;
+        }
+
+        let hover_op = self.pick();
+        if hover_op != self.hover_op {
+            if let Some(id) = self.hover_op {
+                world::propagate(self, id, "$onleave", &json::Null).await;
+            }
+            if let Some(id) = hover_op {
+                world::propagate(self, id, "$onhover", &json::Null).await;
+            }
+
+            self.hover_op = hover_op;
+        }
+
+        #[cfg(feature = "audio")]
+        self.step_audio().await;
+
+        Ok(())
+    }
+
+    /// Let the emitters follow their bodies, the listener follow the camera, and the sounds
+    /// loaded or ended be told to their vnodes.
+    #[cfg(feature = "audio")]
+    async fn step_audio(&mut self) {
+        #[cfg(feature = "physics")]
+        for (id, target) in self.audio_manager.target_v() {
+            if let Some(AtomElement::Physics(h)) = self.element_mp.get(&target) {
+                if let Some(body) = self.physics_manager.physics_engine.rigid_body_set.get(*h) {
+                    let pos = body.translation();
+
+                    self.audio_manager
+                        .set_emitter_position(id, [pos.x, pos.y, pos.z]);
+                }
+            }
+        }
+        self.audio_manager
+            .update_listener(self.vision_manager.camera_state());
+        let mut load_result_v = self.vision_manager.poll_load_v();
+        load_result_v.extend(self.audio_manager.poll_load_v());
+        for (id, result) in load_result_v {
+            let _ = match result {
+                Ok(()) => self.event_entry(id, "$onloaded", &json::Null).await,
+                Err(message) => {
+                    self.event_entry(id, "$onloaderror", &json::object! { "$message": message })
+                        .await
+                }
+            };
+        }
+        if !self.is_paused {
+            self.audio_manager.step();
+
+            for (id, entry_name) in self.audio_manager.poll_event_v() {
+                let _ = self.event_entry(id, entry_name, &json::Null).await;
+            }
+        }
+    }
+
+    /// called => the engine = rendered, unless the render cadence is not due
+    pub fn render(&mut self) -> err::Result<()> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_render_op
+            .map(|last| now.duration_since(last))
+            .unwrap_or(Duration::ZERO);
+        self.last_render_op = Some(now);
+        if self.render_cadence.advance(elapsed) == 0 {
+            return Ok(());
+        }
+
+        // Let the labels and the minimap follow their targets, like the bodies in the physics.
+        for (id, target) in self.vision_manager.label_target_v() {
+            if let Some((pos, _, _)) = self.transform(target) {
+                self.vision_manager.set_label_anchor(id, pos.into());
+            }
+        }
+        if let Some((pos, _, _)) = self
+            .vision_manager
+            .minimap_target_op()
+            .and_then(|target| self.transform(target))
+        {
+            self.vision_manager.set_minimap_center(pos.into());
+        }
+
+        let mut rp = self.vision_manager.render_pass()?;
+
+        inner::render_vnode(
+            &self.world.tree.vnode_mp,
+            &self.element_mp,
+            &mut rp,
+            self.scene_v.last().copied().unwrap_or(0),
+        )?;
+
+        let mut draw_count = rp.element_count();
+        rp.render()?;
+
+        for (i, root_id) in self.window_root_v.iter().enumerate() {
+            let mut rp = self.vision_manager.render_pass_of(i + 1)?;
+
+            inner::render_vnode(
+                &self.world.tree.vnode_mp,
+                &self.element_mp,
+                &mut rp,
+                *root_id,
+            )?;
+
+            draw_count += rp.element_count();
+            rp.render()?;
+        }
+
+        self.vision_manager.end_frame();
+
+        #[cfg(feature = "physics")]
+        let body_count = self.physics_manager.physics_engine.rigid_body_set.len();
+        #[cfg(not(feature = "physics"))]
+        let body_count = 0;
+
+        self.frame_stats.record_frame(now);
+        self.frame_stats.record_count(body_count, draw_count);
+
+        Ok(())
+    }
+}
+
+impl Engine {
+    /// Let the items be appended, past the auth and the middleware.
+    fn append_unchecked<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> std::pin::Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            // The classes of the parts compiled in or out by the features.
+            #[cfg(feature = "physics")]
+            if class == "@physics_params" {
+                let data = inner::object_of(&item_v)?;
+
+                physics::apply_params(
+                    self.physics_manager
+                        .physics_engine
+                        .integration_parameters_mut(),
+                    &data,
+                );
+
+                return Ok(());
+            }
+            #[cfg(feature = "audio")]
+            if class == "@audio_bus" {
+                let volume = item_v
+                    .first()
+                    .and_then(|volume| volume.parse::<f32>().ok())
+                    .ok_or(moon_class::err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("no volume for audio bus {source}"))?;
+
+                self.audio_manager.set_bus_volume(source, volume);
+
+                return Ok(());
+            }
+            #[cfg(feature = "audio")]
+            if class == "@fade" {
+                let id = source
+                    .parse::<u64>()
+                    .map_err(|_| moon_class::err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("{source} is not an audio element"))?;
+                let (volume, millis) = match (
+                    item_v.first().and_then(|volume| volume.parse::<f32>().ok()),
+                    item_v.get(1).and_then(|millis| millis.parse::<u64>().ok()),
+                ) {
+                    (Some(volume), Some(millis)) => (volume, millis),
+                    _ => {
+                        return Err(moon_class::err::Error::NotFound).attach_printable_lazy(|| {
+                            format!("no volume and duration to fade {source}")
+                        })
+                    }
+                };
+
+                self.audio_manager
+                    .fade(id, volume, Duration::from_millis(millis))
+                    .change_context(moon_class::err::Error::NotFound)?;
+
+                return Ok(());
+            }
+            #[cfg(feature = "audio")]
+            if class == "@audio_device" {
+                // No name means the default device.
+                let name_op = item_v.first().map(|name| name.as_str());
+
+                self.audio_manager
+                    .set_device(name_op)
+                    .change_context(moon_class::err::Error::NotFound)
+                    .attach_printable_lazy(|| {
+                        format!("failed to switch audio device to {name_op:?}")
+                    })?;
+
+                return Ok(());
+            }
+            #[cfg(feature = "clipboard")]
+            if class == "@clipboard" {
+                self.set_clipboard_text(&item_v.join("\n"))
+                    .change_context(moon_class::err::Error::NotFound)?;
+
+                return Ok(());
+            }
+
+            if class == "@new_size" && source == "@window" {
+                let data = inner::object_of(&item_v)?;
+
+                self.vision_manager.resize(PhysicalSize {
+                    width: inner::number_of(&data, "$width")? as u32,
+                    height: inner::number_of(&data, "$height")? as u32,
+                });
+
+                Ok(())
+            } else if class == "@new_acc" && source == "@camera" {
+                let data = inner::object_of(&item_v)?;
+
+                self.cc.amount_translation(
+                    inner::number_of(&data, "$x")?,
+                    inner::number_of(&data, "$y")?,
+                    inner::number_of(&data, "$z")?,
+                );
+
+                Ok(())
+            } else if class == "@new_rotation" && source == "@camera" {
+                let data = inner::object_of(&item_v)?;
+
+                self.cc.rorate(
+                    inner::number_of(&data, "$x")?,
+                    inner::number_of(&data, "$y")?,
+                );
+
+                Ok(())
+            } else if class == "@new_scroll" && source == "@camera" {
+                let amount = item_v
+                    .first()
+                    .and_then(|amount| amount.parse::<f32>().ok())
+                    .ok_or(moon_class::err::Error::NotFound)
+                    .attach_printable("no amount to scroll the camera")?;
+
+                self.cc.scroll(amount);
+
+                Ok(())
+            } else if class == "@camera_settings" && source == "@camera" {
+                let data = inner::object_of(&item_v)?;
+
+                if let Some(sensitivity) = prop::number(&data, "$sensitivity") {
+                    self.cc.set_sensitivity(sensitivity);
+                }
+                if let Some(invert_y) = prop::boolean(&data, "$invert_y") {
+                    self.cc.set_y_inverted(invert_y);
+                }
+                if let Some(speed) = prop::number(&data, "$speed") {
+                    self.cc.set_speed(speed);
+                }
+
+                Ok(())
+            } else if class == "@new_roll" && source == "@camera" {
+                let amount = item_v
+                    .first()
+                    .and_then(|amount| amount.parse::<f32>().ok())
+                    .ok_or(moon_class::err::Error::NotFound)
+                    .attach_printable("no amount to roll the camera")?;
+
+                self.cc.roll(amount);
+
+                Ok(())
+            } else if class == "@camera_mode" && source == "@camera" {
+                match item_v.first().map(|mode| mode.as_str()) {
+                    Some("fly") => self.cc.fly(),
+                    Some("follow") => {
+                        let number_v = item_v[1..]
+                            .iter()
+                            .map(|n| n.parse::<f32>())
+                            .collect::<Result<Vec<f32>, _>>()
+                            .change_context(moon_class::err::Error::NotFound)
+                            .attach_printable(
+                                "invalid offset, look-ahead or smoothing of the follow camera",
+                            )?;
+
+                        self.cc.follow(
+                            if number_v.len() >= 3 {
+                                vector![number_v[0], number_v[1], number_v[2]]
+                            } else {
+                                vector![0.0, 2.0, 5.0]
+                            },
+                            number_v.get(3).copied().unwrap_or(0.0),
+                            number_v.get(4).copied().unwrap_or(5.0),
+                        );
+                    }
+                    Some("orbit") => {
+                        let number_v = item_v[1..]
+                            .iter()
+                            .map(|n| n.parse::<f32>())
+                            .collect::<Result<Vec<f32>, _>>()
+                            .change_context(moon_class::err::Error::NotFound)
+                            .attach_printable("invalid target or distance of the orbit camera")?;
+                        if number_v.len() < 3 {
+                            return Err(moon_class::err::Error::NotFound)
+                                .attach_printable("no target for the orbit camera");
+                        }
+
+                        self.cc.orbit(
+                            point![number_v[0], number_v[1], number_v[2]],
+                            number_v.get(3).copied(),
+                            self.vision_manager.camera_state(),
+                        );
+                    }
+                    _ => {
+                        return Err(moon_class::err::Error::NotFound).attach_printable_lazy(|| {
+                            format!("unsupported camera mode {item_v:?}")
+                        })
+                    }
+                }
+
+                Ok(())
+            } else if class == "@camera_pos" && source == "@camera" {
+                // The same signs as the getter.
+                let number_v = item_v
+                    .iter()
+                    .map(|n| n.parse::<f32>())
+                    .collect::<Result<Vec<f32>, _>>()
+                    .change_context(moon_class::err::Error::NotFound)
+                    .attach_printable("invalid position of the camera")?;
+                if number_v.len() < 3 {
+                    return Err(moon_class::err::Error::NotFound)
+                        .attach_printable("no position for the camera");
+                }
+
+                *self.vision_manager.camera_state_mut().position_mut() =
+                    point![-number_v[0], -number_v[1], -number_v[2]];
+
+                Ok(())
+            } else if matches!(class, "@camera_yaw" | "@camera_pitch" | "@camera_roll")
+                && source == "@camera"
+            {
+                let degrees = item_v
+                    .first()
+                    .and_then(|degrees| degrees.parse::<f32>().ok())
+                    .ok_or(moon_class::err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("no degrees for {class}"))?;
+
+                let camera_state = self.vision_manager.camera_state_mut();
+                if class == "@camera_yaw" {
+                    *camera_state.yaw_mut() = degrees.to_radians();
+                } else if class == "@camera_roll" {
+                    *camera_state.roll_mut() = degrees.to_radians();
+                } else {
+                    *camera_state.pitch_mut() =
+                        degrees.to_radians().clamp(-SAFE_FRAC_PI_2, SAFE_FRAC_PI_2);
+                }
+
+                Ok(())
+            } else if class == "@camera_shake" && source == "@camera" {
+                let number_v = item_v
+                    .iter()
+                    .map(|n| n.parse::<f32>())
+                    .collect::<Result<Vec<f32>, _>>()
+                    .change_context(moon_class::err::Error::NotFound)
+                    .attach_printable("invalid amplitude, frequency or duration of the shake")?;
+                if number_v.len() < 3 {
+                    return Err(moon_class::err::Error::NotFound)
+                        .attach_printable("no amplitude, frequency and duration for the shake");
+                }
+
+                if !number_v[..2].iter().all(|n| n.is_finite()) {
+                    return Err(moon_class::err::Error::NotFound).attach_printable(
+                        "the amplitude and the frequency of a shake need to be finite",
+                    );
+                }
+                let duration = Duration::try_from_secs_f32(number_v[2].max(0.0) / 1000.0)
+                    .change_context(moon_class::err::Error::NotFound)
+                    .attach_printable("the duration of a shake needs to be finite")?;
+
+                self.cc.shake(number_v[0], number_v[1], duration);
+
+                Ok(())
+            } else if class == "@camera_projection" && source == "@camera" {
+                let number_v = item_v
+                    .iter()
+                    .map(|n| n.parse::<f32>())
+                    .collect::<Result<Vec<f32>, _>>()
+                    .change_context(moon_class::err::Error::NotFound)
+                    .attach_printable("invalid fov, near or far of the camera")?;
+                if number_v.len() < 3 {
+                    return Err(moon_class::err::Error::NotFound)
+                        .attach_printable("no fov, near and far for the camera");
+                }
+                let (fov, near, far) = (number_v[0], number_v[1], number_v[2]);
+                if !res::Perspective::is_valid_fov(fov)
+                    || !(near > 0.0 && near < far && far.is_finite())
+                {
+                    return Err(moon_class::err::Error::NotFound).attach_printable_lazy(|| {
+                        format!("the camera needs 0 < fov < 180 and 0 < near < far, got {item_v:?}")
+                    });
+                }
+
+                self.vision_manager.set_perspective(res::Perspective {
+                    fovy: fov.to_radians(),
+                    znear: near,
+                    zfar: far,
+                });
+
+                Ok(())
+            } else if class == "@ambient" && source == "@scene" {
+                // `[r, g, b, intensity]`
+                let number_v = item_v
+                    .iter()
+                    .map(|n| n.parse::<f32>())
+                    .collect::<Result<Vec<f32>, _>>()
+                    .change_context(moon_class::err::Error::NotFound)
+                    .attach_printable("invalid color or intensity of the ambient")?;
+                if number_v.len() < 4 {
+                    return Err(moon_class::err::Error::NotFound)
+                        .attach_printable("no r, g, b and intensity for the ambient");
+                }
+
+                self.vision_manager.set_ambient(Ambient {
+                    color: vector![number_v[0], number_v[1], number_v[2], 1.0],
+                    intensity: number_v[3].max(0.0),
+                });
+
+                Ok(())
+            } else if class == "@taa" && source == "@scene" {
+                self.vision_manager
+                    .set_taa(item_v.first().map(|s| s.as_str()) == Some("true"));
+
+                Ok(())
+            } else if class == "@occlusion_culling" && source == "@scene" {
+                self.vision_manager
+                    .set_occlusion_culling(item_v.first().map(|s| s.as_str()) == Some("true"));
+
+                Ok(())
+            } else if class == "@stereo" && source == "@scene" {
+                // The distance between the eyes in meters, none or not above 0 for one eye.
+                self.vision_manager.set_stereo(
+                    item_v
+                        .first()
+                        .and_then(|s| s.parse::<f32>().ok())
+                        .filter(|ipd| *ipd > 0.0),
+                );
+
+                Ok(())
+            } else if source == "@window"
+                && matches!(
+                    class,
+                    "@title"
+                        | "@fullscreen"
+                        | "@decorations"
+                        | "@inner_size"
+                        | "@min_size"
+                        | "@max_size"
+                        | "@cursor_icon"
+                )
+            {
+                let bool_of = |item_v: &Vec<String>| -> moon_class::err::Result<bool> {
+                    item_v
+                        .first()
+                        .and_then(|item| item.parse::<bool>().ok())
+                        .ok_or(moon_class::err::Error::NotFound)
+                        .attach_printable_lazy(|| format!("{class} needs true or false"))
+                };
+
+                let command = match class {
+                    "@title" => {
+                        runner::WindowCommand::Title(item_v.first().cloned().unwrap_or_default())
+                    }
+                    "@fullscreen" => runner::WindowCommand::Fullscreen(bool_of(&item_v)?),
+                    "@decorations" => runner::WindowCommand::Decorations(bool_of(&item_v)?),
+                    "@inner_size" => runner::WindowCommand::InnerSize(inner::size_of(&item_v)?),
+                    // No size for no limit.
+                    "@min_size" if item_v.is_empty() => runner::WindowCommand::MinInnerSize(None),
+                    "@min_size" => {
+                        runner::WindowCommand::MinInnerSize(Some(inner::size_of(&item_v)?))
+                    }
+                    "@max_size" if item_v.is_empty() => runner::WindowCommand::MaxInnerSize(None),
+                    "@max_size" => {
+                        runner::WindowCommand::MaxInnerSize(Some(inner::size_of(&item_v)?))
+                    }
+                    _ => {
+                        let name = item_v
+                            .first()
+                            .map(|name| name.as_str())
+                            .unwrap_or("default");
+
+                        runner::WindowCommand::CursorIcon(
+                            name.parse()
+                                .map_err(|_| moon_class::err::Error::NotFound)
+                                .attach_printable_lazy(|| format!("no cursor icon named {name}"))?,
+                        )
+                    }
+                };
+                self.window_command_v.push(command);
+
+                Ok(())
+            } else if class == "@cursor" && source == "@window" {
+                for op in &item_v {
+                    let command_v = self
+                        .input_provider
+                        .apply_cursor(op)
+                        .change_context(moon_class::err::Error::NotFound)?;
+                    self.window_command_v.extend(command_v);
+                }
+
+                Ok(())
+            } else if class == "@key_repeat" && source == "@input" {
+                let (delay, rate) = match (
+                    item_v.first().and_then(|delay| delay.parse::<u64>().ok()),
+                    item_v.get(1).and_then(|rate| rate.parse::<f32>().ok()),
+                ) {
+                    (Some(delay), Some(rate)) => (delay, rate),
+                    _ => {
+                        return Err(moon_class::err::Error::NotFound)
+                            .attach_printable("no delay and rate to repeat keys")
+                    }
+                };
+
+                self.input_provider
+                    .key_repeater
+                    .configure(Duration::from_millis(delay), rate);
+
+                Ok(())
+            } else if class == "@action" {
+                let binding_v = item_v
+                    .iter()
+                    .map(|text| {
+                        input::Binding::parse(text)
+                            .ok_or(moon_class::err::Error::NotFound)
+                            .attach_printable_lazy(|| format!("invalid binding {text}"))
+                    })
+                    .collect::<moon_class::err::Result<Vec<input::Binding>>>()?;
+
+                self.input_provider.action_map.bind(source, binding_v);
+
+                Ok(())
+            } else if class == "@spawn" {
+                // `[class, props]` spawned under the vnode `source`, see [Engine::spawn].
+                let parent_id = source
+                    .parse::<u64>()
+                    .map_err(|_| moon_class::err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("{source} is not a vnode id"))?;
+                let view_class = item_v
+                    .first()
+                    .ok_or(moon_class::err::Error::NotFound)
+                    .attach_printable("no class to spawn")?;
+                let props = match item_v.get(1) {
+                    Some(props) => json::parse(props)
+                        .change_context(moon_class::err::Error::NotFound)
+                        .attach_printable_lazy(|| format!("invalid props to spawn {view_class}"))?,
+                    None => json::Null,
+                };
+
+                self.spawn(parent_id, view_class, props)
+                    .await
+                    .change_context(moon_class::err::Error::NotFound)?;
+
+                Ok(())
+            } else if class == "@log" {
+                let level = source
+                    .parse::<log::Level>()
+                    .map_err(|_| moon_class::err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("{source} is not a log level"))?;
+
+                for message in &item_v {
+                    self.log(level, message)
+                        .await
+                        .change_context(moon_class::err::Error::NotFound)?;
+                }
+
+                Ok(())
+            } else if class == "@log_mirror" {
+                self.is_log_mirrored = item_v.first().map(|item| item.as_str()) != Some("false");
+
+                Ok(())
+            } else if class == "@console" {
+                if let Some(console) = &mut self.console_op {
+                    console.is_open = item_v
+                        .first()
+                        .and_then(|item| item.parse::<bool>().ok())
+                        .unwrap_or(!console.is_open);
+                }
+                self.refresh_console();
+
+                Ok(())
+            } else if class == "@inspector" {
+                let is_open = item_v
+                    .first()
+                    .and_then(|item| item.parse::<bool>().ok())
+                    .unwrap_or(!self.is_inspector_open);
+                self.set_inspector_open(is_open);
+
+                Ok(())
+            } else if class == "@inspect" {
+                let vnode_id = source
+                    .parse::<u64>()
+                    .map_err(|_| moon_class::err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("{source} is not a vnode id"))?;
+                let data = inner::object_of(&item_v)?;
+
+                for (key, value) in data.entries() {
+                    self.inspect_prop(vnode_id, key, value.clone())
+                        .change_context(moon_class::err::Error::NotFound)?;
+                }
+
+                Ok(())
+            } else if class == "@evict_assets" {
+                let count = self.evict_unused_assets();
+                log::debug!("{count} unused assets evicted");
+
+                Ok(())
+            } else if class == "$stop_propagation" {
+                self.world.stop_propagation();
+
+                Ok(())
+            } else if class == "@autosave" {
+                // The seconds between saves, none or 0 for never.
+                let seconds = item_v
+                    .first()
+                    .map(|seconds| seconds.parse::<f32>())
+                    .transpose()
+                    .change_context(moon_class::err::Error::NotFound)
+                    .attach_printable("invalid seconds of autosave")?
+                    .unwrap_or(0.0);
+
+                self.set_autosave_interval(Duration::try_from_secs_f32(seconds).ok());
+
+                Ok(())
+            } else if class == "@paused" {
+                self.set_paused(item_v.first().map(|s| s.as_str()) == Some("true"));
+
+                Ok(())
+            } else if class == "@random_seed" {
+                // `@random` draws the numbers of the seed from now on.
+                let seed = item_v
+                    .first()
+                    .and_then(|seed| seed.parse::<u64>().ok())
+                    .ok_or(moon_class::err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("{item_v:?} is not a seed"))?;
+                self.world.set_seed(seed);
+
+                Ok(())
+            } else if class == "@scene_push" {
+                // `[class, props]` pushed as the active scene, see [Engine::scene_push].
+                let view_class = item_v
+                    .first()
+                    .ok_or(moon_class::err::Error::NotFound)
+                    .attach_printable("no class to push as a scene")?;
+                let props = match item_v.get(1) {
+                    Some(props) => json::parse(props)
+                        .change_context(moon_class::err::Error::NotFound)
+                        .attach_printable_lazy(|| format!("invalid props of scene {view_class}"))?,
+                    None => json::Null,
+                };
+
+                self.scene_push(view_class, props)
+                    .await
+                    .change_context(moon_class::err::Error::NotFound)?;
+
+                Ok(())
+            } else if class == "@scene_pop" {
+                self.scene_pop()
+                    .await
+                    .change_context(moon_class::err::Error::NotFound)?;
+
+                Ok(())
+            } else if class == "@despawn" {
+                // The vnode `source` is despawned, see [Engine::despawn].
+                let vnode_id = source
+                    .parse::<u64>()
+                    .map_err(|_| moon_class::err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("{source} is not a vnode id"))?;
+
+                self.despawn(vnode_id)
+                    .await
+                    .change_context(moon_class::err::Error::NotFound)?;
+
+                Ok(())
+            } else if let Some(class_fn) = self.class_fn_mp.get(class) {
+                class_fn(source, item_v)?;
+
+                Ok(())
+            } else {
+                snapshot::remember_origin(
+                    &*self.data_manager,
+                    &mut self.world.origin_data_mp,
+                    class,
+                    source,
+                )
+                .await?;
+                self.changed_data_set
+                    .insert((class.to_string(), source.to_string()));
+                if let Some(vnode_id) = source
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|id| self.world.tree.vnode_mp.contains_key(id))
+                {
+                    self.vnode_class_mp
+                        .entry(vnode_id)
+                        .or_default()
+                        .insert(class.to_string());
+                }
+
+                self.data_manager.append(class, source, item_v).await
+            }
+        })
+    }
+
+    /// Let the items be removed, past the auth and the middleware.
+    fn remove_unchecked<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> std::pin::Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        if class == "@action" {
+            self.input_provider.action_map.unbind(source);
+
+            return Box::pin(async { Ok(()) });
+        }
+
+        Box::pin(async move {
+            snapshot::remember_origin(
+                &*self.data_manager,
+                &mut self.world.origin_data_mp,
+                class,
+                source,
+            )
+            .await?;
+            self.changed_data_set
+                .insert((class.to_string(), source.to_string()));
+
+            self.data_manager.remove(class, source, item_v).await
+        })
+    }
+
+    /// called => the result = the items got, past the auth and the middleware
+    fn get_unchecked<'a, 'a1, 'a2, 'f>(
+        &'a self,
+        class: &'a1 str,
+        source: &'a2 str,
+    ) -> std::pin::Pin<Box<dyn Fu<Output = moon_class::err::Result<Vec<String>>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            match class {
+                #[cfg(feature = "physics")]
+                "@moon_world_pos" => {
+                    let vnode_id = source
+                        .parse::<u64>()
+                        .map_err(|_| moon_class::err::Error::NotFound)
+                        .attach_printable_lazy(|| format!("{source} is not a vnode id"))?;
+
+                    let body_op = match self.element_mp.get(&vnode_id) {
+                        Some(AtomElement::Physics(h)) => {
+                            self.physics_manager.physics_engine.rigid_body_set.get(*h)
+                        }
+                        _ => None,
+                    };
+                    if let Some(body) = body_op {
+                        let pos = body.translation();
 
                         Ok(vec![
                             pos.x.to_string(),
@@ -463,6 +3145,53 @@ impl AsClassManager for Engine {
                         })
                     }
                 }
+                "@ambient" => {
+                    let ambient = self.vision_manager.ambient();
+
+                    Ok(vec![
+                        ambient.color.x.to_string(),
+                        ambient.color.y.to_string(),
+                        ambient.color.z.to_string(),
+                        ambient.intensity.to_string(),
+                    ])
+                }
+                "@taa" => Ok(vec![self.vision_manager.is_taa_enabled().to_string()]),
+                "@occlusion_culling" => Ok(vec![self
+                    .vision_manager
+                    .is_occlusion_culling_enabled()
+                    .to_string()]),
+                "@stereo" => Ok(self
+                    .vision_manager
+                    .stereo_ipd_op()
+                    .map(|ipd| ipd.to_string())
+                    .into_iter()
+                    .collect()),
+                #[cfg(feature = "physics")]
+                "@physics_params" => Ok(vec![physics::params_json(
+                    self.physics_manager.physics_engine.integration_parameters(),
+                )
+                .dump()]),
+                "@moon_world_transform" => {
+                    // `[x, y, z, qx, qy, qz, qw, sx, sy, sz]` of a body or a cube3.
+                    let vnode_id = source
+                        .parse::<u64>()
+                        .map_err(|_| moon_class::err::Error::NotFound)
+                        .attach_printable_lazy(|| format!("{source} is not a vnode id"))?;
+
+                    let (pos, rotation, scale) = self
+                        .transform(vnode_id)
+                        .ok_or(moon_class::err::Error::NotFound)
+                        .attach_printable_lazy(|| {
+                            format!("not such AtomElement with id {vnode_id}")
+                        })?;
+
+                    Ok(pos
+                        .iter()
+                        .chain(rotation.coords.iter())
+                        .chain(scale.iter())
+                        .map(|n| n.to_string())
+                        .collect())
+                }
                 "@camera_pos" => {
                     let pos = self.vision_manager.camera_state().position();
 
@@ -472,8 +3201,186 @@ impl AsClassManager for Engine {
                         (-pos.z).to_string(),
                     ])
                 }
-                _ => self.data_manager.get(class, source).await,
+                "@camera_yaw" => Ok(vec![self
+                    .vision_manager
+                    .camera_state()
+                    .yaw()
+                    .to_degrees()
+                    .to_string()]),
+                "@camera_pitch" => Ok(vec![self
+                    .vision_manager
+                    .camera_state()
+                    .pitch()
+                    .to_degrees()
+                    .to_string()]),
+                "@camera_settings" => Ok(vec![json::object! {
+                    "$sensitivity": [self.cc.sensitivity().to_string()],
+                    "$invert_y": [self.cc.is_y_inverted().to_string()],
+                    "$speed": [self.cc.speed().to_string()],
+                }
+                .dump()]),
+                "@camera_roll" => Ok(vec![self
+                    .vision_manager
+                    .camera_state()
+                    .roll()
+                    .to_degrees()
+                    .to_string()]),
+                "@action" => Ok(vec![self
+                    .input_provider
+                    .action_map
+                    .value(source)
+                    .to_string()]),
+                "@key_state" => {
+                    if source.is_empty() {
+                        // No key means all held keys.
+                        Ok(self.input_provider.held_key_v())
+                    } else {
+                        Ok(vec![self
+                            .input_provider
+                            .is_key_held(&input::key_of(source))
+                            .to_string()])
+                    }
+                }
+                "@touch_state" => {
+                    if source.is_empty() {
+                        // No touch id means the ids of all touches.
+                        Ok(self
+                            .input_provider
+                            .touch_id_v()
+                            .into_iter()
+                            .map(|touch_id| touch_id.to_string())
+                            .collect())
+                    } else {
+                        let touch_id = source
+                            .parse::<u64>()
+                            .map_err(|_| moon_class::err::Error::NotFound)
+                            .attach_printable_lazy(|| format!("{source} is not a touch id"))?;
+
+                        Ok(self
+                            .input_provider
+                            .touch_op(touch_id)
+                            .map(|pos| vec![pos[0].to_string(), pos[1].to_string()])
+                            .unwrap_or_default())
+                    }
+                }
+                "@paused" => Ok(vec![self.is_paused.to_string()]),
+                "@random" => Ok(vec![self.world.random().to_string()]),
+                "@elements_of_class" => {
+                    // View classes like `Enemy` never reach `create_element`, so the vnodes
+                    // are looked through rather than the elements.
+                    let mut id_v = self
+                        .world
+                        .tree
+                        .vnode_mp
+                        .iter()
+                        .filter(|(_, vnode)| vnode.view_props.class == source)
+                        .map(|(id, _)| *id)
+                        .collect::<Vec<u64>>();
+                    id_v.sort();
+
+                    Ok(id_v.into_iter().map(|id| id.to_string()).collect())
+                }
+                "@scene" => Ok(self.scene_v.iter().map(|id| id.to_string()).collect()),
+                "@inspector" => Ok(self.inspector_line_v()),
+                "@frame_stats" => Ok(vec![self.frame_stats.to_json().dump()]),
+                "@console" => Ok(self
+                    .console_op
+                    .as_ref()
+                    .map(|console| console.output_v().to_vec())
+                    .unwrap_or_default()),
+                #[cfg(feature = "clipboard")]
+                "@clipboard" => Ok(vec![self
+                    .clipboard_text()
+                    .change_context(moon_class::err::Error::NotFound)?]),
+                "@spawn" => Ok(source
+                    .parse::<u64>()
+                    .ok()
+                    .and_then(|parent_id| self.spawn_mp.get(&parent_id))
+                    .map(|id_v| id_v.iter().map(|id| id.to_string()).collect())
+                    .unwrap_or_default()),
+                #[cfg(feature = "audio")]
+                "@audio_bus" => Ok(vec![self.audio_manager.bus_volume(source).to_string()]),
+                #[cfg(feature = "audio")]
+                "@audio_device" => Ok(self
+                    .audio_manager
+                    .device_name_op()
+                    .map(|name| vec![name.to_string()])
+                    .unwrap_or_default()),
+                _ => match self.class_fn_mp.get(class) {
+                    Some(class_fn) => class_fn(source, Vec::new()),
+                    None => {
+                        if let Some(read_set) = &mut *self.watch_read_op.lock().unwrap() {
+                            read_set.insert((class.to_string(), source.to_string()));
+                        }
+
+                        self.data_manager.get(class, source).await
+                    }
+                },
+            }
+        })
+    }
+}
+
+impl AsClassManager for Engine {
+    /// The principal is checked first, then the middleware gets the items, whether the class is
+    /// of the data manager or of the engine.
+    fn append<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> std::pin::Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            self.check_access(class, source, true)?;
+            let item_v = self.write_through_middleware(WriteOp::Append, class, source, item_v)?;
+
+            self.append_unchecked(class, source, item_v).await
+        })
+    }
+
+    fn remove<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> std::pin::Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            self.check_access(class, source, true)?;
+            let item_v = self.write_through_middleware(WriteOp::Remove, class, source, item_v)?;
+
+            self.remove_unchecked(class, source, item_v).await
+        })
+    }
+
+    fn get<'a, 'a1, 'a2, 'f>(
+        &'a self,
+        class: &'a1 str,
+        source: &'a2 str,
+    ) -> std::pin::Pin<Box<dyn Fu<Output = moon_class::err::Result<Vec<String>>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            self.check_access(class, source, false)?;
+            let mut item_v = self.get_unchecked(class, source).await?;
+
+            for middleware in &self.dm_middleware_v {
+                item_v = middleware.on_get(class, source, item_v)?;
             }
+
+            Ok(item_v)
         })
     }
 }
@@ -489,30 +3396,124 @@ impl AsElementProvider for Engine {
         };
 
         let atom_element = match prefix {
+            #[cfg(feature = "physics")]
+            "Physics" => match self
+                .physics_manager
+                .try_create_element(vnode_id, suffix, props)
+            {
+                Ok(h) => {
+                    if prop::boolean(props, "$watcher") == Some(true) {
+                        self.watcher_binding_body_id = vnode_id;
+                    }
+
+                    AtomElement::Physics(h)
+                }
+                Err(e) => return self.world.skip_element(vnode_id, e),
+            },
+            "Vision" => match self
+                .vision_manager
+                .try_create_element(vnode_id, suffix, props)
+            {
+                Ok(id) => AtomElement::Vision(id),
+                Err(e) => return self.world.skip_element(vnode_id, e),
+            },
+            #[cfg(feature = "audio")]
+            "Audio" => {
+                AtomElement::Audio(self.audio_manager.create_element(vnode_id, suffix, props))
+            }
+            #[cfg(not(feature = "physics"))]
             "Physics" => {
-                AtomElement::Physics(self.physics_manager.create_element(vnode_id, suffix, props))
+                return self.world.skip_element(
+                    vnode_id,
+                    error_stack::Report::new(err::Error::UnsupportedElement)
+                        .attach_printable(format!("{class} needs the physics feature")),
+                )
             }
-            "Vision" => {
-                AtomElement::Vision(self.vision_manager.create_element(vnode_id, suffix, props))
+            #[cfg(not(feature = "audio"))]
+            "Audio" => {
+                return self.world.skip_element(
+                    vnode_id,
+                    error_stack::Report::new(err::Error::UnsupportedElement)
+                        .attach_printable(format!("{class} needs the audio feature")),
+                )
             }
             "Input" => {
                 AtomElement::Input(self.input_provider.create_element(vnode_id, suffix, props))
             }
+            "" if suffix == "console" => {
+                let mut console = console::Console::new(vnode_id);
+                console.is_open = prop::boolean(props, "$open").unwrap_or(false);
+                self.console_op = Some(console);
+                self.refresh_console();
+
+                return vnode_id;
+            }
+            #[cfg(feature = "physics")]
+            "" if suffix == "fps_controller" => {
+                let h = match self
+                    .physics_manager
+                    .try_create_element(vnode_id, suffix, props)
+                {
+                    Ok(h) => h,
+                    Err(e) => return self.world.skip_element(vnode_id, e),
+                };
+
+                self.fps_op = Some(FpsController::new(vnode_id, props));
+                self.watcher_binding_body_id = vnode_id;
+                self.cc
+                    .first_person(prop::number(props, "$eye_height").unwrap_or(0.7));
+                // WASD and space, unless the view binds the actions itself.
+                for (action, binding_v) in [
+                    ("move_x", vec!["key:d", "key:a:-1"]),
+                    ("move_z", vec!["key:s", "key:w:-1"]),
+                    ("jump", vec!["key:space"]),
+                ] {
+                    if !self.input_provider.action_map.is_bound(action) {
+                        self.input_provider.action_map.bind(
+                            action,
+                            binding_v
+                                .into_iter()
+                                .filter_map(input::Binding::parse)
+                                .collect(),
+                        );
+                    }
+                }
+
+                AtomElement::Physics(h)
+            }
             _ => {
                 return vnode_id;
             }
         };
 
         self.element_mp.insert(vnode_id, atom_element);
+        self.world.props_mp.insert(vnode_id, props.clone());
+        if let Some(scene) = self.scene_v.last() {
+            self.scene_of_mp.insert(vnode_id, *scene);
+        }
 
         vnode_id
     }
 
     /// Let the element specified by the id be deleted.
     fn delete_element(&mut self, id: u64) {
+        self.scene_of_mp.remove(&id);
+        self.world.props_mp.remove(&id);
+        if self.console_op.as_ref().map(|console| console.vnode_id) == Some(id) {
+            self.console_op = None;
+            self.refresh_console();
+        }
+        #[cfg(feature = "physics")]
+        if self.fps_op.as_ref().map(|fps| fps.vnode_id) == Some(id) {
+            self.fps_op = None;
+            self.cc.fly();
+        }
+
         if let Some(atom_ele) = self.element_mp.remove(&id) {
             match atom_ele {
-                AtomElement::Audio(_) => todo!(),
+                #[cfg(feature = "audio")]
+                AtomElement::Audio(id) => self.audio_manager.delete_element(id),
+                #[cfg(feature = "physics")]
                 AtomElement::Physics(rigid_body_handle) => {
                     self.physics_manager.delete_element(rigid_body_handle)
                 }
@@ -523,26 +3524,41 @@ impl AsElementProvider for Engine {
     }
 
     /// Let the element specified by the id be updated by this props.
+    ///
+    /// Nothing is touched if the props are the same as the last ones applied, and the physics and
+    /// vision elements only touch their resources for the keys changed.
     fn update_element(&mut self, id: u64, class: &str, props: &json::JsonValue) {
         let (_, suffix) = match class.find(':') {
             Some(pos) => (&class[0..pos], &class[pos + 1..]),
             None => ("", class),
         };
 
+        let changed_key_set = self.world.apply_props(id, props);
+        if changed_key_set.is_empty() {
+            return;
+        }
+
         if let Some(atom_ele) = self.element_mp.get_mut(&id) {
             match atom_ele {
-                AtomElement::Audio(_) => todo!(),
+                #[cfg(feature = "audio")]
+                AtomElement::Audio(id) => {
+                    self.audio_manager.update_element(*id, suffix, props);
+                }
+                #[cfg(feature = "physics")]
                 AtomElement::Physics(rigid_body_handle) => {
-                    self.physics_manager
-                        .update_element(*rigid_body_handle, suffix, props);
-                    if let Some(watcher) = props["$watcher"][0].as_str() {
-                        if watcher == "true" {
-                            self.watcher_binding_body_id = id;
-                        }
+                    self.physics_manager.update_changed(
+                        *rigid_body_handle,
+                        suffix,
+                        props,
+                        &changed_key_set,
+                    );
+                    if prop::boolean(props, "$watcher") == Some(true) {
+                        self.watcher_binding_body_id = id;
                     }
                 }
                 AtomElement::Vision(id) => {
-                    self.vision_manager.update_element(*id, suffix, props);
+                    self.vision_manager
+                        .update_changed(*id, suffix, props, &changed_key_set);
                 }
                 AtomElement::Input(id) => {
                     self.input_provider.update_element(*id, suffix, props);
@@ -561,32 +3577,48 @@ impl AsViewManager for Engine {
         'a: 'f,
         'a1: 'f,
     {
+        // The engine reads the views for itself, whatever the principal may read.
         Box::pin(async move {
-            let rs = self.get("view", class).await.unwrap();
-            if rs.is_empty() {
-                None
-            } else {
-                Some(rs_2_str(&rs))
+            if let Some(read_set) = &mut *self.watch_read_op.lock().unwrap() {
+                read_set.insert(("view".to_string(), class.to_string()));
             }
+
+            tree::class_view_of(&*self.data_manager, class).await
         })
     }
 
     fn get_vnode(&self, id: &u64) -> Option<&VNode> {
-        self.vnode_mp.get(id)
+        self.world.tree.vnode_mp.get(id)
     }
 
     fn get_vnode_mut(&mut self, id: &u64) -> Option<&mut VNode> {
-        self.vnode_mp.get_mut(id)
+        self.world.tree.vnode_mp.get_mut(id)
     }
 
     fn new_vnode(&mut self, context: u64) -> u64 {
-        let new_id = self.unique_id;
-        self.unique_id += 1;
-        self.vnode_mp.insert(new_id, VNode::new(context));
-        new_id
+        self.world.tree.new_vnode(context)
     }
 
     fn rm_vnode(&mut self, id: u64) -> Option<VNode> {
-        self.vnode_mp.remove(&id)
+        self.world.tree.rm_vnode(id)
+    }
+}
+
+impl world::AsWorld for Engine {
+    fn world(&self) -> &world::World {
+        &self.world
+    }
+
+    fn world_mut(&mut self) -> &mut world::World {
+        &mut self.world
+    }
+
+    /// The dynamic bodies of the active scene get `$onstep`.
+    fn stepped_id_v(&self) -> Vec<u64> {
+        self.element_mp
+            .iter()
+            .filter(|(id, ele)| self.is_in_active_scene(**id) && self.is_dynamic(ele))
+            .map(|(id, _)| *id)
+            .collect()
     }
 }
@@ -0,0 +1,92 @@
+//! Help the input of a client engine be played on a server engine.
+
+use std::collections::HashMap;
+
+use error_stack::ResultExt;
+use view_manager::VNode;
+
+use crate::{err, util::prop};
+
+/// An input handled by [crate::Engine::event_handler], tagged with the player who gave it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputMessage {
+    pub player: String,
+    pub entry_name: String,
+    pub data: json::JsonValue,
+}
+
+impl InputMessage {
+    pub fn new(player: &str, entry_name: &str, data: &json::JsonValue) -> Self {
+        Self {
+            player: player.to_string(),
+            entry_name: entry_name.to_string(),
+            data: data.clone(),
+        }
+    }
+
+    /// called => the result = the message as a text to be sent
+    pub fn dump(&self) -> String {
+        json::object! {
+            "$player": self.player.as_str(),
+            "$entry_name": self.entry_name.as_str(),
+            "$data": self.data.clone(),
+        }
+        .dump()
+    }
+
+    /// called => the result = the message dumped by [InputMessage::dump]
+    pub fn parse(s: &str) -> err::Result<Self> {
+        let object = json::parse(s)
+            .change_context(err::Error::Other)
+            .attach_printable("an input message is not JSON")?;
+
+        match (object["$player"].as_str(), object["$entry_name"].as_str()) {
+            (Some(player), Some(entry_name)) => Ok(Self::new(player, entry_name, &object["$data"])),
+            _ => Err(err::Error::NotFound)
+                .attach_printable_lazy(|| format!("no $player or $entry_name in {object}")),
+        }
+    }
+
+    /// called => the result = the data with `$player` added, for the handlers to tell players
+    /// apart
+    pub fn tagged_data(&self) -> json::JsonValue {
+        let mut data = if self.data.is_object() {
+            self.data.clone()
+        } else {
+            json::object! { "$value": self.data.clone() }
+        };
+        data["$player"] = self.player.as_str().into();
+
+        data
+    }
+}
+
+/// called => the result = the vnodes whose `$owner` prop is the player, sorted
+pub fn owned_vnode_v(vnode_mp: &HashMap<u64, VNode>, player: &str) -> Vec<u64> {
+    let mut id_v = vnode_mp
+        .iter()
+        .filter(|(_, vnode)| {
+            prop::string(&vnode.view_props.props, "$owner").as_deref() == Some(player)
+        })
+        .map(|(id, _)| *id)
+        .collect::<Vec<u64>>();
+    id_v.sort();
+
+    id_v
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_input_message() {
+        let message = super::InputMessage::new("p1", "$onkeydown", &json::object! { "$key": "w" });
+
+        assert_eq!(
+            super::InputMessage::parse(&message.dump()).unwrap(),
+            message
+        );
+        assert_eq!(message.tagged_data()["$player"], "p1");
+        assert!(super::InputMessage::parse("{}").is_err());
+        assert!(super::InputMessage::parse("x").is_err());
+    }
+}
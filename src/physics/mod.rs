@@ -1,6 +1,57 @@
+use std::num::NonZeroUsize;
+
 use nalgebra::Vector3;
 use rapier3d::{parry::query::Ray, prelude::*};
 
+use crate::util::prop;
+
+/// Let the parameters in `{$dt, $solver_iterations, $damping, $allowed_penetration}` be set,
+/// each one optional.
+///
+/// `$damping` is the damping ratio of the contacts and `$allowed_penetration` is in meters.
+pub fn apply_params(integration_parameters: &mut IntegrationParameters, data: &json::JsonValue) {
+    if let Some(dt) = prop::number(data, "$dt").filter(|dt| *dt > 0.0) {
+        integration_parameters.dt = dt;
+    }
+    if let Some(count) =
+        prop::number(data, "$solver_iterations").and_then(|count| NonZeroUsize::new(count as usize))
+    {
+        integration_parameters.num_solver_iterations = count;
+    }
+    if let Some(ratio) = prop::number(data, "$damping") {
+        integration_parameters.contact_damping_ratio = ratio;
+    }
+    if let Some(length) = prop::number(data, "$allowed_penetration") {
+        integration_parameters.normalized_allowed_linear_error =
+            length / integration_parameters.length_unit;
+    }
+}
+
+/// called => the result = `{$dt, $solver_iterations, $damping, $allowed_penetration}` of the
+/// parameters
+pub fn params_json(integration_parameters: &IntegrationParameters) -> json::JsonValue {
+    json::object! {
+        "$dt": integration_parameters.dt,
+        "$solver_iterations": integration_parameters.num_solver_iterations.get(),
+        "$damping": integration_parameters.contact_damping_ratio,
+        "$allowed_penetration": integration_parameters.allowed_linear_error(),
+    }
+}
+
+/// The state of the world, to be restored later.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct PhysicsState {
+    rigid_body_set: RigidBodySet,
+    collider_set: ColliderSet,
+    impulse_joint_set: ImpulseJointSet,
+    multibody_joint_set: MultibodyJointSet,
+    island_manager: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
+}
+
 pub struct PhysicsEngine {
     pub rigid_body_set: RigidBodySet,
     pub collider_set: ColliderSet,
@@ -81,6 +132,50 @@ impl PhysicsEngine {
         );
     }
 
+    /// called => the result = the state of the world
+    pub fn snapshot(&self) -> PhysicsState {
+        PhysicsState {
+            rigid_body_set: self.rigid_body_set.clone(),
+            collider_set: self.collider_set.clone(),
+            impulse_joint_set: self.impulse_joint_set.clone(),
+            multibody_joint_set: self.multibody_joint_set.clone(),
+            island_manager: self.island_manager.clone(),
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            ccd_solver: self.ccd_solver.clone(),
+            query_pipeline: self.query_pipeline.clone(),
+        }
+    }
+
+    /// Let the world be back to the state.
+    pub fn restore(&mut self, state: &PhysicsState) {
+        let state = state.clone();
+
+        self.rigid_body_set = state.rigid_body_set;
+        self.collider_set = state.collider_set;
+        self.impulse_joint_set = state.impulse_joint_set;
+        self.multibody_joint_set = state.multibody_joint_set;
+        self.island_manager = state.island_manager;
+        self.broad_phase = state.broad_phase;
+        self.narrow_phase = state.narrow_phase;
+        self.ccd_solver = state.ccd_solver;
+        self.query_pipeline = state.query_pipeline;
+    }
+
+    /// Let each step advance the world by `dt` seconds.
+    pub fn set_dt(&mut self, dt: f32) {
+        self.integration_parameters.dt = dt;
+    }
+
+    pub fn integration_parameters(&self) -> &IntegrationParameters {
+        &self.integration_parameters
+    }
+
+    /// Let the parameters be changed, from the next step on.
+    pub fn integration_parameters_mut(&mut self) -> &mut IntegrationParameters {
+        &mut self.integration_parameters
+    }
+
     pub fn set_event_handler(&mut self, event_handler: Box<dyn EventHandler>) {
         self.event_handler = event_handler;
     }
@@ -0,0 +1,188 @@
+//! Help a run of the engine be recorded and played again exactly, step by step.
+
+use std::path::Path;
+
+use error_stack::ResultExt;
+use view_manager::ViewProps;
+
+use crate::err;
+
+/// The seconds a step stands for when the physics runs once per step.
+pub const DEFAULT_DT: f32 = 1.0 / 60.0;
+
+/// The entry, the class data it starts from and the events handled before each step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub entry_class: String,
+    pub entry_props: json::JsonValue,
+    /// `(class, source, item_v)` appended to the data manager before the entry is applied.
+    pub data_v: Vec<(String, String, Vec<String>)>,
+    /// The fixed seconds of a step.
+    pub dt: f32,
+    /// The events handled before each step, in order.
+    pub step_v: Vec<Vec<(String, json::JsonValue)>>,
+    pending_v: Vec<(String, json::JsonValue)>,
+}
+
+impl Replay {
+    pub fn new(entry: &ViewProps, dt: f32) -> Self {
+        Self {
+            entry_class: entry.class.clone(),
+            entry_props: entry.props.clone(),
+            data_v: Vec::new(),
+            dt,
+            step_v: Vec::new(),
+            pending_v: Vec::new(),
+        }
+    }
+
+    pub fn entry(&self) -> ViewProps {
+        ViewProps {
+            class: self.entry_class.clone(),
+            props: self.entry_props.clone(),
+        }
+    }
+
+    pub fn record_data(&mut self, class: &str, source: &str, item_v: Vec<String>) {
+        self.data_v
+            .push((class.to_string(), source.to_string(), item_v));
+    }
+
+    /// Let the event be recorded for the next step.
+    pub fn record_event(&mut self, entry_name: &str, data: &json::JsonValue) {
+        self.pending_v.push((entry_name.to_string(), data.clone()));
+    }
+
+    /// Let the events recorded since the last step belong to this step.
+    pub fn record_step(&mut self) {
+        self.step_v.push(std::mem::take(&mut self.pending_v));
+    }
+
+    /// Let the events recorded after the last step end the replay as a step of their own.
+    pub fn finish(&mut self) {
+        if !self.pending_v.is_empty() {
+            self.record_step();
+        }
+    }
+
+    /// called => the result = `{$entry, $data_v, $dt, $step_v}`
+    pub fn to_json(&self) -> json::JsonValue {
+        json::object! {
+            "$entry": {
+                "$class": self.entry_class.as_str(),
+                "$props": self.entry_props.clone(),
+            },
+            "$data_v": self.data_v.iter().map(|(class, source, item_v)| json::object! {
+                "$class": class.as_str(),
+                "$source": source.as_str(),
+                "$item_v": item_v.clone(),
+            }).collect::<Vec<json::JsonValue>>(),
+            "$dt": self.dt,
+            "$step_v": self.step_v.iter().map(|event_v| {
+                event_v.iter().map(|(entry_name, data)| json::object! {
+                    "$entry_name": entry_name.as_str(),
+                    "$data": data.clone(),
+                }).collect::<Vec<json::JsonValue>>()
+            }).collect::<Vec<Vec<json::JsonValue>>>(),
+        }
+    }
+
+    /// called => the result = the replay dumped by [Replay::to_json]
+    pub fn parse(s: &str) -> err::Result<Self> {
+        let object = json::parse(s)
+            .change_context(err::Error::Other)
+            .attach_printable("a replay is not JSON")?;
+
+        let class = object["$entry"]["$class"]
+            .as_str()
+            .ok_or(err::Error::NotFound)
+            .attach_printable("no $entry.$class in the replay")?;
+        let dt = object["$dt"].as_f32().unwrap_or(DEFAULT_DT);
+        if !(dt > 0.0 && dt.is_finite()) {
+            return Err(err::Error::Other).attach_printable_lazy(|| {
+                format!("$dt needs to be positive and finite, got {dt}")
+            });
+        }
+        let mut replay = Self::new(
+            &ViewProps {
+                class: class.to_string(),
+                props: object["$entry"]["$props"].clone(),
+            },
+            dt,
+        );
+
+        for data in object["$data_v"].members() {
+            match (data["$class"].as_str(), data["$source"].as_str()) {
+                (Some(class), Some(source)) => replay.record_data(
+                    class,
+                    source,
+                    data["$item_v"]
+                        .members()
+                        .filter_map(|item| item.as_str().map(|item| item.to_string()))
+                        .collect(),
+                ),
+                _ => {
+                    return Err(err::Error::NotFound)
+                        .attach_printable_lazy(|| format!("no $class or $source in {data}"))
+                }
+            }
+        }
+        for event_v in object["$step_v"].members() {
+            for event in event_v.members() {
+                let entry_name = event["$entry_name"]
+                    .as_str()
+                    .ok_or(err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("no $entry_name in {event}"))?;
+
+                replay.record_event(entry_name, &event["$data"]);
+            }
+            replay.record_step();
+        }
+
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> err::Result<()> {
+        std::fs::write(path, self.to_json().dump())
+            .change_context(err::Error::Other)
+            .attach_printable_lazy(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> err::Result<Self> {
+        let s = std::fs::read_to_string(path)
+            .change_context(err::Error::Other)
+            .attach_printable_lazy(|| format!("failed to read {}", path.display()))?;
+
+        Self::parse(&s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use view_manager::ViewProps;
+
+    #[test]
+    fn test_parse() {
+        let mut replay = super::Replay::new(
+            &ViewProps {
+                class: "Main".to_string(),
+                props: json::object! { "$level": 1 },
+            },
+            0.01,
+        );
+        replay.record_data("score", "@player", vec!["3".to_string()]);
+        replay.record_event("$onkeydown", &json::object! { "$key": "w" });
+        replay.record_step();
+        replay.record_step();
+        replay.record_event("$onclose", &json::object! {});
+        replay.finish();
+
+        let parsed = super::Replay::parse(&replay.to_json().dump()).unwrap();
+
+        assert_eq!(parsed, replay);
+        assert_eq!(parsed.step_v.len(), 3);
+        assert!(super::Replay::parse(r#"{"$dt":0.01}"#).is_err());
+        assert!(super::Replay::parse(r#"{"$entry":{"$class":"Main"},"$dt":0}"#).is_err());
+        assert!(super::Replay::parse(r#"{"$entry":{"$class":"Main"},"$dt":1e39}"#).is_err());
+    }
+}
@@ -1,14 +1,24 @@
+#[cfg(feature = "physics")]
+use std::sync::mpsc::{channel, Receiver};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     f32::consts::PI,
-    sync::{mpsc::channel, Arc},
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
-use drawer::{camera::CameraState, Body, Light, ThreeLook};
+use drawer::{camera::CameraState, structs::NoiseParams, Ambient, Body, Light, ThreeLook};
 use error_stack::ResultExt;
-use nalgebra::{point, vector, Matrix4, Vector3};
-use rapier3d::prelude::{
-    ColliderBuilder, IntegrationParameters, RigidBodyBuilder, RigidBodyHandle,
+use nalgebra::{
+    point, vector, Matrix3, Matrix4, Point3, Quaternion, UnitQuaternion, Vector3, Vector4,
+};
+#[cfg(feature = "physics")]
+use rapier3d::{
+    parry::query::Ray,
+    prelude::{
+        ActiveEvents, ColliderBuilder, CollisionEvent, ContactForceEvent, DMatrix,
+        IntegrationParameters, QueryFilter, RigidBodyBuilder, RigidBodyHandle,
+    },
 };
 use view_manager::AsElementProvider;
 use wgpu::{
@@ -16,10 +26,249 @@ use wgpu::{
     BufferUsages, SurfaceTexture,
 };
 
-use crate::err;
+#[cfg(feature = "audio")]
+use crate::asset::AssetLoader;
+use crate::{asset::AssetCache, err, runner, util::prop};
 
+#[cfg(feature = "audio")]
+use super::audio;
+use super::input;
+#[cfg(feature = "physics")]
 use super::physics;
 
+/// called => the result = the three numbers of the prop, or `None` if it is absent
+fn number3_of(props: &json::JsonValue, key: &str) -> err::Result<Option<[f32; 3]>> {
+    if props[key].is_null() {
+        return Ok(None);
+    }
+
+    prop::number3(props, key)
+        .map(Some)
+        .ok_or(err::Error::InvalidProp)
+        .attach_printable_lazy(|| format!("{key} needs three numbers, got {}", props[key]))
+}
+
+/// called => the result = the RGBA color of the `$color` prop, or `None` if it is absent
+pub fn color_of(props: &json::JsonValue) -> err::Result<Option<Vector4<f32>>> {
+    if props["$color"].is_null() {
+        return Ok(None);
+    }
+
+    match prop::number_v(props, "$color").as_deref() {
+        Some([r, g, b]) => Ok(Some(vector![*r, *g, *b, 1.0])),
+        Some([r, g, b, a, ..]) => Ok(Some(vector![*r, *g, *b, *a])),
+        _ => Err(err::Error::InvalidProp).attach_printable_lazy(|| {
+            format!(
+                "$color needs three or four numbers, got {}",
+                props["$color"]
+            )
+        }),
+    }
+}
+
+/// called => the result = the rotation of the `$rotation` prop, or `None` if it is absent
+///
+/// Three numbers are the euler angles in degrees, roll, pitch and yaw; four numbers are a
+/// quaternion, `[x, y, z, w]`.
+fn rotation_of(props: &json::JsonValue) -> err::Result<Option<UnitQuaternion<f32>>> {
+    if props["$rotation"].is_null() {
+        return Ok(None);
+    }
+
+    match prop::number_v(props, "$rotation").as_deref() {
+        Some([roll, pitch, yaw]) => Ok(Some(UnitQuaternion::from_euler_angles(
+            roll.to_radians(),
+            pitch.to_radians(),
+            yaw.to_radians(),
+        ))),
+        Some([x, y, z, w]) => Ok(Some(UnitQuaternion::from_quaternion(Quaternion::new(
+            *w, *x, *y, *z,
+        )))),
+        _ => Err(err::Error::InvalidProp).attach_printable_lazy(|| {
+            format!(
+                "$rotation needs three or four numbers, got {}",
+                props["$rotation"]
+            )
+        }),
+    }
+}
+
+/// called => the result = the scale of the `$scale` prop, or `None` if it is absent
+///
+/// One number scales all the axes the same.
+pub fn scale_of(props: &json::JsonValue) -> err::Result<Option<Vector3<f32>>> {
+    if props["$scale"].is_null() {
+        return Ok(None);
+    }
+
+    match prop::number_v(props, "$scale").as_deref() {
+        Some([s]) => Ok(Some(vector![*s, *s, *s])),
+        Some([x, y, z]) => Ok(Some(vector![*x, *y, *z])),
+        _ => Err(err::Error::InvalidProp).attach_printable_lazy(|| {
+            format!("$scale needs one or three numbers, got {}", props["$scale"])
+        }),
+    }
+}
+
+/// called => the result = the model matrix scaling, then rotating, then translating
+fn model_m_of(
+    pos: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    scale: Vector3<f32>,
+) -> Matrix4<f32> {
+    Matrix4::new_translation(&pos)
+        * rotation.to_homogeneous()
+        * Matrix4::new_nonuniform_scaling(&scale)
+}
+
+/// called => the result = the translation, the rotation and the scale of a model matrix made by
+/// [model_m_of]
+pub fn transform_of(model_m: &Matrix4<f32>) -> (Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>) {
+    let pos = model_m.fixed_view::<3, 1>(0, 3).into_owned();
+    let linear = model_m.fixed_view::<3, 3>(0, 0).into_owned();
+    let scale = Vector3::from_fn(|i, _| linear.column(i).norm());
+    // A zero scale leaves no rotation to tell.
+    let rotation_m = Matrix3::from_fn(|r, c| linear[(r, c)] / scale[c].max(f32::EPSILON));
+
+    (pos, UnitQuaternion::from_matrix(&rotation_m), scale)
+}
+
+/// called => the result = the colors of the faces of a cube3, by `$face_colors`, six colors of
+/// three or four numbers each in the order of [drawer::structs::Point3InputArray::cube_faces],
+/// else six times `$color`
+fn face_colors_of(props: &json::JsonValue) -> err::Result<[Vector4<f32>; 6]> {
+    if props["$face_colors"].is_null() {
+        return Ok([color_of(props)?.unwrap_or(vector![1.0, 1.0, 1.0, 1.0]); 6]);
+    }
+
+    let number_v = prop::number_v(props, "$face_colors").unwrap_or_default();
+    let channel_count = match number_v.len() {
+        18 => 3,
+        24 => 4,
+        _ => {
+            return Err(err::Error::InvalidProp).attach_printable_lazy(|| {
+                format!(
+                    "$face_colors needs six colors of three or four numbers, got {}",
+                    props["$face_colors"]
+                )
+            })
+        }
+    };
+
+    Ok(std::array::from_fn(|face| {
+        let channel_v = &number_v[face * channel_count..(face + 1) * channel_count];
+
+        vector![
+            channel_v[0],
+            channel_v[1],
+            channel_v[2],
+            channel_v.get(3).copied().unwrap_or(1.0)
+        ]
+    }))
+}
+
+/// called => the result = the vertex buffer of a cube with the faces in the colors, shared by
+/// the cubes in the same colors
+fn cube_buf(
+    mesh_cache: &mut AssetCache<wgpu::Buffer>,
+    device: &wgpu::Device,
+    color_v: [Vector4<f32>; 6],
+) -> Arc<wgpu::Buffer> {
+    mesh_cache.get_or_make(&format!("cube:{color_v:?}"), || {
+        vertex_buf_of(
+            device,
+            &drawer::structs::Point3InputArray::cube_faces(color_v),
+        )
+    })
+}
+
+/// called => the result = the image of the `$texture` prop, a path, shared by the bodies of the
+/// same path, or `None` if the prop is absent
+fn texture_of(
+    texture_cache: &mut AssetCache<wgpu::Texture>,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    props: &json::JsonValue,
+) -> err::Result<Option<Arc<wgpu::Texture>>> {
+    let path = match prop::string(props, "$texture") {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+
+    texture_cache
+        .get_or_load(&path, || {
+            drawer::load_texture(device, queue, &path).change_context(err::Error::InvalidProp)
+        })
+        .map(Some)
+}
+
+/// called => the result = the vertex buffer of the triangles
+fn vertex_buf_of(device: &wgpu::Device, array: &drawer::structs::Point3InputArray) -> wgpu::Buffer {
+    device.create_buffer_init(&BufferInitDescriptor {
+        label: None,
+        contents: bytemuck::cast_slice(array.vertex_v()),
+        usage: BufferUsages::VERTEX,
+    })
+}
+
+/// called => the result = the width, the depth, the resolution and the noise of a terrain3, by
+/// `$width`, `$depth`, `$resolution`, `$seed`, `$amplitude`, `$frequency` and `$octaves`
+fn terrain_of(props: &json::JsonValue) -> (f32, f32, usize, NoiseParams) {
+    let default = NoiseParams::default();
+    let noise_params = NoiseParams {
+        seed: prop::number(props, "$seed").map_or(default.seed, |seed| seed as u32),
+        amplitude: prop::number(props, "$amplitude").unwrap_or(default.amplitude),
+        frequency: prop::number(props, "$frequency").unwrap_or(default.frequency),
+        octaves: prop::number(props, "$octaves").map_or(default.octaves, |n| n as u32),
+        ..default
+    };
+
+    (
+        prop::number(props, "$width").unwrap_or(32.0),
+        prop::number(props, "$depth").unwrap_or(32.0),
+        prop::number(props, "$resolution").map_or(32, |n| n.clamp(1.0, 1024.0) as usize),
+        noise_params,
+    )
+}
+
+/// called => the result = the triangles a body of the class is drawn with, as by the props, or
+/// `None` if the class has no body of its own
+pub fn vertex_array_of(
+    class: &str,
+    props: &json::JsonValue,
+) -> err::Result<Option<drawer::structs::Point3InputArray>> {
+    let color = color_of(props)?.unwrap_or(vector![1.0, 1.0, 1.0, 1.0]);
+
+    Ok(match class {
+        "cube3" => Some(drawer::structs::Point3InputArray::cube_faces(
+            face_colors_of(props)?,
+        )),
+        "terrain3" => {
+            let (width, depth, resolution, noise_params) = terrain_of(props);
+
+            Some(drawer::structs::Point3InputArray::terrain(
+                color,
+                width,
+                depth,
+                resolution,
+                &noise_params,
+            ))
+        }
+        "label3" => prop::string(props, "$text")
+            .filter(|text| !text.is_empty())
+            .map(|text| drawer::structs::Point3InputArray::text(color, &text)),
+        _ => None,
+    })
+}
+
+/// called => the result = the duration in milliseconds specified by the prop
+fn millis_of(props: &json::JsonValue, key: &str) -> Option<Duration> {
+    prop::number(props, key)
+        .filter(|millis| *millis >= 0.0)
+        .map(|millis| Duration::from_millis(millis as u64))
+}
+
+#[cfg(feature = "physics")]
 mod inner {
     use std::sync::mpsc::Sender;
 
@@ -92,32 +341,203 @@ mod inner {
     }
 }
 
+/// The farthest distance at which a body can be picked by the cursor.
+#[cfg(feature = "physics")]
+const PICK_DISTANCE: f32 = 500.0;
+
+/// How far below its bottom a body still stands on the ground.
+#[cfg(feature = "physics")]
+const GROUND_TOLERANCE: f32 = 0.05;
+
+/// How far the camera keeps from the geometry in front of it.
+#[cfg(feature = "physics")]
+const CAMERA_RADIUS: f32 = 0.2;
+
+/// The total contact force at which a collision sound plays at full volume.
+#[cfg(feature = "physics")]
+const COLLISION_FORCE_OF_FULL_VOLUME: f32 = 500.0;
+
+#[cfg(feature = "physics")]
 pub struct PhysicsElementProvider {
     pub physics_engine: physics::PhysicsEngine,
+
+    collision_event_rx: Receiver<CollisionEvent>,
+    force_event_rx: Receiver<ContactForceEvent>,
+    collision_sound_mp: HashMap<RigidBodyHandle, String>,
 }
 
+#[cfg(feature = "physics")]
 impl PhysicsElementProvider {
     pub fn new(integration_parameters: IntegrationParameters) -> Self {
-        let (collision_sender, _collision_event_rx) = channel();
-        let (force_sender, _force_event_rx) = channel();
+        let (collision_sender, collision_event_rx) = channel();
+        let (force_sender, force_event_rx) = channel();
         let mut physics_engine = physics::PhysicsEngine::new(integration_parameters);
         physics_engine.set_event_handler(Box::new(inner::InnerEventHandler::new(
             collision_sender,
             force_sender,
         )));
 
-        Self { physics_engine }
+        Self {
+            physics_engine,
+            collision_event_rx,
+            force_event_rx,
+            collision_sound_mp: HashMap::new(),
+        }
     }
 
     pub fn step(&mut self) {
         self.physics_engine.step();
     }
-}
 
-impl AsElementProvider for PhysicsElementProvider {
-    type H = RigidBodyHandle;
+    /// called => the result = whether the body is dynamic, which gets `$onstep`
+    pub fn is_dynamic(&self, h: RigidBodyHandle) -> bool {
+        self.physics_engine
+            .rigid_body_set
+            .get(h)
+            .is_some_and(|body| body.is_dynamic())
+    }
+
+    /// Let each step advance the world by `dt` seconds.
+    pub fn set_dt(&mut self, dt: f32) {
+        self.physics_engine.set_dt(dt);
+    }
+
+    /// Let the world be back to the state, forgetting the collisions not handled yet, and the
+    /// sounds of the bodies be those of their props.
+    pub fn restore<'a>(
+        &mut self,
+        state: &physics::PhysicsState,
+        body_v: impl Iterator<Item = (RigidBodyHandle, &'a json::JsonValue)>,
+    ) {
+        self.physics_engine.restore(state);
+
+        self.collision_event_rx.try_iter().for_each(drop);
+        self.force_event_rx.try_iter().for_each(drop);
+
+        // The colliders of the state already listen to the collisions of their sounds.
+        self.collision_sound_mp = body_v
+            .filter_map(|(h, props)| {
+                props["$collision_sound"][0]
+                    .as_str()
+                    .map(|src| (h, src.to_string()))
+            })
+            .collect();
+    }
+
+    /// called => the result = (src, volume, position) of the sounds caused by the last step
+    pub fn drain_collision_sound_v(&mut self) -> Vec<(String, f32, [f32; 3])> {
+        let mut force_mp = HashMap::new();
+        for event in self.force_event_rx.try_iter() {
+            force_mp.insert(
+                (event.collider1, event.collider2),
+                event.total_force_magnitude,
+            );
+        }
+
+        let mut sound_v = vec![];
+        for event in self.collision_event_rx.try_iter() {
+            if let CollisionEvent::Started(collider1, collider2, _) = event {
+                let force = force_mp
+                    .get(&(collider1, collider2))
+                    .or_else(|| force_mp.get(&(collider2, collider1)))
+                    .copied()
+                    .unwrap_or(0.0);
+                if force <= 0.0 {
+                    continue;
+                }
+
+                for collider in [collider1, collider2] {
+                    let body_op = self
+                        .physics_engine
+                        .collider_set
+                        .get(collider)
+                        .and_then(|collider| collider.parent());
+
+                    if let Some(h) = body_op {
+                        if let (Some(src), Some(body)) = (
+                            self.collision_sound_mp.get(&h),
+                            self.physics_engine.rigid_body_set.get(h),
+                        ) {
+                            let pos = body.translation();
+
+                            sound_v.push((
+                                src.clone(),
+                                (force / COLLISION_FORCE_OF_FULL_VOLUME).min(1.0),
+                                [pos.x, pos.y, pos.z],
+                            ));
+                        }
+                    }
+                }
+            }
+        }
 
-    fn create_element(&mut self, _: u64, class: &str, props: &json::JsonValue) -> RigidBodyHandle {
+        sound_v
+    }
+
+    /// called => the result = the vnode id of the first body hit by the ray
+    pub fn pick(&self, origin: Point3<f32>, dir: Vector3<f32>) -> Option<u64> {
+        let (collider, _) = self.physics_engine.cast_ray(
+            &Ray::new(origin, dir),
+            PICK_DISTANCE,
+            true,
+            QueryFilter::default(),
+        )?;
+        let h = self.physics_engine.collider_set.get(collider)?.parent()?;
+
+        Some(self.physics_engine.rigid_body_set.get(h)?.user_data as u64)
+    }
+
+    /// called => the result = the position pulled in front of the geometry between the pivot and
+    /// the camera, or `None` if the camera is seen clearly
+    ///
+    /// The body specified by `exclude_op` never blocks, nor does the body the pivot is inside,
+    /// the geometry beyond it still pulling the camera.
+    pub fn unobstruct(
+        &self,
+        pivot: Point3<f32>,
+        camera: Point3<f32>,
+        exclude_op: Option<RigidBodyHandle>,
+    ) -> Option<Point3<f32>> {
+        let offset = camera - pivot;
+        let distance = offset.norm();
+        if distance <= f32::EPSILON {
+            return None;
+        }
+        let dir = offset / distance;
+
+        let mut filter = QueryFilter::default();
+        if let Some(h) = exclude_op {
+            filter = filter.exclude_rigid_body(h);
+        }
+        let max_toi = distance + CAMERA_RADIUS;
+        let (_, mut toi) =
+            self.physics_engine
+                .cast_ray(&Ray::new(pivot, dir), max_toi, true, filter)?;
+        if toi <= f32::EPSILON {
+            // The pivot is inside a body, so the ray is cast again from where it leaves it.
+            let (_, exit) =
+                self.physics_engine
+                    .cast_ray(&Ray::new(pivot, dir), max_toi, false, filter)?;
+            let (_, rest) = self.physics_engine.cast_ray(
+                &Ray::new(pivot + dir * exit, dir),
+                max_toi - exit,
+                true,
+                filter,
+            )?;
+            toi = exit + rest;
+        }
+
+        Some(pivot + dir * (toi - CAMERA_RADIUS).clamp(0.0, distance))
+    }
+
+    /// called => the result = the handle of the body created, or an error if the props are
+    /// malformed
+    pub fn try_create_element(
+        &mut self,
+        vnode_id: u64,
+        class: &str,
+        props: &json::JsonValue,
+    ) -> err::Result<RigidBodyHandle> {
         match class {
             "cube3" => {
                 log::debug!("props = {props}");
@@ -130,48 +550,215 @@ impl AsElementProvider for PhysicsElementProvider {
 
                 log::debug!("body_type = {body_type}");
 
-                let pos = if props["$position"].is_array() {
-                    let pos = props["$position"]
-                        .members()
-                        .into_iter()
-                        .map(|n| n.as_str().unwrap().parse().unwrap())
-                        .collect::<Vec<f32>>();
-
-                    vector![pos[0], pos[1], pos[2]]
-                } else {
-                    vector![0.0, 0.0, 0.0]
-                };
+                let pos = number3_of(props, "$position")?
+                    .map(Vector3::from)
+                    .unwrap_or(vector![0.0, 0.0, 0.0]);
 
-                inner::add_body(
+                let h = inner::add_body(
                     self,
                     match body_type {
-                        "fixed" => RigidBodyBuilder::fixed().translation(pos).build(),
-                        "dynamic" => RigidBodyBuilder::dynamic().translation(pos).build(),
-                        _ => panic!("unsupported body type '{body_type}'"),
-                    },
+                        "fixed" => RigidBodyBuilder::fixed(),
+                        "dynamic" => RigidBodyBuilder::dynamic(),
+                        _ => {
+                            return Err(err::Error::InvalidProp).attach_printable_lazy(|| {
+                                format!("unsupported body type '{body_type}'")
+                            })
+                        }
+                    }
+                    .translation(pos)
+                    // Let the body be traced back to its vnode when picked.
+                    .user_data(vnode_id as u128)
+                    .build(),
                     vec![ColliderBuilder::cuboid(0.5, 0.5, 0.5)
                         .translation(vector![0.5, 0.5, -0.5])
                         .build()],
-                )
+                );
+
+                self.set_collision_sound(h, props["$collision_sound"][0].as_str());
+
+                Ok(h)
+            }
+            "terrain3" => {
+                let pos = number3_of(props, "$position")?
+                    .map(Vector3::from)
+                    .unwrap_or(vector![0.0, 0.0, 0.0]);
+                let (width, depth, resolution, noise_params) = terrain_of(props);
+                if !(width.is_finite() && width > 0.0 && depth.is_finite() && depth > 0.0) {
+                    return Err(err::Error::Physics).attach_printable_lazy(|| {
+                        format!("a heightfield needs a positive size, got {width} x {depth}")
+                    });
+                }
+                let height_v = noise_params.height_v(width, depth, resolution);
+
+                Ok(inner::add_body(
+                    self,
+                    RigidBodyBuilder::fixed()
+                        .translation(pos)
+                        .user_data(vnode_id as u128)
+                        .build(),
+                    vec![ColliderBuilder::heightfield(
+                        DMatrix::from_fn(resolution + 1, resolution + 1, |row, column| {
+                            height_v[row][column]
+                        }),
+                        vector![width, 1.0, depth],
+                    )
+                    .build()],
+                ))
             }
-            _ => panic!("unsupported tag '{class}' in PhysicsManager"),
+            "fps_controller" => {
+                let pos = number3_of(props, "$position")?
+                    .map(Vector3::from)
+                    .unwrap_or(vector![0.0, 0.0, 0.0]);
+                let height = prop::number(props, "$height").unwrap_or(1.8);
+                let radius = prop::number(props, "$radius").unwrap_or(0.3);
+
+                Ok(inner::add_body(
+                    self,
+                    // Let the capsule stand upright whatever it bumps into.
+                    RigidBodyBuilder::dynamic()
+                        .translation(pos)
+                        .lock_rotations()
+                        .user_data(vnode_id as u128)
+                        .build(),
+                    vec![
+                        ColliderBuilder::capsule_y((height * 0.5 - radius).max(0.0), radius)
+                            .friction(0.0)
+                            .build(),
+                    ],
+                ))
+            }
+            _ => Err(err::Error::UnsupportedElement)
+                .attach_printable_lazy(|| format!("unsupported class '{class}' in physics")),
         }
     }
 
-    fn update_element(&mut self, _: Self::H, class: &str, _props: &json::JsonValue) {
+    /// Let the body be updated by the props, only for the keys changed.
+    pub fn update_changed(
+        &mut self,
+        h: RigidBodyHandle,
+        class: &str,
+        props: &json::JsonValue,
+        changed_key_set: &HashSet<String>,
+    ) {
         match class {
+            "cube3" => {
+                if changed_key_set.contains("$collision_sound") {
+                    self.set_collision_sound(h, props["$collision_sound"][0].as_str());
+                }
+            }
             _ => (),
         }
     }
 
+    /// Let the body take part in the simulation, or be left out of it until enabled again.
+    pub fn set_enabled(&mut self, h: RigidBodyHandle, is_enabled: bool) {
+        if let Some(body) = self.physics_engine.rigid_body_set.get_mut(h) {
+            body.set_enabled(is_enabled);
+        }
+    }
+
+    /// Let the body walk at the horizontal velocity, and jump at the speed if `jump_op` is given
+    /// and it stands on something.
+    pub fn walk(&mut self, h: RigidBodyHandle, velocity: Vector3<f32>, jump_op: Option<f32>) {
+        let is_grounded = jump_op.is_some() && self.is_grounded(h);
+
+        if let Some(body) = self.physics_engine.rigid_body_set.get_mut(h) {
+            let mut linvel = vector![velocity.x, body.linvel().y, velocity.z];
+            if let (Some(jump), true) = (jump_op, is_grounded) {
+                linvel.y = jump;
+            }
+
+            body.set_linvel(linvel, true);
+        }
+    }
+
+    /// called => the result = whether something is right below the body
+    fn is_grounded(&self, h: RigidBodyHandle) -> bool {
+        let body = match self.physics_engine.rigid_body_set.get(h) {
+            Some(body) => body,
+            None => return false,
+        };
+        // The distance from the center of the body to its bottom.
+        let depth = body
+            .colliders()
+            .iter()
+            .filter_map(|collider| self.physics_engine.collider_set.get(*collider))
+            .map(|collider| -collider.compute_aabb().mins.y + body.translation().y)
+            .fold(0.0, f32::max);
+
+        self.physics_engine
+            .cast_ray(
+                &Ray::new(Point3::from(*body.translation()), vector![0.0, -1.0, 0.0]),
+                depth + GROUND_TOLERANCE,
+                true,
+                QueryFilter::default().exclude_rigid_body(h),
+            )
+            .is_some()
+    }
+
+    /// Let the body play the sound when it collides with others.
+    fn set_collision_sound(&mut self, h: RigidBodyHandle, src_op: Option<&str>) {
+        let active_events = match src_op {
+            Some(src) => {
+                self.collision_sound_mp.insert(h, src.to_string());
+
+                ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS
+            }
+            None => {
+                if self.collision_sound_mp.remove(&h).is_none() {
+                    return;
+                }
+
+                ActiveEvents::empty()
+            }
+        };
+
+        if let Some(body) = self.physics_engine.rigid_body_set.get(h) {
+            for collider in body.colliders() {
+                if let Some(collider) = self.physics_engine.collider_set.get_mut(*collider) {
+                    collider.set_active_events(active_events);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "physics")]
+impl AsElementProvider for PhysicsElementProvider {
+    type H = RigidBodyHandle;
+
+    fn create_element(
+        &mut self,
+        vnode_id: u64,
+        class: &str,
+        props: &json::JsonValue,
+    ) -> RigidBodyHandle {
+        match self.try_create_element(vnode_id, class, props) {
+            Ok(h) => h,
+            Err(e) => {
+                log::error!("create_element: {e:?}");
+
+                RigidBodyHandle::invalid()
+            }
+        }
+    }
+
+    fn update_element(&mut self, h: Self::H, class: &str, props: &json::JsonValue) {
+        let changed_key_set = props.entries().map(|(key, _)| key.to_string()).collect();
+
+        self.update_changed(h, class, props, &changed_key_set);
+    }
+
     /// Let element be updated.
     fn delete_element(&mut self, h: RigidBodyHandle) {
+        self.collision_sound_mp.remove(&h);
         self.physics_engine.remove_rigid_body(h);
     }
 }
 
 pub struct RenderPass<'a> {
     vm: &'a mut VisionElementProvider,
+    window: usize,
     output: SurfaceTexture,
     id_v: Vec<u64>,
 }
@@ -181,27 +768,101 @@ impl<'a> RenderPass<'a> {
         self.id_v.push(id);
     }
 
+    /// called => the result = how many elements are pushed to be drawn
+    pub fn element_count(&self) -> usize {
+        self.id_v.len()
+    }
+
     pub fn render(self) -> err::Result<()> {
         let view = self
             .output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        self.vm
-            .three_drawer
-            .render(
+        let three_drawer = match self.window {
+            0 => &mut self.vm.three_drawer,
+            window => &mut self.vm.window_view_v[window - 1].three_drawer,
+        };
+
+        // Let the labels face the camera of the window.
+        let orientation = *three_drawer.camera_state().orientation();
+        for (id, label) in &self.vm.label_mp {
+            if let Some(ThreeLook::Body(body)) = self.vm.body_mp.get_mut(id) {
+                body.model_m = model_m_of(
+                    label.anchor.coords + label.offset,
+                    orientation,
+                    Vector3::repeat(label.size),
+                );
+            }
+        }
+
+        // Let the directional lights fit their shadows to what the window sees.
+        for (id, directional_light) in &self.vm.directional_light_mp {
+            if let Some(ThreeLook::Light(light)) = self.vm.body_mp.get_mut(id) {
+                light.fit_directional(
+                    &directional_light.direction,
+                    &three_drawer.view_corner_v(directional_light.distance),
+                );
+            }
+        }
+
+        let look_v = self
+            .id_v
+            .iter()
+            .map(|id| self.vm.body_mp.get(id))
+            .filter(|op| op.is_some())
+            .map(|op| op.unwrap())
+            .collect::<Vec<&ThreeLook>>();
+
+        match (self.window, &mut self.vm.stereo_op) {
+            (0, Some(stereo)) => stereo.render(
                 &self.vm.device,
                 &self.vm.queue,
+                three_drawer,
                 &view,
-                self.id_v
-                    .iter()
-                    .map(|id| self.vm.body_mp.get(id))
-                    .filter(|op| op.is_some())
-                    .map(|op| op.unwrap())
-                    .collect(),
-                self.output.texture.width() as f32 / self.output.texture.height() as f32,
-            )
-            .change_context(err::Error::Other)?;
+                (self.output.texture.width(), self.output.texture.height()),
+                look_v.clone(),
+            )?,
+            _ => three_drawer
+                .render(
+                    &self.vm.device,
+                    &self.vm.queue,
+                    &view,
+                    look_v.clone(),
+                    (self.output.texture.width(), self.output.texture.height()),
+                )
+                .change_context(err::Error::Gpu)?,
+        }
+
+        if self.window == 0 {
+            if let Some(minimap) = &mut self.vm.minimap_op {
+                minimap.render(
+                    &self.vm.device,
+                    &self.vm.queue,
+                    &view,
+                    (self.output.texture.width(), self.output.texture.height()),
+                    look_v,
+                )?;
+            }
+
+            if let Some(quad_renderer) = &self.vm.overlay_renderer_op {
+                for overlay in self.vm.overlay_mp.values() {
+                    quad_renderer.quad_render(
+                        &self.vm.device,
+                        &self.vm.queue,
+                        &view,
+                        &overlay
+                            .texture
+                            .create_view(&wgpu::TextureViewDescriptor::default()),
+                        overlay_rect_of(
+                            overlay.corner,
+                            (overlay.texture.width(), overlay.texture.height()),
+                            (self.output.texture.width(), self.output.texture.height()),
+                        ),
+                    );
+                }
+            }
+        }
 
         self.output.present();
 
@@ -209,262 +870,2397 @@ impl<'a> RenderPass<'a> {
     }
 }
 
-pub struct VisionElementProvider {
-    config: wgpu::SurfaceConfiguration,
+/// The perspective projection of the camera.
+#[derive(Debug, Clone, Copy)]
+pub struct Perspective {
+    /// The vertical field of view in radians.
+    pub fovy: f32,
+    pub znear: f32,
+    pub zfar: f32,
+}
 
-    surface: wgpu::Surface<'static>,
-    pub device: wgpu::Device,
-    pub queue: wgpu::Queue,
+impl Default for Perspective {
+    fn default() -> Self {
+        Self {
+            fovy: PI * 0.6,
+            znear: 0.1,
+            zfar: 500.0,
+        }
+    }
+}
 
-    pub three_drawer: drawer::ThreeDrawer,
+impl Perspective {
+    /// called => whether the field of view, in degrees, opens to something to be seen
+    pub fn is_valid_fov(degrees: f32) -> bool {
+        degrees > 0.0 && degrees < 180.0
+    }
 
-    pub body_mp: HashMap<u64, ThreeLook>,
+    pub fn matrix(&self) -> Matrix4<f32> {
+        drawer::WGPU_OFFSET_M * Matrix4::new_perspective(1.0, self.fovy, self.znear, self.zfar)
+    }
 }
 
-impl VisionElementProvider {
-    pub fn new(
-        surface: wgpu::Surface<'static>,
-        device: wgpu::Device,
-        queue: wgpu::Queue,
-        config: wgpu::SurfaceConfiguration,
-    ) -> Self {
-        let three_drawer = drawer::ThreeDrawer::new(
-            &device,
-            config.format,
-            drawer::WGPU_OFFSET_M * Matrix4::new_perspective(1.0, PI * 0.6, 0.1, 500.0),
-        );
+/// A camera declared in the view tree.
+struct CameraElement {
+    position: Point3<f32>,
+    look_at_op: Option<Point3<f32>>,
+    /// The field of view overriding the one of the perspective.
+    fovy_op: Option<f32>,
+    roll: f32,
+    /// The window seen through, 0 for the main one.
+    window: usize,
+}
 
-        Self {
-            three_drawer,
-            device,
-            queue,
-            config,
-            surface,
-            body_mp: HashMap::new(),
+/// Let the camera be applied to the camera state and the projection of the drawer, or the
+/// perspective alone if there is no camera.
+fn apply_camera_to(
+    camera_op: Option<&CameraElement>,
+    three_drawer: &mut drawer::ThreeDrawer,
+    perspective: &Perspective,
+) {
+    let camera = match camera_op {
+        Some(camera) => camera,
+        None => {
+            three_drawer.set_proj_m(perspective.matrix());
+            return;
         }
-    }
+    };
 
-    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        if new_size.width > 0 && new_size.height > 0 {
-            self.config.width = new_size.width;
-            self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+    let camera_state = three_drawer.camera_state_mut();
+    *camera_state.position_mut() = camera.position;
+    *camera_state.roll_mut() = camera.roll;
+    if let Some(look_at) = camera.look_at_op {
+        camera_state.look_at(&look_at);
+    }
 
-            log::debug!("new_size = {new_size:?}");
+    three_drawer.set_proj_m(
+        Perspective {
+            fovy: camera.fovy_op.unwrap_or(perspective.fovy),
+            ..*perspective
         }
-    }
+        .matrix(),
+    );
+}
 
-    /// called => the result = a new render pass
-    pub fn render_pass(&mut self) -> err::Result<RenderPass> {
-        // Let the surface be drew.
-        let output = self
-            .surface
-            .get_current_texture()
-            .change_context(err::Error::Other)?;
+/// A light shining along a direction from afar, like the sun, its shadow fitting what is seen.
+struct DirectionalLight {
+    direction: Vector3<f32>,
+    /// How far from the camera the shadows are cast.
+    distance: f32,
+}
 
-        Ok(RenderPass {
-            vm: self,
-            output,
-            id_v: Vec::new(),
-        })
-    }
+/// A line of text facing the camera, above the element it follows or at its position.
+struct Label {
+    /// The vnode the label follows, or `None` for the label to stay at its anchor.
+    target_op: Option<u64>,
+    /// Where the target is, or the `$position`.
+    anchor: Point3<f32>,
+    offset: Vector3<f32>,
+    /// The height of a line of the text.
+    size: f32,
+    text: String,
+    color: Vector4<f32>,
+    /// The vertex buffer of the text, of the label alone as each text is drawn once, or `None`
+    /// for an empty text.
+    buf_op: Option<Arc<wgpu::Buffer>>,
+}
 
-    pub fn camera_state(&self) -> &CameraState {
-        self.three_drawer.camera_state()
-    }
+/// How far from its center the minimap sees at the zoom 1.
+const MINIMAP_EXTENT: f32 = 20.0;
+
+/// How high above its center the minimap looks down from, seeing as far below.
+const MINIMAP_HEIGHT: f32 = 100.0;
+
+/// The pixels between the minimap and the corner of the window.
+const MINIMAP_MARGIN: f32 = 16.0;
+
+/// A top-down map of the scene, at the top right corner of the main window.
+struct Minimap {
+    id: u64,
+    three_drawer: drawer::ThreeDrawer,
+    quad_renderer: drawer::quad_renderer::QuadRenderer,
+    /// The vnode the minimap follows, or `None` for the minimap to stay at its center.
+    target_op: Option<u64>,
+    /// Where the target is, or the `$position`.
+    center: Point3<f32>,
+    /// The width and the height in pixels.
+    size: u32,
+    zoom: f32,
+    /// The texture rendered to, kept across the frames and made again when `$size` changes.
+    texture_op: Option<wgpu::Texture>,
+}
 
-    pub fn camera_state_mut(&mut self) -> &mut CameraState {
-        self.three_drawer.camera_state_mut()
+impl Minimap {
+    /// called => the looks = rendered from above the center, then drawn onto the target of the
+    /// size
+    fn render(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        target: &wgpu::TextureView,
+        (width, height): (u32, u32),
+        look_v: Vec<&ThreeLook>,
+    ) -> err::Result<()> {
+        let extent = MINIMAP_EXTENT / self.zoom;
+
+        *self.three_drawer.camera_state_mut().position_mut() =
+            self.center + vector![0.0, MINIMAP_HEIGHT, 0.0];
+        self.three_drawer.set_proj_m(
+            drawer::WGPU_OFFSET_M
+                * Matrix4::new_orthographic(
+                    -extent,
+                    extent,
+                    -extent,
+                    extent,
+                    0.0,
+                    MINIMAP_HEIGHT * 2.0,
+                ),
+        );
+
+        if self
+            .texture_op
+            .as_ref()
+            .map_or(true, |texture| texture.width() != self.size)
+        {
+            self.texture_op = Some(
+                self.three_drawer
+                    .target_texture(device, self.size, self.size),
+            );
+        }
+        let texture_view = self
+            .texture_op
+            .as_ref()
+            .unwrap()
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        self.three_drawer
+            .render(device, queue, &texture_view, look_v, (self.size, self.size))
+            .change_context(err::Error::Gpu)?;
+        self.quad_renderer.quad_render(
+            device,
+            queue,
+            target,
+            &texture_view,
+            corner_rect_of(self.size as f32, width as f32, height as f32),
+        );
+
+        Ok(())
     }
 }
 
-impl AsElementProvider for VisionElementProvider {
-    type H = u64;
+/// A view of the main window seen by two eyes apart, rendered once for each eye a frame and
+/// drawn side by side, the left eye on the left.
+///
+/// The pair is what a headset would be given; the submission to an OpenXR swapchain and the poses
+/// of the controllers are left to a runtime hosting the device.
+struct Stereo {
+    /// The distance between the eyes in meters.
+    ipd: f32,
+    /// The left eye first. Each has a drawer of its own, the TAA history and the depth the
+    /// occlusion culling tests against being of one point of view.
+    eye_v: [Eye; 2],
+    quad_renderer: drawer::quad_renderer::QuadRenderer,
+}
 
-    fn create_element(&mut self, vnode_id: u64, class: &str, props: &json::JsonValue) -> u64 {
-        match class {
-            "light3" => {
-                log::debug!("create_element: create light3 {vnode_id}");
+/// An eye of the [Stereo] view.
+struct Eye {
+    three_drawer: drawer::ThreeDrawer,
+    /// The texture rendered to, kept across the frames and made again when the window is resized.
+    texture_op: Option<wgpu::Texture>,
+}
 
-                let pos = if props["$position"].is_array() {
-                    let pos = props["$position"]
-                        .members()
-                        .into_iter()
-                        .map(|n| n.as_str().unwrap().parse().unwrap())
-                        .collect::<Vec<f32>>();
+/// called => the result = the offsets of the left and the right eye from the camera, half the
+/// distance between them along its right
+fn eye_offset_v(orientation: &UnitQuaternion<f32>, ipd: f32) -> [Vector3<f32>; 2] {
+    let right = orientation * Vector3::x() * (ipd * 0.5);
 
-                    point![pos[0], pos[1], pos[2]]
-                } else {
-                    point![0.0, 0.0, 0.0]
-                };
-                let (yaw, pitch) = if props["$direction"].is_array() {
-                    let direction = props["$direction"]
-                        .members()
-                        .into_iter()
-                        .map(|n| n.as_str().unwrap().parse().unwrap())
-                        .collect::<Vec<f32>>();
-
-                    (direction[0], direction[1])
-                } else {
-                    (0.0, 0.0)
-                };
-                let view = Matrix4::look_at_rh(
-                    &pos,
-                    &point![pos.x - yaw.tan(), pos.y + pitch.tan(), pos.z - 1.0],
-                    &Vector3::new(0.0, 1.0, 0.0),
-                );
+    [-right, right]
+}
 
-                let color = if props["$color"].is_array() {
-                    let color = props["$color"]
-                        .members()
-                        .into_iter()
-                        .map(|n| n.as_str().unwrap().parse().unwrap())
-                        .collect::<Vec<f32>>();
+/// called => the result = the half of the target the eye is drawn onto, `[left, bottom, right,
+/// top]` in clip space
+fn eye_rect_of(eye: usize) -> [f32; 4] {
+    let left = eye as f32 - 1.0;
 
-                    vector![color[0], color[1], color[2], *color.get(3).unwrap_or(&1.0)]
-                } else {
-                    vector![1.0, 1.0, 1.0, 1.0]
-                };
+    [left, -1.0, left + 1.0, 1.0]
+}
+
+impl Stereo {
+    /// called => the looks = rendered for each eye from the camera of the drawer, then drawn side
+    /// by side onto the target of the size
+    fn render(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        camera_drawer: &drawer::ThreeDrawer,
+        target: &wgpu::TextureView,
+        (width, height): (u32, u32),
+        look_v: Vec<&ThreeLook>,
+    ) -> err::Result<()> {
+        let camera_state = camera_drawer.camera_state();
+        let eye_size = ((width / 2).max(1), height);
+        let offset_v = eye_offset_v(camera_state.orientation(), self.ipd);
+
+        for (i, (eye, offset)) in self.eye_v.iter_mut().zip(offset_v).enumerate() {
+            *eye.three_drawer.camera_state_mut() = camera_state.clone();
+            *eye.three_drawer.camera_state_mut().position_mut() = camera_state.position() + offset;
+            eye.three_drawer.set_proj_m(*camera_drawer.proj_m());
+
+            if eye.texture_op.as_ref().map_or(true, |texture| {
+                (texture.width(), texture.height()) != eye_size
+            }) {
+                eye.texture_op = Some(
+                    eye.three_drawer
+                        .target_texture(device, eye_size.0, eye_size.1),
+                );
+            }
+            let texture_view = eye
+                .texture_op
+                .as_ref()
+                .unwrap()
+                .create_view(&wgpu::TextureViewDescriptor::default());
+
+            eye.three_drawer
+                .render(device, queue, &texture_view, look_v.clone(), eye_size)
+                .change_context(err::Error::Gpu)?;
+            self.quad_renderer
+                .quad_render(device, queue, target, &texture_view, eye_rect_of(i));
+        }
+
+        Ok(())
+    }
+}
+
+/// The pixels between an overlay and the corner of the window.
+const OVERLAY_MARGIN: f32 = 16.0;
+
+/// The corner of the main window an overlay is drawn at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayCorner {
+    TopLeft,
+    BottomLeft,
+    BottomRight,
+}
+
+/// An image drawn over the main window, pixel for pixel, like the inspector or the HUD.
+struct Overlay {
+    texture: wgpu::Texture,
+    corner: OverlayCorner,
+}
+
+/// called => the result = the rect of an image of the size at the corner of a target of the
+/// width and the height, `[left, bottom, right, top]` in clip space
+fn overlay_rect_of(
+    corner: OverlayCorner,
+    (image_width, image_height): (u32, u32),
+    (width, height): (u32, u32),
+) -> [f32; 4] {
+    let (width, height) = (width as f32, height as f32);
+    let (left, right) = match corner {
+        OverlayCorner::TopLeft | OverlayCorner::BottomLeft => {
+            let left = -1.0 + 2.0 * OVERLAY_MARGIN / width;
+
+            (left, left + 2.0 * image_width as f32 / width)
+        }
+        OverlayCorner::BottomRight => {
+            let right = 1.0 - 2.0 * OVERLAY_MARGIN / width;
+
+            (right - 2.0 * image_width as f32 / width, right)
+        }
+    };
+
+    match corner {
+        OverlayCorner::TopLeft => {
+            let top = 1.0 - 2.0 * OVERLAY_MARGIN / height;
+
+            [left, top - 2.0 * image_height as f32 / height, right, top]
+        }
+        OverlayCorner::BottomLeft | OverlayCorner::BottomRight => {
+            let bottom = -1.0 + 2.0 * OVERLAY_MARGIN / height;
+
+            [
+                left,
+                bottom,
+                right,
+                bottom + 2.0 * image_height as f32 / height,
+            ]
+        }
+    }
+}
+
+/// called => the result = the rect of a square of the size at the top right corner of a target
+/// of the width and the height, `[left, bottom, right, top]` in clip space
+fn corner_rect_of(size: f32, width: f32, height: f32) -> [f32; 4] {
+    let (right, top) = (
+        1.0 - 2.0 * MINIMAP_MARGIN / width,
+        1.0 - 2.0 * MINIMAP_MARGIN / height,
+    );
+
+    [
+        right - 2.0 * size / width,
+        top - 2.0 * size / height,
+        right,
+        top,
+    ]
+}
+
+/// A window other than the main one, with a camera of its own.
+struct WindowView {
+    config: wgpu::SurfaceConfiguration,
+    surface: wgpu::Surface<'static>,
+    three_drawer: drawer::ThreeDrawer,
+}
+
+/// A flight of the camera through the keyframes, for intros and cutscenes.
+struct CameraPath {
+    position_v: Vec<Point3<f32>>,
+    /// The points looked at in the keyframes, or empty to look where the camera heads.
+    look_at_v: Vec<Point3<f32>>,
+    /// The rolls in radians in the keyframes, the last one kept by the keyframes after it.
+    roll_v: Vec<f32>,
+    duration: Duration,
+    start: Instant,
+}
+
+impl CameraPath {
+    /// called => the result = the camera when the progress is `t`, on the spline through the
+    /// keyframes and turning from the orientation of one keyframe to the next
+    fn sample(&self, t: f32) -> CameraState {
+        let (i, u) = segment_of(self.position_v.len(), t);
+        let next = (i + 1).min(self.position_v.len() - 1);
+
+        let mut camera_state = self.key_state(i).interpolate(&self.key_state(next), u);
+        *camera_state.position_mut() = catmull_rom(&self.position_v, t);
+
+        camera_state
+    }
+
+    /// called => the result = the camera at the keyframe of the index
+    fn key_state(&self, i: usize) -> CameraState {
+        let t = match self.position_v.len() {
+            0 | 1 => 0.0,
+            len => i as f32 / (len - 1) as f32,
+        };
+        let position = self.position_v[i];
+        let look_at = if self.look_at_v.is_empty() {
+            // Look a bit ahead along the path.
+            let ahead = catmull_rom(&self.position_v, (t + 0.01).min(1.0));
+            if (ahead - position).norm() > f32::EPSILON {
+                ahead
+            } else {
+                position + (position - catmull_rom(&self.position_v, (t - 0.01).max(0.0)))
+            }
+        } else {
+            catmull_rom(&self.look_at_v, t)
+        };
+
+        let mut camera_state = CameraState::new(position, 0.0, 0.0);
+        *camera_state.roll_mut() = self
+            .roll_v
+            .get(i)
+            .or(self.roll_v.last())
+            .copied()
+            .unwrap_or(0.0);
+        camera_state.look_at(&look_at);
+
+        camera_state
+    }
+}
+
+/// called => the result = the index of the keyframe the progress `t` in [0, 1] is after, of
+/// `count` keyframes, and how far it is to the next in [0, 1]
+fn segment_of(count: usize, t: f32) -> (usize, f32) {
+    if count < 2 {
+        return (0, 0.0);
+    }
+
+    let last = count - 1;
+    let s = t.clamp(0.0, 1.0) * last as f32;
+    let i = (s.floor() as usize).min(last - 1);
+
+    (i, s - i as f32)
+}
+
+/// called => the result = the point at the progress `t` in [0, 1] on the Catmull-Rom spline
+/// through the points
+fn catmull_rom(point_v: &[Point3<f32>], t: f32) -> Point3<f32> {
+    if point_v.len() < 2 {
+        return point_v.first().copied().unwrap_or(point![0.0, 0.0, 0.0]);
+    }
+
+    let last = point_v.len() - 1;
+    let (i, u) = segment_of(point_v.len(), t);
+
+    let p0 = point_v[i.saturating_sub(1)].coords;
+    let p1 = point_v[i].coords;
+    let p2 = point_v[i + 1].coords;
+    let p3 = point_v[(i + 2).min(last)].coords;
+
+    Point3::from(
+        (p1 * 2.0
+            + (p2 - p0) * u
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * u * u
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * u * u * u)
+            * 0.5,
+    )
+}
+
+/// called => the result = the points listed flat in the prop, like `[x0, y0, z0, x1, ...]`
+fn point_v_of(props: &json::JsonValue, key: &str) -> Vec<Point3<f32>> {
+    prop::number_v(props, key)
+        .unwrap_or_default()
+        .chunks_exact(3)
+        .map(|p| point![p[0], p[1], p[2]])
+        .collect()
+}
+
+pub struct VisionElementProvider {
+    config: wgpu::SurfaceConfiguration,
+
+    surface: wgpu::Surface<'static>,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+
+    pub three_drawer: drawer::ThreeDrawer,
+
+    pub body_mp: HashMap<u64, ThreeLook>,
+
+    /// Vertex buffers shared by the bodies of the same shape and color.
+    mesh_cache: AssetCache<wgpu::Buffer>,
+    /// Images shared by the bodies of the same `$texture`.
+    texture_cache: AssetCache<wgpu::Texture>,
+    perspective: Perspective,
+    camera_mp: HashMap<u64, CameraElement>,
+    /// The camera3 the scene is seen through, or `None` for the free camera.
+    active_camera_op: Option<u64>,
+    path_mp: HashMap<u64, CameraPath>,
+    /// The camera_path being played, which overrides any camera3.
+    active_path_op: Option<u64>,
+    /// The windows other than the main one, the window 1 first.
+    window_view_v: Vec<WindowView>,
+    directional_light_mp: HashMap<u64, DirectionalLight>,
+    label_mp: HashMap<u64, Label>,
+    minimap_op: Option<Minimap>,
+    /// The stereo view the main window is rendered as, or `None` for one eye.
+    stereo_op: Option<Stereo>,
+    overlay_mp: HashMap<String, Overlay>,
+    /// The renderer of the overlays, made with the first one.
+    overlay_renderer_op: Option<drawer::quad_renderer::QuadRenderer>,
+
+    loader: AssetLoader<drawer::structs::Point3InputArray>,
+    /// The keys of the meshes the bodies are waiting for, a placeholder drawn till then.
+    waiting_mp: HashMap<u64, String>,
+    /// Pairs of body id and whether its mesh is loaded, or why not.
+    load_result_v: Vec<(u64, Result<(), String>)>,
+}
+
+impl VisionElementProvider {
+    pub fn new(
+        surface: wgpu::Surface<'static>,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: wgpu::SurfaceConfiguration,
+        perspective: Perspective,
+    ) -> Self {
+        let three_drawer = drawer::ThreeDrawer::new(&device, config.format, perspective.matrix());
+
+        Self {
+            three_drawer,
+            device,
+            queue,
+            config,
+            surface,
+            body_mp: HashMap::new(),
+            mesh_cache: AssetCache::new(),
+            texture_cache: AssetCache::new(),
+            perspective,
+            camera_mp: HashMap::new(),
+            active_camera_op: None,
+            path_mp: HashMap::new(),
+            active_path_op: None,
+            window_view_v: Vec::new(),
+            directional_light_mp: HashMap::new(),
+            label_mp: HashMap::new(),
+            minimap_op: None,
+            stereo_op: None,
+            overlay_mp: HashMap::new(),
+            overlay_renderer_op: None,
+            loader: AssetLoader::new(),
+            waiting_mp: HashMap::new(),
+            load_result_v: Vec::new(),
+        }
+    }
+
+    /// Let the bodies whose meshes are built take them in place of their placeholders.
+    ///
+    /// called => the result = pairs of body id and whether its mesh is loaded, or why not, since
+    /// the last poll
+    pub fn poll_load_v(&mut self) -> Vec<(u64, Result<(), String>)> {
+        for (key, result) in self.loader.poll() {
+            let id_v = self
+                .waiting_mp
+                .iter()
+                .filter(|(_, waiting_key)| **waiting_key == key)
+                .map(|(id, _)| *id)
+                .collect::<Vec<u64>>();
+            for id in &id_v {
+                self.waiting_mp.remove(id);
+            }
+
+            match result {
+                Ok(array) => {
+                    let buf = self
+                        .mesh_cache
+                        .insert(&key, vertex_buf_of(&self.device, &array));
+
+                    for id in id_v {
+                        if let Some(body) = self
+                            .body_mp
+                            .get_mut(&id)
+                            .and_then(|look| look.as_body_mut())
+                        {
+                            body.buf = buf.clone();
+                        }
+                        self.load_result_v.push((id, Ok(())));
+                    }
+                }
+                Err(e) => {
+                    log::error!("poll_load_v: {e:?}");
+
+                    let message = err::describe(&e);
+                    for id in id_v {
+                        self.load_result_v.push((id, Err(message.clone())));
+                    }
+                }
+            }
+        }
+
+        std::mem::take(&mut self.load_result_v)
+    }
+
+    /// called => the result = the id of the element created, or an error if the props are
+    /// malformed
+    pub fn try_create_element(
+        &mut self,
+        vnode_id: u64,
+        class: &str,
+        props: &json::JsonValue,
+    ) -> err::Result<u64> {
+        match class {
+            "light3" => {
+                log::debug!("create_element: create light3 {vnode_id}");
+
+                let pos = number3_of(props, "$position")?
+                    .map(Point3::from)
+                    .unwrap_or(point![0.0, 0.0, 0.0]);
+                let (yaw, pitch) = match prop::number_v(props, "$direction").as_deref() {
+                    Some([yaw, pitch, ..]) => (*yaw, *pitch),
+                    None if props["$direction"].is_null() => (0.0, 0.0),
+                    _ => {
+                        return Err(err::Error::InvalidProp).attach_printable_lazy(|| {
+                            format!("$direction needs two numbers, got {}", props["$direction"])
+                        })
+                    }
+                };
+                let view = Matrix4::look_at_rh(
+                    &pos,
+                    &point![pos.x - yaw.tan(), pos.y + pitch.tan(), pos.z - 1.0],
+                    &Vector3::new(0.0, 1.0, 0.0),
+                );
+
+                let color = color_of(props)?.unwrap_or(vector![1.0, 1.0, 1.0, 1.0]);
+                let intensity = prop::number(props, "$intensity").unwrap_or(1.0).max(0.0);
 
                 self.body_mp.insert(
                     vnode_id,
                     ThreeLook::Light(Light {
                         color,
+                        intensity,
                         view,
                         proj: drawer::WGPU_OFFSET_M
                             * Matrix4::new_orthographic(-10.0, 10.0, -10.0, 10.0, 0.0, 20.0),
                     }),
                 );
             }
+            "directional_light3" => {
+                log::debug!("create_element: create directional_light3 {vnode_id}");
+
+                let direction = number3_of(props, "$direction")?
+                    .map(Vector3::from)
+                    .unwrap_or(vector![0.0, -1.0, 0.0]);
+                let color = color_of(props)?.unwrap_or(vector![1.0, 1.0, 1.0, 1.0]);
+                let intensity = prop::number(props, "$intensity").unwrap_or(1.0).max(0.0);
+                let distance = prop::number(props, "$distance").unwrap_or(50.0).max(0.0);
+
+                // The view and the projection are fitted before each frame.
+                self.body_mp.insert(
+                    vnode_id,
+                    ThreeLook::Light(Light {
+                        color,
+                        intensity,
+                        view: Matrix4::identity(),
+                        proj: Matrix4::identity(),
+                    }),
+                );
+                self.directional_light_mp.insert(
+                    vnode_id,
+                    DirectionalLight {
+                        direction,
+                        distance,
+                    },
+                );
+            }
+            "label3" => {
+                log::debug!("create_element: create label3 {vnode_id}");
+
+                let text = prop::string(props, "$text").unwrap_or_default();
+                let color = color_of(props)?.unwrap_or(vector![1.0, 1.0, 1.0, 1.0]);
+                let anchor = number3_of(props, "$position")?
+                    .map(Point3::from)
+                    .unwrap_or(point![0.0, 0.0, 0.0]);
+                let offset = number3_of(props, "$offset")?
+                    .map(Vector3::from)
+                    .unwrap_or(vector![0.0, 1.0, 0.0]);
+                let size = prop::number(props, "$size").unwrap_or(0.25).max(0.0);
+
+                // The buffer of the label before is kept unless the text or the color changed,
+                // and dropped with the label otherwise.
+                let buf_op = match self.label_mp.remove(&vnode_id) {
+                    Some(label) if label.text == text && label.color == color => label.buf_op,
+                    // An empty text has nothing to draw.
+                    _ if text.is_empty() => None,
+                    _ => Some(Arc::new(vertex_buf_of(
+                        &self.device,
+                        &drawer::structs::Point3InputArray::text(color, &text),
+                    ))),
+                };
+
+                self.body_mp.remove(&vnode_id);
+                if let Some(buf) = &buf_op {
+                    // The model matrix is made to face the camera before each frame.
+                    self.body_mp.insert(
+                        vnode_id,
+                        ThreeLook::Body(Body {
+                            model_m: Matrix4::identity(),
+                            prev_model_m: Matrix4::identity(),
+                            buf: buf.clone(),
+                            // Turned to the camera each frame, a label is never culled.
+                            bound_op: None,
+                            texture_op: None,
+                        }),
+                    );
+                }
+                self.label_mp.insert(
+                    vnode_id,
+                    Label {
+                        target_op: prop::string(props, "$target")
+                            .and_then(|target| target.parse::<u64>().ok()),
+                        anchor,
+                        offset,
+                        size,
+                        text,
+                        color,
+                        buf_op,
+                    },
+                );
+            }
+            "minimap" => {
+                log::debug!("create_element: create minimap {vnode_id}");
+
+                let center = number3_of(props, "$position")?
+                    .map(Point3::from)
+                    .unwrap_or(point![0.0, 0.0, 0.0]);
+                let target_op =
+                    prop::string(props, "$target").and_then(|target| target.parse::<u64>().ok());
+                let size = prop::number(props, "$size").unwrap_or(200.0).max(1.0) as u32;
+                let zoom = prop::number(props, "$zoom").unwrap_or(1.0).max(0.01);
+
+                // A reloaded minimap keeps its drawers, and its texture unless resized.
+                match &mut self.minimap_op {
+                    Some(minimap) if minimap.id == vnode_id => {
+                        minimap.target_op = target_op;
+                        minimap.center = center;
+                        minimap.size = size;
+                        minimap.zoom = zoom;
+                    }
+                    // The corner has room for one.
+                    Some(minimap) => {
+                        return Err(err::Error::UnsupportedElement).attach_printable_lazy(|| {
+                            format!("minimap {} is shown already", minimap.id)
+                        })
+                    }
+                    None => {
+                        let mut three_drawer = drawer::ThreeDrawer::new(
+                            &self.device,
+                            self.config.format,
+                            Matrix4::identity(),
+                        );
+                        three_drawer.set_ambient(*self.three_drawer.ambient());
+                        // Looking down, the top of the map is -Z.
+                        *three_drawer.camera_state_mut().orientation_mut() =
+                            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), -PI * 0.5);
+
+                        self.minimap_op = Some(Minimap {
+                            id: vnode_id,
+                            three_drawer,
+                            quad_renderer: drawer::quad_renderer::QuadRenderer::new(
+                                &self.device,
+                                self.config.format,
+                            ),
+                            target_op,
+                            center,
+                            size,
+                            zoom,
+                            texture_op: None,
+                        });
+                    }
+                }
+            }
+            "camera3" => {
+                log::debug!("create_element: create camera3 {vnode_id}");
+
+                self.load_camera(vnode_id, props)?;
+            }
+            "camera_path" => {
+                log::debug!("create_element: create camera_path {vnode_id}");
+
+                self.load_path(vnode_id, props);
+            }
             "cube3" => {
                 log::debug!("create_element: create cube3 {vnode_id}");
 
-                let pos = if props["$position"].is_array() {
-                    let pos = props["$position"]
-                        .members()
-                        .into_iter()
-                        .map(|n| n.as_str().unwrap().parse().unwrap())
-                        .collect::<Vec<f32>>();
+                let pos = number3_of(props, "$position")?
+                    .map(Vector3::from)
+                    .unwrap_or(vector![0.0, 0.0, 0.0]);
+                let rotation = rotation_of(props)?.unwrap_or(UnitQuaternion::identity());
+                let scale = scale_of(props)?.unwrap_or(vector![1.0, 1.0, 1.0]);
+                let face_color_v = face_colors_of(props)?;
+                let texture_op =
+                    texture_of(&mut self.texture_cache, &self.device, &self.queue, props)?;
+                let model_m = model_m_of(pos, rotation, scale);
 
-                    vector![pos[0], pos[1], pos[2]]
-                } else {
-                    vector![0.0, 0.0, 0.0]
-                };
-                let color = if props["$color"].is_array() {
-                    let color = props["$color"]
-                        .members()
-                        .into_iter()
-                        .map(|n| n.as_str().unwrap().parse().unwrap())
-                        .collect::<Vec<f32>>();
-
-                    vector![color[0], color[1], color[2], *color.get(3).unwrap_or(&1.0)]
-                } else {
-                    vector![1.0, 1.0, 1.0, 1.0]
+                self.body_mp.insert(
+                    vnode_id,
+                    ThreeLook::Body(Body {
+                        model_m,
+                        prev_model_m: model_m,
+                        buf: cube_buf(&mut self.mesh_cache, &self.device, face_color_v),
+                        bound_op: drawer::structs::Point3InputArray::cube_faces(face_color_v)
+                            .bound(),
+                        texture_op,
+                    }),
+                );
+            }
+            "terrain3" => {
+                log::debug!("create_element: create terrain3 {vnode_id}");
+
+                let pos = number3_of(props, "$position")?
+                    .map(Vector3::from)
+                    .unwrap_or(vector![0.0, 0.0, 0.0]);
+                let color = color_of(props)?.unwrap_or(vector![1.0, 1.0, 1.0, 1.0]);
+                let (width, depth, resolution, noise_params) = terrain_of(props);
+                let key =
+                    format!("terrain:{width},{depth},{resolution},{noise_params:?},{color:?}");
+
+                let buf = match self.mesh_cache.get(&key) {
+                    Some(buf) => {
+                        self.waiting_mp.remove(&vnode_id);
+                        self.load_result_v.push((vnode_id, Ok(())));
+
+                        buf
+                    }
+                    None => {
+                        // A fine terrain takes long to build, so it is built in the background
+                        // and flat till then, see [VisionElementProvider::poll_load_v].
+                        self.loader.load(&key, move || {
+                            Ok(drawer::structs::Point3InputArray::terrain(
+                                color,
+                                width,
+                                depth,
+                                resolution,
+                                &noise_params,
+                            ))
+                        });
+                        self.waiting_mp.insert(vnode_id, key);
+
+                        self.mesh_cache.get_or_make(
+                            &format!("terrain:{width},{depth},flat,{color:?}"),
+                            || {
+                                let flat = NoiseParams {
+                                    amplitude: 0.0,
+                                    ..noise_params
+                                };
+
+                                vertex_buf_of(
+                                    &self.device,
+                                    &drawer::structs::Point3InputArray::terrain(
+                                        color, width, depth, 1, &flat,
+                                    ),
+                                )
+                            },
+                        )
+                    }
                 };
 
                 self.body_mp.insert(
                     vnode_id,
                     ThreeLook::Body(Body {
                         model_m: Matrix4::new_translation(&pos),
-                        buf: Arc::new(self.device.create_buffer_init(&BufferInitDescriptor {
-                            label: None,
-                            contents: bytemuck::cast_slice(
-                                drawer::structs::Point3InputArray::cube(color).vertex_v(),
-                            ),
-                            usage: BufferUsages::VERTEX,
-                        })),
+                        prev_model_m: Matrix4::new_translation(&pos),
+                        buf,
+                        // As wide as the view, a terrain hides more than it is hidden.
+                        bound_op: None,
+                        texture_op: None,
                     }),
                 );
             }
-            _ => (),
+            _ => {
+                return Err(err::Error::UnsupportedElement)
+                    .attach_printable_lazy(|| format!("unsupported class '{class}' in vision"))
+            }
         }
 
-        vnode_id
+        Ok(vnode_id)
     }
 
-    fn delete_element(&mut self, id: u64) {
-        self.body_mp.remove(&id);
-    }
+    /// Let the element be updated by the props, only for the keys changed.
+    ///
+    /// A camera3, camera_path, directional_light3, label3 or minimap is reloaded as a whole.
+    pub fn update_changed(
+        &mut self,
+        id: u64,
+        class: &str,
+        props: &json::JsonValue,
+        changed_key_set: &HashSet<String>,
+    ) {
+        if class == "camera3" {
+            if let Err(e) = self.load_camera(id, props) {
+                log::error!("update_element: {e:?}");
+            }
+
+            return;
+        }
+        if class == "camera_path" {
+            self.load_path(id, props);
+
+            return;
+        }
+        if ["directional_light3", "label3", "minimap"].contains(&class) {
+            if let Err(e) = self.try_create_element(id, class, props) {
+                log::error!("update_element: {e:?}");
+            }
+
+            return;
+        }
 
-    fn update_element(&mut self, id: u64, class: &str, props: &json::JsonValue) {
         if let Some(body) = self.body_mp.get_mut(&id) {
             match class {
                 "cube3" => {
                     let body = body.as_body_mut().unwrap();
 
-                    if props["$position"].is_array() {
-                        let pos = props["$position"]
-                            .members()
-                            .into_iter()
-                            .map(|n| n.as_str().unwrap().parse().unwrap())
-                            .collect::<Vec<f32>>();
-
-                        let o_origin = body.model_m.transform_point(&point![0.0, 0.0, 0.0]);
+                    if ["$position", "$rotation", "$scale"]
+                        .iter()
+                        .any(|key| changed_key_set.contains(*key))
+                    {
+                        // Without $position the body stays where it is.
+                        let pos = prop::number3(props, "$position")
+                            .map(Vector3::from)
+                            .unwrap_or_else(|| {
+                                body.model_m.transform_point(&point![0.0, 0.0, 0.0]).coords
+                            });
+                        let rotation_scale = rotation_of(props).and_then(|rotation| {
+                            Ok((
+                                rotation.unwrap_or(UnitQuaternion::identity()),
+                                scale_of(props)?,
+                            ))
+                        });
+
+                        match rotation_scale {
+                            Ok((rotation, scale)) => {
+                                body.model_m = model_m_of(
+                                    pos,
+                                    rotation,
+                                    scale.unwrap_or(vector![1.0, 1.0, 1.0]),
+                                );
+                            }
+                            Err(e) => log::error!("update_element: {e:?}"),
+                        }
+                    }
 
-                        body.model_m = Matrix4::new_translation(&vector![
-                            pos[0] - o_origin.x,
-                            pos[1] - o_origin.y,
-                            pos[2] - o_origin.z
-                        ]) * body.model_m;
+                    if changed_key_set.contains("$color")
+                        || changed_key_set.contains("$face_colors")
+                    {
+                        match face_colors_of(props) {
+                            Ok(face_color_v) => {
+                                body.buf =
+                                    cube_buf(&mut self.mesh_cache, &self.device, face_color_v);
+                            }
+                            Err(e) => log::error!("update_element: {e:?}"),
+                        }
                     }
 
-                    if props["$color"].is_array() {
-                        let color = props["$color"]
-                            .members()
-                            .into_iter()
-                            .map(|n| n.as_str().unwrap().parse().unwrap())
-                            .collect::<Vec<f32>>();
-
-                        body.buf = Arc::new(
-                            self.device.create_buffer_init(&BufferInitDescriptor {
-                                label: None,
-                                contents: bytemuck::cast_slice(
-                                    drawer::structs::Point3InputArray::cube(vector![
-                                        color[0],
-                                        color[1],
-                                        color[2],
-                                        *color.get(3).unwrap_or(&1.0)
-                                    ])
-                                    .vertex_v(),
-                                ),
-                                usage: BufferUsages::VERTEX,
-                            }),
-                        );
+                    if changed_key_set.contains("$texture") {
+                        match texture_of(&mut self.texture_cache, &self.device, &self.queue, props)
+                        {
+                            Ok(texture_op) => body.texture_op = texture_op,
+                            Err(e) => log::error!("update_element: {e:?}"),
+                        }
                     }
                 }
                 _ => (),
             }
         }
     }
-}
 
-pub struct InputProvider {}
+    /// Let the GPU finish its work, then the buffers, the surfaces of the windows and the device
+    /// be dropped in that order.
+    pub fn shutdown(self) {
+        let _ = self.device.poll(wgpu::Maintain::Wait);
 
-impl InputProvider {
-    pub fn new() -> Self {
-        Self {}
+        let Self {
+            surface,
+            device,
+            queue,
+            three_drawer,
+            body_mp,
+            mesh_cache,
+            texture_cache,
+            window_view_v,
+            minimap_op,
+            stereo_op,
+            overlay_mp,
+            overlay_renderer_op,
+            ..
+        } = self;
+
+        drop(body_mp);
+        drop(mesh_cache);
+        drop(texture_cache);
+        drop(three_drawer);
+        drop(minimap_op);
+        drop(stereo_op);
+        drop(overlay_mp);
+        drop(overlay_renderer_op);
+        drop(window_view_v);
+        drop(surface);
+        drop(queue);
+        drop(device);
     }
-}
 
-impl AsElementProvider for InputProvider {
-    type H = u64;
+    /// called => the result = the translation, the rotation and the scale of the body, `None`
+    /// for a light or no element
+    pub fn transform(&self, id: u64) -> Option<(Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>)> {
+        self.body_mp
+            .get(&id)
+            .and_then(|look| look.as_body())
+            .map(|body| transform_of(&body.model_m))
+    }
 
-    fn update_element(&mut self, id: Self::H, _class: &str, _props: &json::JsonValue) {
-        log::debug!("update_element: {id}")
+    /// Let the vertex buffers and the textures no body uses be dropped.
+    ///
+    /// called => the result = how many buffers and textures are dropped
+    pub fn evict_unused(&mut self) -> usize {
+        self.mesh_cache.evict_unused() + self.texture_cache.evict_unused()
     }
 
-    fn delete_element(&mut self, id: Self::H) {
-        log::debug!("delete_element: {id}")
+    /// called => the result = pairs of label id and the vnode id it follows
+    pub fn label_target_v(&self) -> Vec<(u64, u64)> {
+        self.label_mp
+            .iter()
+            .filter_map(|(id, label)| label.target_op.map(|target| (*id, target)))
+            .collect()
     }
 
-    fn create_element(&mut self, vnode_id: u64, class: &str, _props: &json::JsonValue) -> Self::H {
-        log::debug!("create_element: vnode_id = {vnode_id}, class = {class}");
+    /// Let the label be anchored at the position, its offset above it.
+    pub fn set_label_anchor(&mut self, id: u64, anchor: Point3<f32>) {
+        if let Some(label) = self.label_mp.get_mut(&id) {
+            label.anchor = anchor;
+        }
+    }
 
-        vnode_id
+    /// called => the result = the vnode id the minimap follows
+    pub fn minimap_target_op(&self) -> Option<u64> {
+        self.minimap_op
+            .as_ref()
+            .and_then(|minimap| minimap.target_op)
     }
-}
 
-#[cfg(test)]
+    /// Let the minimap be centered at the position.
+    pub fn set_minimap_center(&mut self, center: Point3<f32>) {
+        if let Some(minimap) = &mut self.minimap_op {
+            minimap.center = center;
+        }
+    }
+
+    /// Let the image be drawn over the main window at the corner, in place of the overlay of the
+    /// name, or that overlay be removed if `None`.
+    pub fn set_overlay(
+        &mut self,
+        name: &str,
+        image_op: Option<(&drawer::overlay::OverlayImage, OverlayCorner)>,
+    ) {
+        let (image, corner) = match image_op {
+            Some(image) => image,
+            None => {
+                self.overlay_mp.remove(name);
+                return;
+            }
+        };
+
+        if self.overlay_renderer_op.is_none() {
+            self.overlay_renderer_op = Some(drawer::quad_renderer::QuadRenderer::new(
+                &self.device,
+                self.config.format,
+            ));
+        }
+        self.overlay_mp.insert(
+            name.to_string(),
+            Overlay {
+                texture: image.texture(&self.device, &self.queue),
+                corner,
+            },
+        );
+    }
+
+    /// Let the camera see through the perspective, unless a camera3 overrides its field of view.
+    pub fn set_perspective(&mut self, perspective: Perspective) {
+        self.perspective = perspective;
+
+        self.apply_camera();
+    }
+
+    pub fn ambient(&self) -> &Ambient {
+        self.three_drawer.ambient()
+    }
+
+    /// Let the scene be lit by the ambient, in every window.
+    pub fn set_ambient(&mut self, ambient: Ambient) {
+        self.three_drawer.set_ambient(ambient);
+        for window_view in &mut self.window_view_v {
+            window_view.three_drawer.set_ambient(ambient);
+        }
+        if let Some(minimap) = &mut self.minimap_op {
+            minimap.three_drawer.set_ambient(ambient);
+        }
+        for eye in self
+            .stereo_op
+            .iter_mut()
+            .flat_map(|stereo| &mut stereo.eye_v)
+        {
+            eye.three_drawer.set_ambient(ambient);
+        }
+    }
+
+    pub fn is_taa_enabled(&self) -> bool {
+        self.three_drawer.is_taa_enabled()
+    }
+
+    /// Let the edges be smoothed by the temporal anti-aliasing in every window, or not.
+    pub fn set_taa(&mut self, is_enabled: bool) {
+        self.three_drawer.set_taa(&self.device, is_enabled);
+        for window_view in &mut self.window_view_v {
+            window_view.three_drawer.set_taa(&self.device, is_enabled);
+        }
+        for eye in self
+            .stereo_op
+            .iter_mut()
+            .flat_map(|stereo| &mut stereo.eye_v)
+        {
+            eye.three_drawer.set_taa(&self.device, is_enabled);
+        }
+    }
+
+    pub fn is_occlusion_culling_enabled(&self) -> bool {
+        self.three_drawer.is_occlusion_culling_enabled()
+    }
+
+    /// Let the bodies hidden behind others be culled in every window, or not.
+    pub fn set_occlusion_culling(&mut self, is_enabled: bool) {
+        self.three_drawer
+            .set_occlusion_culling(&self.device, is_enabled);
+        for window_view in &mut self.window_view_v {
+            window_view
+                .three_drawer
+                .set_occlusion_culling(&self.device, is_enabled);
+        }
+        for eye in self
+            .stereo_op
+            .iter_mut()
+            .flat_map(|stereo| &mut stereo.eye_v)
+        {
+            eye.three_drawer
+                .set_occlusion_culling(&self.device, is_enabled);
+        }
+    }
+
+    /// called => the result = the distance between the eyes of the stereo view, `None` for one eye
+    pub fn stereo_ipd_op(&self) -> Option<f32> {
+        self.stereo_op.as_ref().map(|stereo| stereo.ipd)
+    }
+
+    /// Let the main window be rendered for two eyes the distance apart, side by side, or for one
+    /// if `None`.
+    pub fn set_stereo(&mut self, ipd_op: Option<f32>) {
+        let ipd = match ipd_op {
+            Some(ipd) => ipd,
+            None => {
+                self.stereo_op = None;
+                return;
+            }
+        };
+
+        if let Some(stereo) = &mut self.stereo_op {
+            stereo.ipd = ipd;
+            return;
+        }
+
+        let eye_v = [(); 2].map(|_| {
+            let mut three_drawer =
+                drawer::ThreeDrawer::new(&self.device, self.config.format, Matrix4::identity());
+            three_drawer.set_ambient(*self.three_drawer.ambient());
+            three_drawer.set_taa(&self.device, self.three_drawer.is_taa_enabled());
+            three_drawer.set_occlusion_culling(
+                &self.device,
+                self.three_drawer.is_occlusion_culling_enabled(),
+            );
+
+            Eye {
+                three_drawer,
+                texture_op: None,
+            }
+        });
+        self.stereo_op = Some(Stereo {
+            ipd,
+            eye_v,
+            quad_renderer: drawer::quad_renderer::QuadRenderer::new(
+                &self.device,
+                self.config.format,
+            ),
+        });
+    }
+
+    /// Let the bodies remember where they are, for their motion in the next frame.
+    pub fn end_frame(&mut self) {
+        for look in self.body_mp.values_mut() {
+            if let ThreeLook::Body(body) = look {
+                body.prev_model_m = body.model_m;
+            }
+        }
+    }
+
+    /// called => the result = whether the scene is seen through a camera3 rather than the free
+    /// camera
+    pub fn has_active_camera(&self) -> bool {
+        self.active_camera_op.is_some() || self.active_path_op.is_some()
+    }
+
+    /// Let the camera_path of the element be (re)loaded from the props and played from the start,
+    /// unless its keyframes and duration are unchanged.
+    fn load_path(&mut self, id: u64, props: &json::JsonValue) {
+        let position_v = point_v_of(props, "$position");
+        if position_v.is_empty() {
+            log::warn!("load_path: camera_path {id} has no keyframe");
+            return;
+        }
+        let look_at_v = point_v_of(props, "$look_at");
+        let roll_v = prop::number_v(props, "$roll")
+            .unwrap_or_default()
+            .into_iter()
+            .map(f32::to_radians)
+            .collect::<Vec<f32>>();
+        let duration = millis_of(props, "$duration").unwrap_or(Duration::from_secs(1));
+
+        if let Some(path) = self.path_mp.get(&id) {
+            if path.position_v == position_v
+                && path.look_at_v == look_at_v
+                && path.roll_v == roll_v
+                && path.duration == duration
+            {
+                return;
+            }
+        }
+
+        self.path_mp.insert(
+            id,
+            CameraPath {
+                position_v,
+                look_at_v,
+                roll_v,
+                duration,
+                start: Instant::now(),
+            },
+        );
+        self.active_path_op = Some(id);
+    }
+
+    /// Let the camera fly along the camera_path being played.
+    ///
+    /// called => the result = the camera_path just ended, if any
+    pub fn advance_path(&mut self, now: Instant) -> Option<u64> {
+        let id = self.active_path_op?;
+        let path = match self.path_mp.get(&id) {
+            Some(path) => path,
+            None => {
+                self.active_path_op = None;
+                return None;
+            }
+        };
+
+        let t = if path.duration.is_zero() {
+            1.0
+        } else {
+            now.saturating_duration_since(path.start).as_secs_f32() / path.duration.as_secs_f32()
+        };
+        *self.three_drawer.camera_state_mut() = path.sample(t.min(1.0));
+
+        if t < 1.0 {
+            return None;
+        }
+
+        // Let the camera3, if any, take the camera back.
+        self.active_path_op = None;
+        if self.active_camera_op.is_some() {
+            self.apply_camera();
+        }
+
+        Some(id)
+    }
+
+    /// Let the camera3 of the element be (re)loaded from the props.
+    ///
+    /// The newest camera3, or the one with `$active` = true, is the one the scene is seen
+    /// through. A camera3 with `$window` > 0 is the camera of that window instead.
+    fn load_camera(&mut self, id: u64, props: &json::JsonValue) -> err::Result<()> {
+        let point_of = |key: &str| prop::number3(props, key).map(Point3::from);
+        let fov_op = prop::number(props, "$fov");
+        if let Some(fov) = fov_op.filter(|fov| !Perspective::is_valid_fov(*fov)) {
+            return Err(err::Error::InvalidProp)
+                .attach_printable_lazy(|| format!("$fov needs to be in (0, 180), got {fov}"));
+        }
+        let fovy_op = fov_op.map(|fov| fov.to_radians());
+        let window = prop::number(props, "$window").unwrap_or(0.0).max(0.0) as usize;
+
+        let is_new = !self.camera_mp.contains_key(&id);
+        self.camera_mp.insert(
+            id,
+            CameraElement {
+                position: point_of("$position").unwrap_or(point![0.0, 0.0, 0.0]),
+                look_at_op: point_of("$look_at"),
+                fovy_op,
+                roll: prop::number(props, "$roll").unwrap_or(0.0).to_radians(),
+                window,
+            },
+        );
+
+        if window > 0 {
+            match self.window_view_v.get_mut(window - 1) {
+                Some(window_view) => apply_camera_to(
+                    self.camera_mp.get(&id),
+                    &mut window_view.three_drawer,
+                    &self.perspective,
+                ),
+                None => log::warn!("load_camera: no window {window} for camera3 {id}"),
+            }
+
+            return Ok(());
+        }
+
+        if is_new || prop::boolean(props, "$active") == Some(true) {
+            self.active_camera_op = Some(id);
+        }
+        self.apply_camera();
+
+        Ok(())
+    }
+
+    /// Let the active camera3 be applied to the camera state and the projection.
+    fn apply_camera(&mut self) {
+        apply_camera_to(
+            self.active_camera_op.and_then(|id| self.camera_mp.get(&id)),
+            &mut self.three_drawer,
+            &self.perspective,
+        );
+    }
+
+    /// Let another window be drawn on, with a camera of its own and the ambient, the TAA and the
+    /// occlusion culling of the main one.
+    ///
+    /// called => the result = the index of the window, the main one being 0
+    pub fn add_window(
+        &mut self,
+        surface: wgpu::Surface<'static>,
+        config: wgpu::SurfaceConfiguration,
+    ) -> usize {
+        let mut three_drawer =
+            drawer::ThreeDrawer::new(&self.device, config.format, self.perspective.matrix());
+        three_drawer.set_ambient(*self.three_drawer.ambient());
+        three_drawer.set_taa(&self.device, self.is_taa_enabled());
+        three_drawer.set_occlusion_culling(&self.device, self.is_occlusion_culling_enabled());
+
+        self.window_view_v.push(WindowView {
+            config,
+            surface,
+            three_drawer,
+        });
+
+        self.window_view_v.len()
+    }
+
+    /// called => the result = how many windows are drawn on, the main one included
+    pub fn window_count(&self) -> usize {
+        self.window_view_v.len() + 1
+    }
+
+    /// Let the window of the index be resized, the main one being 0.
+    pub fn resize_window(&mut self, window: usize, new_size: winit::dpi::PhysicalSize<u32>) {
+        if window == 0 {
+            return self.resize(new_size);
+        }
+
+        if let Some(window_view) = self.window_view_v.get_mut(window - 1) {
+            if new_size.width > 0 && new_size.height > 0 {
+                window_view.config.width = new_size.width;
+                window_view.config.height = new_size.height;
+                window_view
+                    .surface
+                    .configure(&self.device, &window_view.config);
+            }
+        }
+    }
+
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(&self.device, &self.config);
+
+            log::debug!("new_size = {new_size:?}");
+        }
+    }
+
+    /// called => the result = a new render pass
+    pub fn render_pass(&mut self) -> err::Result<RenderPass> {
+        self.render_pass_of(0)
+    }
+
+    /// called => the result = a new render pass of the window, the main one being 0
+    pub fn render_pass_of(&mut self, window: usize) -> err::Result<RenderPass> {
+        let surface = if window == 0 {
+            &self.surface
+        } else {
+            &self
+                .window_view_v
+                .get(window - 1)
+                .ok_or(err::Error::NotFound)
+                .attach_printable_lazy(|| format!("no window {window} to render"))?
+                .surface
+        };
+        // Let the surface be drew.
+        let output = surface
+            .get_current_texture()
+            .change_context(err::Error::Gpu)?;
+
+        Ok(RenderPass {
+            vm: self,
+            window,
+            output,
+            id_v: Vec::new(),
+        })
+    }
+
+    pub fn camera_state(&self) -> &CameraState {
+        self.three_drawer.camera_state()
+    }
+
+    /// called => the result = the origin and direction of the ray from the camera through the
+    /// cursor
+    ///
+    /// The cursor is normalized to [0, 1] from the top left of the window.
+    pub fn cursor_ray(&self, cursor: [f32; 2]) -> Option<(Point3<f32>, Vector3<f32>)> {
+        let ratio = self.config.width as f32 / self.config.height as f32;
+        let mut x = cursor[0] * 2.0 - 1.0;
+        let mut y = 1.0 - cursor[1] * 2.0;
+        // The square view is cropped to fill the window by the body shader.
+        if ratio > 1.0 {
+            y /= ratio;
+        } else {
+            x *= ratio;
+        }
+
+        let inv_m =
+            (self.three_drawer.proj_m() * self.camera_state().calc_matrix()).try_inverse()?;
+        let near = inv_m.transform_point(&point![x, y, 0.0]);
+        let far = inv_m.transform_point(&point![x, y, 1.0]);
+
+        Some((near, (far - near).normalize()))
+    }
+
+    pub fn camera_state_mut(&mut self) -> &mut CameraState {
+        self.three_drawer.camera_state_mut()
+    }
+}
+
+impl AsElementProvider for VisionElementProvider {
+    type H = u64;
+
+    fn create_element(&mut self, vnode_id: u64, class: &str, props: &json::JsonValue) -> u64 {
+        match self.try_create_element(vnode_id, class, props) {
+            Ok(id) => id,
+            Err(e) => {
+                log::error!("create_element: {e:?}");
+
+                vnode_id
+            }
+        }
+    }
+
+    fn delete_element(&mut self, id: u64) {
+        self.body_mp.remove(&id);
+        self.waiting_mp.remove(&id);
+        self.directional_light_mp.remove(&id);
+        self.label_mp.remove(&id);
+        if self
+            .minimap_op
+            .as_ref()
+            .is_some_and(|minimap| minimap.id == id)
+        {
+            self.minimap_op = None;
+        }
+
+        if self.camera_mp.remove(&id).is_some() && self.active_camera_op == Some(id) {
+            // Let the newest camera left take over, or the free camera if none.
+            self.active_camera_op = self
+                .camera_mp
+                .iter()
+                .filter(|(_, camera)| camera.window == 0)
+                .map(|(id, _)| *id)
+                .max();
+            self.apply_camera();
+        }
+
+        if self.path_mp.remove(&id).is_some() && self.active_path_op == Some(id) {
+            self.active_path_op = None;
+            self.apply_camera();
+        }
+    }
+
+    fn update_element(&mut self, id: u64, class: &str, props: &json::JsonValue) {
+        let changed_key_set = props.entries().map(|(key, _)| key.to_string()).collect();
+
+        self.update_changed(id, class, props, &changed_key_set);
+    }
+}
+
+pub struct InputProvider {
+    pub action_map: input::ActionMap,
+    pub gesture_detector: input::GestureDetector,
+    pub key_repeater: input::KeyRepeater,
+
+    is_cursor_grabbed: bool,
+
+    held_key_set: HashSet<String>,
+    /// Positions of the touches, normalized to [0, 1] from the top left of the window.
+    touch_mp: HashMap<u64, [f32; 2]>,
+    /// The entries each input element subscribes to, or `None` if it takes all.
+    subscription_mp: HashMap<u64, Option<HashSet<String>>>,
+}
+
+impl InputProvider {
+    pub fn new() -> Self {
+        Self {
+            action_map: input::ActionMap::new(),
+            gesture_detector: input::GestureDetector::new(),
+            key_repeater: input::KeyRepeater::new(),
+            is_cursor_grabbed: false,
+            held_key_set: HashSet::new(),
+            touch_mp: HashMap::new(),
+            subscription_mp: HashMap::new(),
+        }
+    }
+
+    /// called => the result = the input elements subscribing to the entry
+    pub fn subscriber_v(&self, entry_name: &str) -> Vec<u64> {
+        self.subscription_mp
+            .iter()
+            .filter(|(_, entry_set_op)| match entry_set_op {
+                Some(entry_set) => entry_set.contains(entry_name),
+                None => true,
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Let the element subscribe to the entries in `$events` of the props, or to all if there is
+    /// no `$events`.
+    fn subscribe(&mut self, id: u64, props: &json::JsonValue) {
+        let entry_set_op = if props["$events"].is_array() {
+            Some(
+                props["$events"]
+                    .members()
+                    .filter_map(|entry_name| entry_name.as_str())
+                    .map(|entry_name| entry_name.to_string())
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        self.subscription_mp.insert(id, entry_set_op);
+    }
+
+    /// Let the touch be at the position, or be lifted if `pos_op` is `None`.
+    pub fn set_touch(&mut self, touch_id: u64, pos_op: Option<[f32; 2]>) {
+        match pos_op {
+            Some(pos) => {
+                self.touch_mp.insert(touch_id, pos);
+            }
+            None => {
+                self.touch_mp.remove(&touch_id);
+            }
+        }
+    }
+
+    pub fn touch_op(&self, touch_id: u64) -> Option<[f32; 2]> {
+        self.touch_mp.get(&touch_id).copied()
+    }
+
+    /// called => the result = the ids of the touches on the window
+    pub fn touch_id_v(&self) -> Vec<u64> {
+        self.touch_mp.keys().copied().collect()
+    }
+
+    pub fn is_cursor_grabbed(&self) -> bool {
+        self.is_cursor_grabbed
+    }
+
+    /// called => the result = the commands for the runner to grab, release, hide, show, center
+    /// the cursor or toggle it between grabbed and released
+    ///
+    /// A grabbed cursor is hidden as well, for looking around. The cursor counts as grabbed once
+    /// asked, the runner only logging a window that can not grab it.
+    pub fn apply_cursor(&mut self, op: &str) -> err::Result<Vec<runner::WindowCommand>> {
+        let command_v = match op {
+            "grab" => {
+                self.is_cursor_grabbed = true;
+
+                vec![
+                    runner::WindowCommand::CursorGrab(true),
+                    runner::WindowCommand::CursorVisible(false),
+                ]
+            }
+            "release" => {
+                self.is_cursor_grabbed = false;
+
+                vec![
+                    runner::WindowCommand::CursorGrab(false),
+                    runner::WindowCommand::CursorVisible(true),
+                ]
+            }
+            "toggle" => {
+                return self.apply_cursor(if self.is_cursor_grabbed {
+                    "release"
+                } else {
+                    "grab"
+                })
+            }
+            "hide" => vec![runner::WindowCommand::CursorVisible(false)],
+            "show" => vec![runner::WindowCommand::CursorVisible(true)],
+            "center" => vec![runner::WindowCommand::CenterCursor],
+            _ => {
+                return Err(err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("unsupported cursor operation '{op}'"))
+            }
+        };
+
+        Ok(command_v)
+    }
+
+    /// Let the input be at the value.
+    pub fn set_input(&mut self, source: &input::InputSource, value: f32) {
+        if let input::InputSource::Key(key) = source {
+            if value == 0.0 {
+                self.held_key_set.remove(key);
+                self.key_repeater.release(key);
+            } else {
+                self.held_key_set.insert(key.clone());
+                self.key_repeater.press(key, Instant::now());
+            }
+        }
+    }
+
+    pub fn is_key_held(&self, key: &str) -> bool {
+        self.held_key_set.contains(key)
+    }
+
+    /// called => the result = the keys being held
+    pub fn held_key_v(&self) -> Vec<String> {
+        self.held_key_set.iter().cloned().collect()
+    }
+}
+
+impl AsElementProvider for InputProvider {
+    type H = u64;
+
+    fn update_element(&mut self, id: Self::H, _class: &str, props: &json::JsonValue) {
+        log::debug!("update_element: {id}");
+
+        self.subscribe(id, props);
+    }
+
+    fn delete_element(&mut self, id: Self::H) {
+        log::debug!("delete_element: {id}");
+
+        self.subscription_mp.remove(&id);
+    }
+
+    fn create_element(&mut self, vnode_id: u64, class: &str, props: &json::JsonValue) -> Self::H {
+        log::debug!("create_element: vnode_id = {vnode_id}, class = {class}");
+
+        self.subscribe(vnode_id, props);
+
+        vnode_id
+    }
+}
+
+#[cfg(feature = "audio")]
+pub struct AudioElementProvider {
+    pub audio_engine: audio::AudioEngine,
+
+    pub sound_mp: HashMap<u64, audio::Sound>,
+    /// Sounds not owned by any element, dropped once finished.
+    pub oneshot_v: Vec<audio::Sound>,
+    /// Sounds replaced by others, dropped once faded out.
+    pub fading_out_v: Vec<audio::Sound>,
+
+    is_paused: bool,
+    /// Sounds paused by the engine rather than by their elements.
+    resume_v: Vec<u64>,
+
+    loader: AssetLoader<audio::Decoded>,
+    /// The props of the sounds waiting for their files to be decoded.
+    waiting_mp: HashMap<u64, json::JsonValue>,
+    /// The one-shots waiting for their files to be decoded, as `(src, volume, position)`.
+    waiting_oneshot_v: Vec<(String, f32, [f32; 3])>,
+    /// Pairs of sound id and whether it is loaded, or why not.
+    load_result_v: Vec<(u64, Result<(), String>)>,
+}
+
+#[cfg(feature = "audio")]
+impl AudioElementProvider {
+    pub fn new(device_name_op: Option<&str>) -> Self {
+        Self {
+            audio_engine: audio::AudioEngine::new(device_name_op),
+            sound_mp: HashMap::new(),
+            oneshot_v: Vec::new(),
+            fading_out_v: Vec::new(),
+            is_paused: false,
+            resume_v: Vec::new(),
+            loader: AssetLoader::new(),
+            waiting_mp: HashMap::new(),
+            waiting_oneshot_v: Vec::new(),
+            load_result_v: Vec::new(),
+        }
+    }
+
+    /// Let the sounds whose files are decoded be loaded.
+    ///
+    /// called => the result = pairs of sound id and whether it is loaded, or why not, since the
+    /// last poll
+    pub fn poll_load_v(&mut self) -> Vec<(u64, Result<(), String>)> {
+        for (src, result) in self.loader.poll() {
+            let id_v = self
+                .waiting_mp
+                .iter()
+                .filter(|(_, props)| props["$src"][0].as_str() == Some(src.as_str()))
+                .map(|(id, _)| *id)
+                .collect::<Vec<u64>>();
+            let (oneshot_v, waiting_oneshot_v) = std::mem::take(&mut self.waiting_oneshot_v)
+                .into_iter()
+                .partition::<Vec<_>, _>(|(oneshot_src, _, _)| *oneshot_src == src);
+            self.waiting_oneshot_v = waiting_oneshot_v;
+
+            match result {
+                Ok(decoded) => {
+                    self.audio_engine.cache_decoded(&src, decoded);
+
+                    for id in id_v {
+                        if let Some(props) = self.waiting_mp.remove(&id) {
+                            self.load_sound(id, &props);
+                        }
+                    }
+                    for (src, volume, position) in oneshot_v {
+                        self.play_oneshot(&src, volume, position);
+                    }
+                }
+                Err(e) => {
+                    log::error!("poll_load_v: {e:?}");
+
+                    let message = err::describe(&e);
+                    for id in id_v {
+                        self.waiting_mp.remove(&id);
+                        self.load_result_v.push((id, Err(message.clone())));
+                    }
+                }
+            }
+        }
+
+        std::mem::take(&mut self.load_result_v)
+    }
+
+    /// Let all playing sounds be paused, or the sounds paused by this be resumed.
+    pub fn set_paused(&mut self, is_paused: bool) {
+        if self.is_paused == is_paused {
+            return;
+        }
+        self.is_paused = is_paused;
+
+        if is_paused {
+            for (id, sound) in &self.sound_mp {
+                if !sound.sink.is_paused() {
+                    sound.sink.pause();
+                    self.resume_v.push(*id);
+                }
+            }
+            for sound in self.oneshot_v.iter().chain(self.fading_out_v.iter()) {
+                sound.sink.pause();
+            }
+        } else {
+            for id in self.resume_v.drain(..) {
+                if let Some(sound) = self.sound_mp.get(&id) {
+                    sound.sink.play();
+                }
+            }
+            for sound in self.oneshot_v.iter().chain(self.fading_out_v.iter()) {
+                sound.sink.play();
+            }
+        }
+    }
+
+    /// Let every sound be stopped and dropped, and the files being decoded be forgotten.
+    pub fn stop_all(&mut self) {
+        for sound in self
+            .sound_mp
+            .values()
+            .chain(self.oneshot_v.iter())
+            .chain(self.fading_out_v.iter())
+        {
+            sound.sink.stop();
+        }
+
+        self.sound_mp.clear();
+        self.oneshot_v.clear();
+        self.fading_out_v.clear();
+        self.resume_v.clear();
+        self.waiting_mp.clear();
+        self.waiting_oneshot_v.clear();
+    }
+
+    /// called => fades = advanced, finished sounds = dropped
+    pub fn step(&mut self) {
+        let mixer = &self.audio_engine.mixer;
+
+        for sound in self.sound_mp.values_mut() {
+            sound.apply_fade(mixer.gain(&sound.bus));
+        }
+
+        self.fading_out_v.retain_mut(|sound| {
+            let is_done = sound.apply_fade(mixer.gain(&sound.bus));
+            if is_done {
+                sound.sink.stop();
+            }
+
+            !is_done
+        });
+        self.oneshot_v.retain(|sound| !sound.sink.empty());
+    }
+
+    /// called => the result = pairs of sound id and the event it emitted
+    pub fn poll_event_v(&mut self) -> Vec<(u64, &'static str)> {
+        self.sound_mp
+            .iter_mut()
+            .filter_map(|(id, sound)| sound.poll_event().map(|event| (*id, event)))
+            .collect()
+    }
+
+    /// Let the sound at `src` be played once at the position.
+    ///
+    /// A file not decoded yet is decoded in the background first, then played by
+    /// [AudioElementProvider::poll_load_v].
+    pub fn play_oneshot(&mut self, src: &str, volume: f32, position: [f32; 3]) {
+        if self.is_paused {
+            return;
+        }
+        if !self.audio_engine.is_cached(src) {
+            let path = src.to_string();
+            self.loader.load(src, move || audio::decode(&path));
+            self.waiting_oneshot_v
+                .push((src.to_string(), volume, position));
+
+            return;
+        }
+
+        match self.audio_engine.load_sound(
+            src,
+            audio::PlayMode {
+                is_loop: false,
+                is_autoplay: true,
+                is_cached: true,
+            },
+            Some(position),
+            volume,
+            "sfx",
+        ) {
+            Ok(sound) => self.oneshot_v.push(sound),
+            Err(e) => log::error!("play_oneshot: {e:?}"),
+        }
+    }
+
+    pub fn bus_volume(&self, bus: &str) -> f32 {
+        self.audio_engine.mixer.bus_volume(bus)
+    }
+
+    /// Let the bus be as loud as the volume, and the sounds through it follow.
+    pub fn set_bus_volume(&mut self, bus: &str, volume: f32) {
+        self.audio_engine.mixer.set_bus_volume(bus, volume);
+
+        let mixer = &self.audio_engine.mixer;
+        for sound in self
+            .sound_mp
+            .values()
+            .chain(self.oneshot_v.iter())
+            .chain(self.fading_out_v.iter())
+        {
+            sound.apply_volume(mixer.gain(&sound.bus));
+        }
+    }
+
+    pub fn device_name_op(&self) -> Option<&str> {
+        self.audio_engine.device_name_op()
+    }
+
+    /// Let the output be switched to the device named `name`, or to the default one if `name_op`
+    /// is `None`, and all sounds follow.
+    pub fn set_device(&mut self, name_op: Option<&str>) -> err::Result<()> {
+        if self.audio_engine.device_name_op() == name_op {
+            return Ok(());
+        }
+
+        // What is played to its end stays ended, instead of playing again on the new device.
+        self.oneshot_v.retain(|sound| !sound.is_finished());
+        self.fading_out_v.retain(|sound| !sound.is_finished());
+
+        self.audio_engine.set_device(name_op)?;
+
+        for sound in self
+            .sound_mp
+            .values_mut()
+            .chain(self.oneshot_v.iter_mut())
+            .chain(self.fading_out_v.iter_mut())
+            .filter(|sound| !sound.is_finished())
+        {
+            if let Err(e) = self.audio_engine.rebind(sound) {
+                log::error!("set_device: {e:?}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Let the sound specified by the id ramp to the volume over the duration.
+    pub fn fade(&mut self, id: u64, volume: f32, duration: Duration) -> err::Result<()> {
+        let sound = self
+            .sound_mp
+            .get_mut(&id)
+            .ok_or(err::Error::NotFound)
+            .attach_printable_lazy(|| format!("no sound {id}"))?;
+
+        sound.fade_op = Some(audio::Fade::new(sound.volume, volume, duration));
+
+        Ok(())
+    }
+
+    /// Let the sound be started with the fade in and end with the fade out specified by the
+    /// props, the fade in starting once the sound is first played.
+    fn set_fade_of(sound: &mut audio::Sound, props: &json::JsonValue) {
+        if let Some(fade_in) = millis_of(props, "$fade_in") {
+            sound.fade_op = Some(audio::Fade::on_play(0.0, sound.volume, fade_in));
+            sound.volume = 0.0;
+            sound.sink.set_volume(0.0);
+        }
+        sound.fade_out = millis_of(props, "$fade_out").unwrap_or_default();
+    }
+
+    /// Let the ears of the listener follow the camera.
+    pub fn update_listener(&mut self, camera_state: &CameraState) {
+        self.audio_engine.update_listener(
+            camera_state,
+            self.sound_mp
+                .values()
+                .chain(self.oneshot_v.iter())
+                .map(|sound| &sound.sink),
+        );
+    }
+
+    /// Let the sound specified by the id be moved to the position.
+    #[cfg(feature = "physics")]
+    pub fn set_emitter_position(&mut self, id: u64, position: [f32; 3]) {
+        if let Some(sound) = self.sound_mp.get_mut(&id) {
+            if let audio::AudioSink::Spatial(sink) = &sound.sink {
+                sink.set_emitter_position(position);
+                sound.position_op = Some(position);
+            }
+        }
+    }
+
+    /// called => the result = pairs of sound id and the vnode id its body is bound to
+    #[cfg(feature = "physics")]
+    pub fn target_v(&self) -> Vec<(u64, u64)> {
+        self.sound_mp
+            .iter()
+            .filter_map(|(id, sound)| sound.target_op.map(|target| (*id, target)))
+            .collect()
+    }
+
+    /// Let the sound of the element be (re)loaded from the props.
+    ///
+    /// A file not decoded yet is decoded in the background first, see
+    /// [AudioElementProvider::poll_load_v].
+    fn load_sound(&mut self, id: u64, props: &json::JsonValue) {
+        let src = match props["$src"][0].as_str() {
+            Some(src) => src,
+            None => {
+                log::warn!("load_sound: no $src in sound3 {id}");
+                return;
+            }
+        };
+        if !self.audio_engine.is_cached(src) {
+            let path = src.to_string();
+            self.loader.load(src, move || audio::decode(&path));
+            self.waiting_mp.insert(id, props.clone());
+
+            return;
+        }
+        self.waiting_mp.remove(&id);
+        let is_loop = prop::boolean(props, "$loop") == Some(true);
+        let is_autoplay = prop::boolean(props, "$autoplay") != Some(false);
+        let volume = prop::number(props, "$volume").unwrap_or(1.0);
+        let bus = props["$bus"][0].as_str().unwrap_or("sfx");
+        let target_op =
+            prop::string(props, "$target").and_then(|target| target.parse::<u64>().ok());
+        let position_op = if let Some(pos) = prop::number3(props, "$position") {
+            Some([pos[0], pos[1], pos[2]])
+        } else if target_op.is_some() {
+            Some([0.0, 0.0, 0.0])
+        } else {
+            None
+        };
+
+        match self.audio_engine.load_sound(
+            src,
+            audio::PlayMode {
+                is_loop,
+                is_autoplay: is_autoplay && !self.is_paused,
+                is_cached: true,
+            },
+            position_op,
+            volume,
+            bus,
+        ) {
+            Ok(mut sound) => {
+                sound.target_op = target_op;
+                Self::set_fade_of(&mut sound, props);
+                if is_autoplay && self.is_paused {
+                    self.resume_v.push(id);
+                }
+
+                if let Some(o_sound) = self.sound_mp.insert(id, sound) {
+                    o_sound.sink.stop();
+                }
+                self.load_result_v.push((id, Ok(())));
+            }
+            Err(e) => {
+                log::error!("load_sound: {e:?}");
+
+                self.load_result_v.push((id, Err(err::describe(&e))));
+            }
+        }
+    }
+
+    /// called => the result = the generator specified by the props of the tone or noise
+    fn synth_of(class: &str, props: &json::JsonValue) -> audio::Synth {
+        let waveform = if class == "noise" {
+            audio::Waveform::Noise
+        } else {
+            props["$waveform"][0]
+                .as_str()
+                .and_then(audio::Waveform::from_name)
+                .unwrap_or(audio::Waveform::Sine)
+        };
+        let frequency = prop::number(props, "$frequency").unwrap_or(440.0);
+        let duration_op = millis_of(props, "$duration");
+
+        audio::Synth::new(waveform, frequency, duration_op)
+    }
+
+    /// Let the generated sound of the tone or noise be (re)loaded from the props.
+    fn load_synth(&mut self, id: u64, class: &str, props: &json::JsonValue) {
+        let is_loop = prop::boolean(props, "$loop") == Some(true);
+        let is_autoplay = prop::boolean(props, "$autoplay") != Some(false);
+        let volume = prop::number(props, "$volume").unwrap_or(1.0);
+        let bus = props["$bus"][0].as_str().unwrap_or("sfx");
+
+        match self.audio_engine.load_synth(
+            Self::synth_of(class, props),
+            audio::PlayMode {
+                is_loop,
+                is_autoplay: is_autoplay && !self.is_paused,
+                is_cached: false,
+            },
+            None,
+            volume,
+            bus,
+        ) {
+            Ok(mut sound) => {
+                Self::set_fade_of(&mut sound, props);
+                if is_autoplay && self.is_paused {
+                    self.resume_v.push(id);
+                }
+
+                if let Some(o_sound) = self.sound_mp.insert(id, sound) {
+                    o_sound.sink.stop();
+                }
+            }
+            Err(e) => log::error!("load_synth: {e:?}"),
+        }
+    }
+
+    /// Let the music of the element cross fade into the one specified by the props.
+    fn load_music(&mut self, id: u64, props: &json::JsonValue) {
+        let src = match props["$src"][0].as_str() {
+            Some(src) => src,
+            None => {
+                log::warn!("load_music: no $src in music {id}");
+                return;
+            }
+        };
+        let is_loop = prop::boolean(props, "$loop") != Some(false);
+        let volume = prop::number(props, "$volume").unwrap_or(1.0);
+        let bus = props["$bus"][0].as_str().unwrap_or("music");
+        let crossfade = millis_of(props, "$crossfade").unwrap_or(Duration::from_millis(1000));
+
+        // The decoder reads the file on demand, so long tracks are streamed from disk.
+        match self.audio_engine.load_sound(
+            src,
+            audio::PlayMode {
+                is_loop,
+                is_autoplay: !self.is_paused,
+                is_cached: false,
+            },
+            None,
+            0.0,
+            bus,
+        ) {
+            Ok(mut sound) => {
+                sound.fade_op = Some(audio::Fade::new(0.0, volume, crossfade));
+                if self.is_paused {
+                    self.resume_v.push(id);
+                }
+
+                if let Some(mut o_sound) = self.sound_mp.insert(id, sound) {
+                    o_sound.fade_op = Some(audio::Fade::new(o_sound.volume, 0.0, crossfade));
+
+                    self.fading_out_v.push(o_sound);
+                }
+            }
+            Err(e) => log::error!("load_music: {e:?}"),
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+impl AsElementProvider for AudioElementProvider {
+    type H = u64;
+
+    fn create_element(&mut self, vnode_id: u64, class: &str, props: &json::JsonValue) -> u64 {
+        match class {
+            "sound3" => {
+                log::debug!("create_element: create sound3 {vnode_id}");
+
+                self.load_sound(vnode_id, props);
+            }
+            "music" => {
+                log::debug!("create_element: create music {vnode_id}");
+
+                self.load_music(vnode_id, props);
+            }
+            "tone" | "noise" => {
+                log::debug!("create_element: create {class} {vnode_id}");
+
+                self.load_synth(vnode_id, class, props);
+            }
+            _ => (),
+        }
+
+        vnode_id
+    }
+
+    fn update_element(&mut self, id: u64, class: &str, props: &json::JsonValue) {
+        match class {
+            "sound3" => {
+                let is_changed = match (self.sound_mp.get(&id), props["$src"][0].as_str()) {
+                    (Some(sound), Some(src)) => {
+                        sound.src != src
+                            || sound.is_loop != (prop::boolean(props, "$loop") == Some(true))
+                    }
+                    (None, Some(_)) => true,
+                    _ => false,
+                };
+
+                if is_changed {
+                    self.load_sound(id, props);
+                } else if let Some(sound) = self.sound_mp.get_mut(&id) {
+                    if let Some(bus) = props["$bus"][0].as_str() {
+                        sound.bus = bus.to_string();
+                    }
+                    sound.fade_out = millis_of(props, "$fade_out").unwrap_or_default();
+
+                    // Let the running fade reach its own volume first.
+                    if sound.fade_op.is_none() {
+                        if let Some(volume) = prop::number(props, "$volume") {
+                            sound.volume = volume;
+                        }
+                        sound.apply_volume(self.audio_engine.mixer.gain(&sound.bus));
+                    }
+
+                    sound.target_op = prop::string(props, "$target")
+                        .and_then(|target| target.parse::<u64>().ok());
+
+                    if let Some(pos) = prop::number3(props, "$position") {
+                        self.set_emitter_position(id, [pos[0], pos[1], pos[2]]);
+                    }
+                }
+            }
+            "music" => {
+                let is_changed = match (self.sound_mp.get(&id), props["$src"][0].as_str()) {
+                    (Some(sound), Some(src)) => sound.src != src,
+                    (None, Some(_)) => true,
+                    _ => false,
+                };
+
+                if is_changed {
+                    self.load_music(id, props);
+                } else if let Some(sound) = self.sound_mp.get_mut(&id) {
+                    if let Some(bus) = props["$bus"][0].as_str() {
+                        sound.bus = bus.to_string();
+                    }
+
+                    // Let the running cross fade reach its own volume first.
+                    if sound.fade_op.is_none() {
+                        if let Some(volume) = prop::number(props, "$volume") {
+                            sound.volume = volume;
+                        }
+                        sound.apply_volume(self.audio_engine.mixer.gain(&sound.bus));
+                    }
+                }
+            }
+            "tone" | "noise" => {
+                let is_changed = match self.sound_mp.get(&id) {
+                    Some(sound) => {
+                        sound.src != Self::synth_of(class, props).describe()
+                            || sound.is_loop != (prop::boolean(props, "$loop") == Some(true))
+                    }
+                    None => true,
+                };
+
+                if is_changed {
+                    self.load_synth(id, class, props);
+                } else if let Some(sound) = self.sound_mp.get_mut(&id) {
+                    if let Some(bus) = props["$bus"][0].as_str() {
+                        sound.bus = bus.to_string();
+                    }
+                    sound.fade_out = millis_of(props, "$fade_out").unwrap_or_default();
+
+                    // Let the running fade reach its own volume first.
+                    if sound.fade_op.is_none() {
+                        if let Some(volume) = prop::number(props, "$volume") {
+                            sound.volume = volume;
+                        }
+                        sound.apply_volume(self.audio_engine.mixer.gain(&sound.bus));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn delete_element(&mut self, id: u64) {
+        self.waiting_mp.remove(&id);
+        if let Some(mut sound) = self.sound_mp.remove(&id) {
+            if sound.fade_out.is_zero() || sound.sink.is_paused() {
+                sound.sink.stop();
+            } else {
+                sound.fade_op = Some(audio::Fade::new(sound.volume, 0.0, sound.fade_out));
+
+                self.fading_out_v.push(sound);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{vector, UnitQuaternion};
+
+    #[test]
+    fn test_transform_of() {
+        let rotation = UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3);
+        let model_m = super::model_m_of(vector![1.0, 2.0, 3.0], rotation, vector![2.0, 1.0, 0.5]);
+
+        let (pos, got_rotation, scale) = super::transform_of(&model_m);
+
+        assert!((pos - vector![1.0, 2.0, 3.0]).norm() < 1e-5);
+        assert!(got_rotation.angle_to(&rotation) < 1e-4);
+        assert!((scale - vector![2.0, 1.0, 0.5]).norm() < 1e-5);
+    }
+
+    #[cfg(feature = "physics")]
+    #[test]
+    fn test_unobstruct() {
+        use nalgebra::point;
+        use rapier3d::prelude::IntegrationParameters;
+
+        let mut physics = super::PhysicsElementProvider::new(IntegrationParameters::default());
+        // The body the pivot is inside, then a wall between it and the camera.
+        physics
+            .try_create_element(1, "cube3", &json::object! { "$position": [0, 0, 0] })
+            .unwrap();
+        physics
+            .try_create_element(2, "cube3", &json::object! { "$position": [3, 0, 0] })
+            .unwrap();
+        physics.physics_engine.step();
+
+        let pivot = point![0.5, 0.5, -0.5];
+        let camera = physics
+            .unobstruct(pivot, point![5.5, 0.5, -0.5], None)
+            .unwrap();
+        assert!((camera.x - (3.0 - super::CAMERA_RADIUS)).abs() < 1e-4);
+
+        // Nothing between the body and the camera.
+        assert_eq!(
+            physics.unobstruct(pivot, point![2.5, 0.5, -0.5], None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_camera_path_roll() {
+        use std::time::{Duration, Instant};
+
+        use nalgebra::point;
+
+        let path = super::CameraPath {
+            position_v: vec![point![0.0, 0.0, 0.0], point![0.0, 0.0, -10.0]],
+            look_at_v: Vec::new(),
+            roll_v: vec![0.0, 90f32.to_radians()],
+            duration: Duration::from_secs(1),
+            start: Instant::now(),
+        };
+
+        let camera_state = path.sample(0.5);
+        assert!((camera_state.roll() - 45f32.to_radians()).abs() < 1e-4);
+        assert!((camera_state.position().z + 5.0).abs() < 1e-4);
+        assert!((path.sample(1.0).roll() - 90f32.to_radians()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_overlay_rect_of() {
+        use super::OverlayCorner;
+
+        // 16 pixels from the left and the top, 200 pixels wide and 100 high.
+        let [left, bottom, right, top] =
+            super::overlay_rect_of(OverlayCorner::TopLeft, (200, 100), (800, 400));
+        assert!((left + 0.96).abs() < 1e-6 && (top - 0.92).abs() < 1e-6);
+        assert!((right - left - 0.5).abs() < 1e-6);
+        assert!((top - bottom - 0.5).abs() < 1e-6);
+
+        // 16 pixels from the bottom.
+        let [_, bottom, _, top] =
+            super::overlay_rect_of(OverlayCorner::BottomLeft, (200, 100), (800, 400));
+        assert!((bottom + 0.92).abs() < 1e-6);
+        assert!((top - bottom - 0.5).abs() < 1e-6);
+
+        // 16 pixels from the right and the bottom.
+        let [left, bottom, right, _] =
+            super::overlay_rect_of(OverlayCorner::BottomRight, (200, 100), (800, 400));
+        assert!((right - 0.96).abs() < 1e-6 && (bottom + 0.92).abs() < 1e-6);
+        assert!((right - left - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_corner_rect_of() {
+        let [left, bottom, right, top] = super::corner_rect_of(200.0, 800.0, 400.0);
+
+        // 16 pixels from the right and the top, 200 pixels wide and high.
+        assert!((right - 0.96).abs() < 1e-6 && (top - 0.92).abs() < 1e-6);
+        assert!((right - left - 0.5).abs() < 1e-6);
+        assert!((top - bottom - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_eye_offset_v() {
+        // Turned to look along -X, the right of the camera is -Z.
+        let orientation = UnitQuaternion::from_euler_angles(0.0, std::f32::consts::FRAC_PI_2, 0.0);
+        let [left, right] = super::eye_offset_v(&orientation, 0.064);
+
+        assert!((left - vector![0.0, 0.0, 0.032]).norm() < 1e-6);
+        assert!((right + left).norm() < 1e-6);
+
+        assert_eq!(super::eye_rect_of(0), [-1.0, -1.0, 0.0, 1.0]);
+        assert_eq!(super::eye_rect_of(1), [0.0, -1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_is_valid_fov() {
+        assert!(super::Perspective::is_valid_fov(60.0));
+        assert!(!super::Perspective::is_valid_fov(0.0));
+        assert!(!super::Perspective::is_valid_fov(180.0));
+        assert!(!super::Perspective::is_valid_fov(f32::NAN));
+    }
+
+    #[test]
+    fn test_invalid_prop() {
+        let props = json::object! { "$color": ["1", "0"], "$scale": ["2"] };
+
+        let e = super::color_of(&props).unwrap_err();
+        assert!(matches!(
+            e.current_context(),
+            crate::err::Error::InvalidProp
+        ));
+        assert_eq!(
+            super::scale_of(&props).unwrap(),
+            Some(vector![2.0, 2.0, 2.0])
+        );
+    }
+
+    #[test]
+    fn test_face_colors_of() {
+        let red = vector![1.0, 0.0, 0.0, 1.0];
+        let props = json::object! { "$color": [1, 0, 0] };
+        assert_eq!(super::face_colors_of(&props).unwrap(), [red; 6]);
+
+        let mut number_v = vec![1.0; 18];
+        number_v[3..6].copy_from_slice(&[1.0, 0.0, 0.0]);
+        let props = json::object! { "$face_colors": number_v, "$color": [0, 0, 1] };
+        let face_color_v = super::face_colors_of(&props).unwrap();
+        assert_eq!(face_color_v[1], red);
+        assert_eq!(face_color_v[5], vector![1.0, 1.0, 1.0, 1.0]);
+
+        let props = json::object! { "$face_colors": [1, 0, 0] };
+        assert!(super::face_colors_of(&props).is_err());
+    }
+}
+
+#[cfg(all(test, feature = "audio"))]
 mod test_rodio {
     #[test]
     fn test() {
@@ -0,0 +1,402 @@
+//! Help an application run the [Engine] in a window without writing the event loop itself.
+
+use std::{
+    path::Path,
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use error_stack::ResultExt;
+use moon_class::AsClassManager;
+use view_manager::ViewProps;
+use winit::{
+    application::ApplicationHandler,
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{
+        DeviceEvent, ElementState, MouseButton, MouseScrollDelta, Touch, TouchPhase, WindowEvent,
+    },
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    keyboard::Key,
+    window::{CursorGrabMode, CursorIcon, Fullscreen, Window, WindowAttributes, WindowId},
+};
+
+use crate::{err, views::ViewDir, Engine, EngineBuilder, EngineEvent};
+
+/// How long the engine thread rests between frames.
+const FRAME_INTERVAL: Duration = Duration::from_millis(10);
+
+/// called => the result = the text typed by the key, or the name of the key like `escape` or
+/// `control` if it types nothing
+fn key_name(key: &Key) -> String {
+    match key.to_text() {
+        Some(text) => text.to_string(),
+        None => match key {
+            Key::Named(named_key) => format!("{named_key:?}").to_lowercase(),
+            _ => String::new(),
+        },
+    }
+}
+
+/// A change of the window asked through the class API, like `@title(@window)`, applied by the
+/// [Runner] on the thread of the event loop.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WindowCommand {
+    Title(String),
+    /// Borderless fullscreen on the current monitor, or windowed.
+    Fullscreen(bool),
+    Decorations(bool),
+    InnerSize(PhysicalSize<u32>),
+    MinInnerSize(Option<PhysicalSize<u32>>),
+    MaxInnerSize(Option<PhysicalSize<u32>>),
+    CursorIcon(CursorIcon),
+    /// Locked, or confined where it can not be locked, or free.
+    CursorGrab(bool),
+    CursorVisible(bool),
+    /// To the center of the window.
+    CenterCursor,
+}
+
+impl WindowCommand {
+    /// Let the command be applied to the window.
+    pub fn apply(&self, window: &Window) {
+        match self {
+            WindowCommand::Title(title) => window.set_title(title),
+            WindowCommand::Fullscreen(is_fullscreen) => window.set_fullscreen(if *is_fullscreen {
+                Some(Fullscreen::Borderless(None))
+            } else {
+                None
+            }),
+            WindowCommand::Decorations(is_decorated) => window.set_decorations(*is_decorated),
+            WindowCommand::InnerSize(size) => {
+                let _ = window.request_inner_size(*size);
+            }
+            WindowCommand::MinInnerSize(size_op) => window.set_min_inner_size(*size_op),
+            WindowCommand::MaxInnerSize(size_op) => window.set_max_inner_size(*size_op),
+            WindowCommand::CursorIcon(icon) => window.set_cursor(*icon),
+            WindowCommand::CursorGrab(is_grabbed) => {
+                let result = if *is_grabbed {
+                    window
+                        .set_cursor_grab(CursorGrabMode::Locked)
+                        .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+                } else {
+                    window.set_cursor_grab(CursorGrabMode::None)
+                };
+
+                if let Err(e) = result {
+                    log::warn!("failed to grab or release the cursor: {e}");
+                }
+            }
+            WindowCommand::CursorVisible(is_visible) => window.set_cursor_visible(*is_visible),
+            WindowCommand::CenterCursor => {
+                let size = window.inner_size();
+
+                if let Err(e) = window
+                    .set_cursor_position(PhysicalPosition::new(size.width / 2, size.height / 2))
+                {
+                    log::warn!("failed to center the cursor: {e}");
+                }
+            }
+        }
+    }
+}
+
+/// Owns the event loop and the window, and drives an [Engine] on a thread of its own, which
+/// gets the window events as `$onkeydown`, `$onmousemove`, `$onclose` and so on.
+///
+/// ```no_run
+/// # fn run(dm: Box<dyn moon_class::AsClassManager + Send>) {
+/// use moon_world::runner::Runner;
+/// use view_manager::ViewProps;
+///
+/// Runner::new(
+///     ViewProps {
+///         class: "Main".to_string(),
+///         props: json::Null,
+///     },
+///     dm,
+/// )
+/// .run()
+/// .unwrap();
+/// # }
+/// ```
+pub struct Runner {
+    entry_op: Option<ViewProps>,
+    dm_op: Option<Box<dyn AsClassManager + Send>>,
+    window_attributes: WindowAttributes,
+    window_op: Option<&'static Window>,
+    tx_op: Option<Sender<EngineEvent>>,
+    /// Told when the engine is done with `$onclose` and shut down.
+    closed_rx_op: Option<Receiver<()>>,
+    command_rx_op: Option<Receiver<WindowCommand>>,
+    /// The views loaded before the entry, and whether they are hot reloaded.
+    view_dir_op: Option<(ViewDir, bool)>,
+}
+
+impl Runner {
+    /// called => the result = a new [Runner] of the entry view on the data manager
+    pub fn new(entry: ViewProps, dm: Box<dyn AsClassManager + Send>) -> Self {
+        Self {
+            entry_op: Some(entry),
+            dm_op: Some(dm),
+            window_attributes: Window::default_attributes(),
+            window_op: None,
+            tx_op: None,
+            closed_rx_op: None,
+            command_rx_op: None,
+            view_dir_op: None,
+        }
+    }
+
+    /// Let the window be created with the attributes, like its title or size.
+    pub fn with_window_attributes(mut self, window_attributes: WindowAttributes) -> Self {
+        self.window_attributes = window_attributes;
+        self
+    }
+
+    /// Let the views in the directory, like `views/`, be loaded before the entry, and, if hot,
+    /// be reloaded whenever their files change.
+    pub fn with_view_dir(mut self, path: impl AsRef<Path>, is_hot: bool) -> Self {
+        self.view_dir_op = Some((ViewDir::new(path), is_hot));
+        self
+    }
+
+    /// called => the application = run until its window is closed
+    pub fn run(mut self) -> err::Result<()> {
+        log::info!("run");
+        let event_loop = EventLoop::new().change_context(err::Error::Other)?;
+
+        event_loop.set_control_flow(ControlFlow::Poll);
+
+        event_loop
+            .run_app(&mut self)
+            .change_context(err::Error::Other)
+    }
+
+    /// Let the event be sent to the engine.
+    fn send(&self, event: EngineEvent) {
+        if let Some(tx) = &self.tx_op {
+            let _ = tx.send(event);
+        }
+    }
+
+    /// called => the result = the position normalized to [0, 1] by the size of the window
+    fn normalize(&self, x: f64, y: f64) -> (f64, f64) {
+        match self.window_op {
+            Some(window) => {
+                let size = window.inner_size();
+
+                (x / size.width as f64, y / size.height as f64)
+            }
+            None => (x, y),
+        }
+    }
+}
+
+/// Let the engine handle the events from the window and step and render, until `$onclose`,
+/// then shut it down.
+///
+/// The window commands of the engine are sent back by `command_tx`.
+async fn drive(
+    mut engine: Engine,
+    entry: ViewProps,
+    view_dir_op: Option<(ViewDir, bool)>,
+    rx: Receiver<EngineEvent>,
+    command_tx: Sender<WindowCommand>,
+) -> err::Result<()> {
+    if let Some((view_dir, is_hot)) = view_dir_op {
+        engine.load_views(view_dir, is_hot).await?;
+    }
+    engine.init(entry).await?;
+
+    loop {
+        let mut is_closed = false;
+        for event in rx.try_iter() {
+            is_closed |= event == EngineEvent::Close;
+
+            engine.push_event(event);
+        }
+
+        engine.step().await?;
+        if is_closed {
+            engine.shutdown().await;
+
+            return Ok(());
+        }
+
+        for command in engine.drain_window_command_v() {
+            let _ = command_tx.send(command);
+        }
+        engine.render()?;
+
+        tokio::time::sleep(FRAME_INTERVAL).await;
+    }
+}
+
+impl ApplicationHandler for Runner {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        // Resumed again after a suspension, the engine is still running.
+        let (entry, dm) = match (self.entry_op.take(), self.dm_op.take()) {
+            (Some(entry), Some(dm)) => (entry, dm),
+            _ => return,
+        };
+
+        let window: &'static Window = match event_loop.create_window(self.window_attributes.clone())
+        {
+            Ok(window) => Box::leak(Box::new(window)),
+            Err(e) => {
+                log::error!("failed to create the window: {e:?}");
+                event_loop.exit();
+
+                return;
+            }
+        };
+        let engine_builder = match EngineBuilder::from_window(window) {
+            Ok(engine_builder) => engine_builder,
+            Err(e) => {
+                log::error!("failed to prepare the engine: {e:?}");
+                event_loop.exit();
+
+                return;
+            }
+        };
+        self.window_op = Some(window);
+        let view_dir_op = self.view_dir_op.take();
+
+        let (tx, rx) = channel();
+        let (closed_tx, closed_rx) = channel();
+        let (command_tx, command_rx) = channel();
+        self.tx_op = Some(tx);
+        self.closed_rx_op = Some(closed_rx);
+        self.command_rx_op = Some(command_rx);
+
+        thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            rt.block_on(async move {
+                let result = match engine_builder.build(dm).await {
+                    Ok(engine) => drive(engine, entry, view_dir_op, rx, command_tx).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    log::error!("the engine stopped: {e:?}");
+                }
+
+                let _ = closed_tx.send(());
+            });
+        });
+    }
+
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if let (Some(window), Some(command_rx)) = (self.window_op, &self.command_rx_op) {
+            for command in command_rx.try_iter() {
+                command.apply(window);
+            }
+        }
+    }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: winit::event::DeviceId,
+        event: DeviceEvent,
+    ) {
+        match event {
+            DeviceEvent::MouseMotion { delta: (dx, dy) } => {
+                let unit = match self.window_op {
+                    Some(window) => window.inner_size().height as f64,
+                    None => return,
+                };
+
+                self.send(EngineEvent::MouseMotion {
+                    dx: dx / unit,
+                    dy: dy / unit,
+                });
+            }
+            _ => (),
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match &event {
+            WindowEvent::KeyboardInput { event, .. } => {
+                let key = key_name(&event.logical_key);
+
+                self.send(if event.state.is_pressed() {
+                    EngineEvent::KeyDown {
+                        key,
+                        repeat: event.repeat,
+                    }
+                } else {
+                    EngineEvent::KeyUp { key }
+                });
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let (x, y) = self.normalize(position.x, position.y);
+
+                self.send(EngineEvent::MouseMove { x, y });
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    MouseButton::Left => "left".to_string(),
+                    MouseButton::Right => "right".to_string(),
+                    MouseButton::Middle => "middle".to_string(),
+                    MouseButton::Back => "back".to_string(),
+                    MouseButton::Forward => "forward".to_string(),
+                    MouseButton::Other(n) => n.to_string(),
+                };
+
+                self.send(match state {
+                    ElementState::Pressed => EngineEvent::MouseDown { button },
+                    ElementState::Released => EngineEvent::MouseUp { button },
+                });
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let (dx, dy) = match delta {
+                    MouseScrollDelta::LineDelta(dx, dy) => (*dx as f64, *dy as f64),
+                    // Let a line be about 20 pixels, as on most desktops.
+                    MouseScrollDelta::PixelDelta(pos) => (pos.x / 20.0, pos.y / 20.0),
+                };
+
+                self.send(EngineEvent::Scroll { x: dx, y: dy });
+            }
+            WindowEvent::Touch(Touch {
+                phase,
+                location,
+                id,
+                ..
+            }) => {
+                let (x, y) = self.normalize(location.x, location.y);
+                let id = *id;
+
+                self.send(match phase {
+                    TouchPhase::Started => EngineEvent::TouchStart { id, x, y },
+                    TouchPhase::Moved => EngineEvent::TouchMove { id, x, y },
+                    TouchPhase::Ended => EngineEvent::TouchEnd { id, x, y },
+                    TouchPhase::Cancelled => EngineEvent::TouchCancel { id, x, y },
+                });
+            }
+            WindowEvent::CloseRequested => {
+                log::info!("The close button was pressed; stopping");
+                self.send(EngineEvent::Close);
+
+                // Let the engine finish with `$onclose`, like saving, before the window goes.
+                if let Some(closed_rx) = &self.closed_rx_op {
+                    let _ = closed_rx.recv();
+                }
+
+                event_loop.exit();
+            }
+            WindowEvent::Resized(n_sz) => {
+                self.send(EngineEvent::Resize {
+                    width: n_sz.width,
+                    height: n_sz.height,
+                });
+            }
+            _ => (),
+        }
+    }
+}
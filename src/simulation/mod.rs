@@ -0,0 +1,518 @@
+//! Help the game logic built on the view tree run without a window, like on a server or in a
+//! test.
+
+use std::{pin::Pin, time::Instant};
+
+#[cfg(feature = "physics")]
+use std::collections::HashMap;
+
+use error_stack::ResultExt;
+use moon_class::{AsClassManager, Fu};
+#[cfg(feature = "physics")]
+use rapier3d::prelude::{IntegrationParameters, RigidBodyHandle};
+use view_manager::{AsElementProvider, AsViewManager, VNode, ViewProps};
+
+#[cfg(feature = "physics")]
+use crate::res;
+use crate::{
+    err, net, snapshot, tree,
+    world::{self, AsWorld},
+    EngineSnapshot,
+};
+
+/// An engine of the physics and the data only, with no device, surface or audio output.
+///
+/// The `Physics:` elements are simulated, the `Vision:`, `Audio:` and `Input:` ones are left
+/// out. The world is stepped and the events are propagated as in [crate::Engine], see
+/// [world].
+///
+/// stepped => time = next time
+pub struct SimulationEngine {
+    world: world::World,
+    #[cfg(feature = "physics")]
+    element_mp: HashMap<u64, RigidBodyHandle>,
+    last_step_op: Option<Instant>,
+
+    data_manager: Box<dyn AsClassManager>,
+    #[cfg(feature = "physics")]
+    physics_manager: res::PhysicsElementProvider,
+}
+
+impl SimulationEngine {
+    /// called => the result = a new [SimulationEngine]
+    pub fn new(
+        dm: Box<dyn AsClassManager>,
+        #[cfg(feature = "physics")] integration_parameters: IntegrationParameters,
+    ) -> Self {
+        Self {
+            world: world::World::new(),
+            #[cfg(feature = "physics")]
+            element_mp: HashMap::new(),
+            last_step_op: None,
+            data_manager: dm,
+            #[cfg(feature = "physics")]
+            physics_manager: res::PhysicsElementProvider::new(integration_parameters),
+        }
+    }
+
+    pub async fn init(&mut self, entry: ViewProps) -> err::Result<()> {
+        let root_id = self.new_vnode(0);
+        self.apply_props(root_id, &entry, 0, true)
+            .await
+            .change_context(err::Error::Other)
+            .attach_printable_lazy(|| format!("failed to apply the entry {}", entry.class))
+    }
+
+    /// called => the result = the position of the body of the vnode
+    #[cfg(feature = "physics")]
+    pub fn position(&self, vnode_id: u64) -> Option<[f32; 3]> {
+        let h = self.element_mp.get(&vnode_id)?;
+        let pos = self
+            .physics_manager
+            .physics_engine
+            .rigid_body_set
+            .get(*h)?
+            .translation();
+
+        Some([pos.x, pos.y, pos.z])
+    }
+
+    /// called => the result = `None`, there being no bodies
+    #[cfg(not(feature = "physics"))]
+    pub fn position(&self, _vnode_id: u64) -> Option<[f32; 3]> {
+        None
+    }
+
+    /// called => the result = the state of the vnode tree, the props of the elements, the class
+    /// data changed, the physics and the generator of `@random`, for
+    /// [SimulationEngine::restore]
+    pub async fn snapshot(&self) -> err::Result<EngineSnapshot> {
+        Ok(EngineSnapshot {
+            vnode_id_set: self.world.tree.vnode_mp.keys().copied().collect(),
+            props_mp: self.world.props_mp.clone(),
+            spawn_mp: Default::default(),
+            data_mp: snapshot::data_of(&*self.data_manager, &self.world.origin_data_mp).await?,
+            #[cfg(feature = "physics")]
+            physics_state: self.physics_manager.physics_engine.snapshot(),
+            step_index: self.world.step_index,
+            random: *self.world.random.lock().unwrap(),
+            is_paused: false,
+        })
+    }
+
+    /// Let the engine be back to the state of the snapshot, taken of it or of another engine
+    /// with the same vnodes.
+    ///
+    /// The elements get their props back and the class data its items. With no spawns, the
+    /// vnodes need to be those of the snapshot, or it is refused.
+    pub async fn restore(&mut self, snapshot: &EngineSnapshot) -> err::Result<()> {
+        if let Some(id) = snapshot
+            .vnode_id_set
+            .symmetric_difference(&self.world.tree.vnode_mp.keys().copied().collect())
+            .next()
+        {
+            return Err(err::Error::NotFound).attach_printable_lazy(|| {
+                format!("vnode {id} is not in both the snapshot and the engine")
+            });
+        }
+
+        for (id, props) in &snapshot.props_mp {
+            if self.world.props_mp.get(id) == Some(props) {
+                continue;
+            }
+
+            if let Some(class) = self
+                .world
+                .tree
+                .vnode_mp
+                .get(id)
+                .map(|vnode| vnode.view_props.class.clone())
+            {
+                self.update_element(*id, &class, props);
+            }
+        }
+
+        // After the props, which would move the bodies where they were spawned.
+        #[cfg(feature = "physics")]
+        self.physics_manager.restore(
+            &snapshot.physics_state,
+            self.element_mp
+                .iter()
+                .filter_map(|(id, h)| Some((*h, self.world.props_mp.get(id)?))),
+        );
+
+        snapshot::restore_data(
+            &mut *self.data_manager,
+            &mut self.world.origin_data_mp,
+            &snapshot.data_mp,
+        )
+        .await?;
+
+        self.world.step_index = snapshot.step_index;
+        *self.world.random.get_mut().unwrap() = snapshot.random;
+
+        Ok(())
+    }
+
+    /// Let the input of a player, forwarded by a client [crate::Engine], be dispatched to the
+    /// vnodes whose `$owner` prop is the player, with `$player` added to the data.
+    pub async fn dispatch_player_input(&mut self, message: &net::InputMessage) {
+        let data = message.tagged_data();
+
+        for id in net::owned_vnode_v(&self.world.tree.vnode_mp, &message.player) {
+            let _ = self.event_entry(id, &message.entry_name, &data).await;
+        }
+    }
+
+    /// called => the engine = stepped
+    ///
+    /// The dynamic bodies get `$onstep` with `$dt` and `$step`, and an element skipped for
+    /// malformed props gets `$onerror` with `$message`, propagated from its vnode.
+    pub async fn step(&mut self) {
+        world::propagate_error_v(self).await;
+
+        let now = Instant::now();
+        let dt = self
+            .last_step_op
+            .map(|last| now.duration_since(last).as_secs_f32())
+            .unwrap_or(0.0);
+        self.last_step_op = Some(now);
+
+        #[cfg(feature = "physics")]
+        {
+            self.physics_manager.step();
+            // No one hears the collisions.
+            self.physics_manager.drain_collision_sound_v();
+        }
+
+        world::dispatch_step(self, dt).await;
+    }
+}
+
+impl AsClassManager for SimulationEngine {
+    fn append<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        if class == "$stop_propagation" {
+            self.world.stop_propagation();
+
+            return Box::pin(async { Ok(()) });
+        }
+
+        Box::pin(async move {
+            if class == "@random_seed" {
+                let seed = item_v
+                    .first()
+                    .and_then(|seed| seed.parse::<u64>().ok())
+                    .ok_or(moon_class::err::Error::NotFound)
+                    .attach_printable_lazy(|| format!("{item_v:?} is not a seed"))?;
+                self.world.set_seed(seed);
+
+                return Ok(());
+            }
+
+            snapshot::remember_origin(
+                &*self.data_manager,
+                &mut self.world.origin_data_mp,
+                class,
+                source,
+            )
+            .await?;
+
+            self.data_manager.append(class, source, item_v).await
+        })
+    }
+
+    fn remove<'a, 'a1, 'a2, 'f>(
+        &'a mut self,
+        class: &'a1 str,
+        source: &'a2 str,
+        item_v: Vec<String>,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<()>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            snapshot::remember_origin(
+                &*self.data_manager,
+                &mut self.world.origin_data_mp,
+                class,
+                source,
+            )
+            .await?;
+
+            self.data_manager.remove(class, source, item_v).await
+        })
+    }
+
+    fn get<'a, 'a1, 'a2, 'f>(
+        &'a self,
+        class: &'a1 str,
+        source: &'a2 str,
+    ) -> Pin<Box<dyn Fu<Output = moon_class::err::Result<Vec<String>>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+        'a2: 'f,
+    {
+        Box::pin(async move {
+            match class {
+                "@moon_world_pos" => {
+                    let vnode_id = source
+                        .parse::<u64>()
+                        .map_err(|_| moon_class::err::Error::NotFound)
+                        .attach_printable_lazy(|| format!("{source} is not a vnode id"))?;
+
+                    self.position(vnode_id)
+                        .map(|pos| pos.iter().map(|n| n.to_string()).collect())
+                        .ok_or(moon_class::err::Error::NotFound)
+                        .attach_printable_lazy(|| format!("not such body with id {vnode_id}"))
+                }
+                "@random" => Ok(vec![self.world.random().to_string()]),
+                _ => self.data_manager.get(class, source).await,
+            }
+        })
+    }
+}
+
+impl AsElementProvider for SimulationEngine {
+    type H = u64;
+
+    /// Let the body of a `Physics:` class be created, the other classes being left out.
+    fn create_element(&mut self, vnode_id: u64, class: &str, props: &json::JsonValue) -> u64 {
+        let suffix = match class.strip_prefix("Physics:") {
+            Some(suffix) => suffix,
+            None => return vnode_id,
+        };
+
+        #[cfg(feature = "physics")]
+        match self
+            .physics_manager
+            .try_create_element(vnode_id, suffix, props)
+        {
+            Ok(h) => {
+                self.element_mp.insert(vnode_id, h);
+                self.world.props_mp.insert(vnode_id, props.clone());
+            }
+            Err(e) => return self.world.skip_element(vnode_id, e),
+        }
+        #[cfg(not(feature = "physics"))]
+        self.world.skip_element(
+            vnode_id,
+            error_stack::Report::new(err::Error::UnsupportedElement)
+                .attach_printable(format!("Physics:{suffix} needs the physics feature")),
+        );
+
+        vnode_id
+    }
+
+    /// Let the element specified by the id be deleted.
+    fn delete_element(&mut self, id: u64) {
+        self.world.props_mp.remove(&id);
+
+        #[cfg(feature = "physics")]
+        if let Some(h) = self.element_mp.remove(&id) {
+            self.physics_manager.delete_element(h);
+        }
+    }
+
+    /// Let the element specified by the id be updated by this props.
+    fn update_element(&mut self, id: u64, class: &str, props: &json::JsonValue) {
+        let suffix = match class.strip_prefix("Physics:") {
+            Some(suffix) => suffix,
+            None => return,
+        };
+
+        let changed_key_set = self.world.apply_props(id, props);
+        if changed_key_set.is_empty() {
+            return;
+        }
+
+        #[cfg(feature = "physics")]
+        if let Some(h) = self.element_mp.get(&id) {
+            self.physics_manager
+                .update_changed(*h, suffix, props, &changed_key_set);
+        }
+        #[cfg(not(feature = "physics"))]
+        let _ = suffix;
+    }
+}
+
+impl AsViewManager for SimulationEngine {
+    fn get_class_view<'a, 'a1, 'f>(
+        &'a self,
+        class: &'a1 str,
+    ) -> Pin<Box<dyn Fu<Output = Option<String>> + 'f>>
+    where
+        'a: 'f,
+        'a1: 'f,
+    {
+        Box::pin(tree::class_view_of(&*self.data_manager, class))
+    }
+
+    fn get_vnode(&self, id: &u64) -> Option<&VNode> {
+        self.world.tree.vnode_mp.get(id)
+    }
+
+    fn get_vnode_mut(&mut self, id: &u64) -> Option<&mut VNode> {
+        self.world.tree.vnode_mp.get_mut(id)
+    }
+
+    fn new_vnode(&mut self, context: u64) -> u64 {
+        self.world.tree.new_vnode(context)
+    }
+
+    fn rm_vnode(&mut self, id: u64) -> Option<VNode> {
+        self.world.tree.rm_vnode(id)
+    }
+}
+
+impl AsWorld for SimulationEngine {
+    fn world(&self) -> &world::World {
+        &self.world
+    }
+
+    fn world_mut(&mut self) -> &mut world::World {
+        &mut self.world
+    }
+
+    /// The dynamic bodies get `$onstep`.
+    #[cfg(feature = "physics")]
+    fn stepped_id_v(&self) -> Vec<u64> {
+        self.element_mp
+            .iter()
+            .filter(|(_, h)| self.physics_manager.is_dynamic(**h))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Nothing gets `$onstep`, there being no bodies.
+    #[cfg(not(feature = "physics"))]
+    fn stepped_id_v(&self) -> Vec<u64> {
+        Vec::new()
+    }
+}
+
+#[cfg(all(test, feature = "physics"))]
+mod tests {
+    use moon_class::AsClassManager;
+    use rapier3d::prelude::IntegrationParameters;
+    use view_manager::{AsElementProvider, AsViewManager};
+
+    use crate::{dm::MemoryDataManager, EngineSnapshot};
+
+    /// called => the result = an engine with a dynamic body falling from 10 m, and its vnode id
+    fn falling_engine() -> (super::SimulationEngine, u64) {
+        let mut engine = super::SimulationEngine::new(
+            Box::new(MemoryDataManager::new()),
+            IntegrationParameters::default(),
+        );
+
+        let id = engine.new_vnode(0);
+        engine.create_element(
+            id,
+            "Physics:cube3",
+            &json::object! { "$body_type": ["dynamic"], "$position": [0, 10, 0] },
+        );
+
+        (engine, id)
+    }
+
+    #[tokio::test]
+    async fn test_step() {
+        let mut engine = super::SimulationEngine::new(
+            Box::new(MemoryDataManager::new()),
+            IntegrationParameters::default(),
+        );
+
+        let id = engine.new_vnode(0);
+        engine.create_element(
+            id,
+            "Physics:cube3",
+            &json::object! { "$body_type": ["dynamic"], "$position": [0, 10, 0] },
+        );
+        let skipped_id = engine.new_vnode(0);
+        engine.create_element(
+            skipped_id,
+            "Physics:cube3",
+            &json::object! { "$position": ["x"] },
+        );
+        let vision_id = engine.new_vnode(0);
+        engine.create_element(vision_id, "Vision:cube3", &json::Null);
+
+        for _ in 0..10 {
+            engine.step().await;
+        }
+
+        assert!(engine.position(id).unwrap()[1] < 10.0);
+        assert_eq!(engine.position(skipped_id), None);
+        assert_eq!(engine.element_mp.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot() {
+        let (mut engine, id) = falling_engine();
+        engine
+            .append("@random_seed", "", vec!["7".to_string()])
+            .await
+            .unwrap();
+        engine
+            .append("hp", "player", vec!["3".to_string()])
+            .await
+            .unwrap();
+        for _ in 0..5 {
+            engine.step().await;
+        }
+        engine.get("@random", "").await.unwrap();
+
+        let snapshot = engine.snapshot().await.unwrap();
+        let dump = snapshot.to_json().unwrap().dump();
+
+        // Changed after the snapshot, then brought back.
+        engine
+            .remove("hp", "player", vec!["3".to_string()])
+            .await
+            .unwrap();
+        for _ in 0..5 {
+            engine.step().await;
+        }
+        engine.restore(&snapshot).await.unwrap();
+
+        let (mut copy, copy_id) = falling_engine();
+        copy.restore(&EngineSnapshot::parse(&dump).unwrap())
+            .await
+            .unwrap();
+        assert!(
+            (copy.position(copy_id).unwrap()[1] - engine.position(id).unwrap()[1]).abs() < 1e-5
+        );
+
+        for _ in 0..10 {
+            engine.step().await;
+            copy.step().await;
+        }
+
+        let (pos, copy_pos) = (
+            engine.position(id).unwrap(),
+            copy.position(copy_id).unwrap(),
+        );
+        assert!(pos[1] < 9.0);
+        assert!((0..3).all(|i| (pos[i] - copy_pos[i]).abs() < 1e-5));
+        assert_eq!(engine.world.step_index, copy.world.step_index);
+        assert_eq!(
+            engine.get("@random", "").await.unwrap(),
+            copy.get("@random", "").await.unwrap()
+        );
+        assert_eq!(engine.get("hp", "player").await.unwrap(), vec!["3"]);
+        assert_eq!(copy.get("hp", "player").await.unwrap(), vec!["3"]);
+    }
+}
@@ -0,0 +1,250 @@
+//! Help the state of an engine be taken at a step and brought back later, in the same engine or
+//! in another one loaded from its JSON, like for a rewind or a client-side prediction.
+
+use std::collections::{HashMap, HashSet};
+
+use error_stack::ResultExt;
+use moon_class::AsClassManager;
+
+#[cfg(feature = "physics")]
+use crate::physics;
+use crate::{err, util::random::Random};
+
+/// The class data by `(class, source)`.
+pub type DataMap = HashMap<(String, String), Vec<String>>;
+
+/// The state of an engine at a step, taken by [crate::Engine::snapshot] or
+/// [crate::SimulationEngine::snapshot] to be restored later.
+#[derive(Clone)]
+pub struct EngineSnapshot {
+    pub(crate) vnode_id_set: HashSet<u64>,
+    pub(crate) props_mp: HashMap<u64, json::JsonValue>,
+    pub(crate) spawn_mp: HashMap<u64, Vec<u64>>,
+    /// The class data the engine changed so far.
+    pub(crate) data_mp: DataMap,
+    #[cfg(feature = "physics")]
+    pub(crate) physics_state: physics::PhysicsState,
+    pub(crate) step_index: u64,
+    pub(crate) random: Random,
+    pub(crate) is_paused: bool,
+}
+
+impl EngineSnapshot {
+    /// called => the result = `{$vnode_id_v, $props_v, $spawn_v, $data_v, $physics, $step,
+    /// $random, $paused}`, `$physics` being the state of the physics as a JSON string
+    pub fn to_json(&self) -> err::Result<json::JsonValue> {
+        let mut vnode_id_v = self.vnode_id_set.iter().copied().collect::<Vec<u64>>();
+        vnode_id_v.sort_unstable();
+
+        #[cfg_attr(not(feature = "physics"), allow(unused_mut))]
+        let mut object = json::object! {
+            "$vnode_id_v": vnode_id_v,
+            "$props_v": self.props_mp.iter().map(|(id, props)| json::object! {
+                "$id": *id,
+                "$props": props.clone(),
+            }).collect::<Vec<json::JsonValue>>(),
+            "$spawn_v": self.spawn_mp.iter().map(|(parent, child_v)| json::object! {
+                "$parent": *parent,
+                "$child_v": child_v.clone(),
+            }).collect::<Vec<json::JsonValue>>(),
+            "$data_v": self.data_mp.iter().map(|((class, source), item_v)| json::object! {
+                "$class": class.as_str(),
+                "$source": source.as_str(),
+                "$item_v": item_v.clone(),
+            }).collect::<Vec<json::JsonValue>>(),
+            "$step": self.step_index,
+            "$random": self.random.state(),
+            "$paused": self.is_paused,
+        };
+        #[cfg(feature = "physics")]
+        {
+            object["$physics"] = serde_json::to_string(&self.physics_state)
+                .change_context(err::Error::Physics)
+                .attach_printable("failed to serialize the physics")?
+                .into();
+        }
+
+        Ok(object)
+    }
+
+    /// called => the result = the snapshot dumped by [EngineSnapshot::to_json]
+    pub fn parse(s: &str) -> err::Result<Self> {
+        let object = json::parse(s)
+            .change_context(err::Error::Other)
+            .attach_printable("a snapshot is not JSON")?;
+
+        let mut props_mp = HashMap::new();
+        for props in object["$props_v"].members() {
+            props_mp.insert(id_of(&props["$id"])?, props["$props"].clone());
+        }
+        let mut spawn_mp = HashMap::new();
+        for spawn in object["$spawn_v"].members() {
+            spawn_mp.insert(
+                id_of(&spawn["$parent"])?,
+                spawn["$child_v"]
+                    .members()
+                    .map(id_of)
+                    .collect::<err::Result<Vec<u64>>>()?,
+            );
+        }
+        let mut data_mp = HashMap::new();
+        for data in object["$data_v"].members() {
+            match (data["$class"].as_str(), data["$source"].as_str()) {
+                (Some(class), Some(source)) => {
+                    data_mp.insert(
+                        (class.to_string(), source.to_string()),
+                        data["$item_v"]
+                            .members()
+                            .filter_map(|item| item.as_str().map(|item| item.to_string()))
+                            .collect(),
+                    );
+                }
+                _ => {
+                    return Err(err::Error::NotFound)
+                        .attach_printable_lazy(|| format!("no $class or $source in {data}"))
+                }
+            }
+        }
+
+        Ok(Self {
+            vnode_id_set: object["$vnode_id_v"]
+                .members()
+                .map(id_of)
+                .collect::<err::Result<HashSet<u64>>>()?,
+            props_mp,
+            spawn_mp,
+            data_mp,
+            #[cfg(feature = "physics")]
+            physics_state: serde_json::from_str(
+                object["$physics"]
+                    .as_str()
+                    .ok_or(err::Error::NotFound)
+                    .attach_printable("no $physics in the snapshot")?,
+            )
+            .change_context(err::Error::Physics)
+            .attach_printable("failed to deserialize the physics")?,
+            step_index: object["$step"].as_u64().unwrap_or(0),
+            random: Random::new(object["$random"].as_u64().unwrap_or(0)),
+            is_paused: object["$paused"].as_bool().unwrap_or(false),
+        })
+    }
+}
+
+/// called => the result = the vnode id of the JSON number
+fn id_of(value: &json::JsonValue) -> err::Result<u64> {
+    value
+        .as_u64()
+        .ok_or(err::Error::NotFound)
+        .attach_printable_lazy(|| format!("{value} is not a vnode id"))
+}
+
+/// Let the items of the class data be remembered as they are before the engine first changes
+/// them, so a snapshot taken before can bring them back.
+pub async fn remember_origin(
+    data_manager: &dyn AsClassManager,
+    origin_data_mp: &mut DataMap,
+    class: &str,
+    source: &str,
+) -> moon_class::err::Result<()> {
+    let key = (class.to_string(), source.to_string());
+    if !origin_data_mp.contains_key(&key) {
+        let item_v = data_manager.get(class, source).await?;
+        origin_data_mp.insert(key, item_v);
+    }
+
+    Ok(())
+}
+
+/// called => the result = the items of the class data the engine changed so far
+pub async fn data_of(
+    data_manager: &dyn AsClassManager,
+    origin_data_mp: &DataMap,
+) -> err::Result<DataMap> {
+    let mut data_mp = HashMap::new();
+    for (class, source) in origin_data_mp.keys() {
+        let item_v = data_manager
+            .get(class, source)
+            .await
+            .change_context(err::Error::Data)?;
+        data_mp.insert((class.clone(), source.clone()), item_v);
+    }
+
+    Ok(data_mp)
+}
+
+/// Let the class data be back to the items of the snapshot, the data changed only since it
+/// back to what it was before.
+///
+/// called => the result = the `(class, source)` changed
+pub async fn restore_data(
+    data_manager: &mut dyn AsClassManager,
+    origin_data_mp: &mut DataMap,
+    data_mp: &DataMap,
+) -> err::Result<Vec<(String, String)>> {
+    for (class, source) in data_mp.keys() {
+        remember_origin(&*data_manager, origin_data_mp, class, source)
+            .await
+            .change_context(err::Error::Data)?;
+    }
+
+    let mut changed_v = Vec::new();
+    for (key, origin) in origin_data_mp.iter() {
+        let (class, source) = key;
+        let item_v = data_mp.get(key).unwrap_or(origin);
+        let current_v = data_manager
+            .get(class, source)
+            .await
+            .change_context(err::Error::Data)?;
+        if current_v == *item_v {
+            continue;
+        }
+
+        data_manager
+            .remove(class, source, current_v)
+            .await
+            .change_context(err::Error::Data)?;
+        data_manager
+            .append(class, source, item_v.clone())
+            .await
+            .change_context(err::Error::Data)
+            .attach_printable_lazy(|| format!("failed to restore {class} of {source}"))?;
+        changed_v.push(key.clone());
+    }
+
+    Ok(changed_v)
+}
+
+#[cfg(test)]
+mod tests {
+    use moon_class::AsClassManager;
+
+    use crate::dm::MemoryDataManager;
+
+    #[tokio::test]
+    async fn test_restore_data() {
+        let mut dm = MemoryDataManager::new();
+        let mut origin_data_mp = super::DataMap::new();
+        dm.append("hp", "a", vec!["3".to_string()]).await.unwrap();
+
+        super::remember_origin(&dm, &mut origin_data_mp, "hp", "a")
+            .await
+            .unwrap();
+        dm.append("hp", "a", vec!["2".to_string()]).await.unwrap();
+        let data_mp = super::data_of(&dm, &origin_data_mp).await.unwrap();
+
+        // Changed after the snapshot, one item changed and one class new.
+        dm.remove("hp", "a", vec!["3".to_string()]).await.unwrap();
+        super::remember_origin(&dm, &mut origin_data_mp, "mp", "a")
+            .await
+            .unwrap();
+        dm.append("mp", "a", vec!["1".to_string()]).await.unwrap();
+
+        let changed_v = super::restore_data(&mut dm, &mut origin_data_mp, &data_mp)
+            .await
+            .unwrap();
+
+        assert_eq!(changed_v.len(), 2);
+        assert_eq!(dm.get("hp", "a").await.unwrap(), vec!["3", "2"]);
+        assert!(dm.get("mp", "a").await.unwrap().is_empty());
+    }
+}
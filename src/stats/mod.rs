@@ -0,0 +1,151 @@
+//! Help the performance of the engine be measured frame by frame.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use drawer::overlay::{self, OverlayImage};
+
+/// How many frame times are kept for the graph.
+const FRAME_TIME_COUNT: usize = 120;
+
+/// The pixels of the height of the graph of the frame times.
+const GRAPH_HEIGHT: usize = 48;
+
+/// The frame time as high as the graph, two frames at 60 fps.
+const GRAPH_MAX_MS: f32 = 1000.0 / 30.0;
+
+/// The statistics of the recent frames.
+#[derive(Default)]
+pub struct FrameStats {
+    last_frame_op: Option<Instant>,
+    /// The times between the recent frames, the newest last.
+    frame_time_v: VecDeque<Duration>,
+    physics_step_time: Duration,
+    body_count: usize,
+    draw_count: usize,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let a frame rendered at the time be counted.
+    pub fn record_frame(&mut self, now: Instant) {
+        if let Some(last) = self.last_frame_op {
+            self.frame_time_v.push_back(now.duration_since(last));
+
+            if self.frame_time_v.len() > FRAME_TIME_COUNT {
+                self.frame_time_v.pop_front();
+            }
+        }
+        self.last_frame_op = Some(now);
+    }
+
+    /// Let the time the last physics step took be kept.
+    pub fn record_physics_step(&mut self, time: Duration) {
+        self.physics_step_time = time;
+    }
+
+    /// Let the bodies simulated and the elements drawn by the last frame be counted.
+    pub fn record_count(&mut self, body_count: usize, draw_count: usize) {
+        self.body_count = body_count;
+        self.draw_count = draw_count;
+    }
+
+    /// called => the result = the frames per second over the recent frames
+    pub fn fps(&self) -> f32 {
+        let total = self.frame_time_v.iter().sum::<Duration>().as_secs_f32();
+        if total <= 0.0 {
+            return 0.0;
+        }
+
+        self.frame_time_v.len() as f32 / total
+    }
+
+    /// called => the result = the times between the recent frames in milliseconds, the newest
+    /// last
+    pub fn frame_time_ms_v(&self) -> Vec<f32> {
+        self.frame_time_v
+            .iter()
+            .map(|time| time.as_secs_f32() * 1000.0)
+            .collect()
+    }
+
+    /// called => the result = `{$fps, $frame_time_v, $body_count, $draw_count,
+    /// $physics_step_ms}`
+    pub fn to_json(&self) -> json::JsonValue {
+        json::object! {
+            "$fps": self.fps(),
+            "$frame_time_v": self.frame_time_ms_v(),
+            "$body_count": self.body_count,
+            "$draw_count": self.draw_count,
+            "$physics_step_ms": self.physics_step_time.as_secs_f32() * 1000.0,
+        }
+    }
+
+    /// called => the result = an image of the fps, the counts and the physics step time, above a
+    /// graph of the recent frame times, a bar a frame
+    pub fn hud_image(&self) -> OverlayImage {
+        let line_v = vec![
+            format!("{:.1} FPS", self.fps()),
+            format!("{} BODIES {} DRAWS", self.body_count, self.draw_count),
+            format!(
+                "PHYSICS {:.2} MS",
+                self.physics_step_time.as_secs_f32() * 1000.0
+            ),
+        ];
+        let (text_width, text_height) = overlay::text_size_of(&line_v);
+
+        let mut image = OverlayImage::new(
+            text_width.max(FRAME_TIME_COUNT) + overlay::PADDING * 2,
+            text_height + GRAPH_HEIGHT + overlay::PADDING * 3,
+        );
+        image.draw_line_v(
+            overlay::PADDING,
+            overlay::PADDING,
+            &line_v,
+            [255, 255, 255, 255],
+        );
+        image.draw_graph(
+            [
+                overlay::PADDING,
+                text_height + overlay::PADDING * 2,
+                FRAME_TIME_COUNT,
+                GRAPH_HEIGHT,
+            ],
+            &self.frame_time_ms_v(),
+            GRAPH_MAX_MS,
+        );
+
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_fps() {
+        let mut stats = super::FrameStats::new();
+        assert_eq!(stats.fps(), 0.0);
+
+        let start = Instant::now();
+        for i in 0..=super::FRAME_TIME_COUNT + 10 {
+            stats.record_frame(start + Duration::from_millis(20 * i as u64));
+        }
+
+        assert!((stats.fps() - 50.0).abs() < 0.01);
+        assert_eq!(stats.frame_time_ms_v().len(), super::FRAME_TIME_COUNT);
+
+        stats.record_count(3, 2);
+        assert_eq!(stats.to_json()["$draw_count"], 2);
+
+        let image = stats.hud_image();
+        assert!(image.width() >= super::FRAME_TIME_COUNT);
+        assert!(image.height() > super::GRAPH_HEIGHT);
+    }
+}
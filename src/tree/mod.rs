@@ -0,0 +1,70 @@
+//! Help the engines keep the view tree alike, the windowed one and the one of the simulation.
+
+use std::collections::HashMap;
+
+use moon_class::{util::rs_2_str, AsClassManager};
+use view_manager::VNode;
+
+/// The vnodes of a view tree by id, each new one of an id never used before.
+#[derive(Default)]
+pub struct VNodeTree {
+    unique_id: u64,
+    pub vnode_mp: HashMap<u64, VNode>,
+}
+
+impl VNodeTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// called => the result = the id of a new vnode in the context
+    pub fn new_vnode(&mut self, context: u64) -> u64 {
+        let new_id = self.unique_id;
+        self.unique_id += 1;
+        self.vnode_mp.insert(new_id, VNode::new(context));
+
+        new_id
+    }
+
+    pub fn rm_vnode(&mut self, id: u64) -> Option<VNode> {
+        self.vnode_mp.remove(&id)
+    }
+}
+
+/// called => the result = the view of the class in the data manager, or `None` if there is none
+/// or it can not be got
+pub async fn class_view_of(data_manager: &dyn AsClassManager, class: &str) -> Option<String> {
+    match data_manager.get("view", class).await {
+        Ok(rs) if rs.is_empty() => None,
+        Ok(rs) => Some(rs_2_str(&rs)),
+        Err(e) => {
+            log::error!("failed to get the view of {class}: {e:?}");
+
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moon_class::AsClassManager;
+
+    use crate::dm::MemoryDataManager;
+
+    #[tokio::test]
+    async fn test_class_view_of() {
+        let mut dm = MemoryDataManager::new();
+        dm.append("view", "Box", vec!["<div/>".to_string()])
+            .await
+            .unwrap();
+
+        assert!(super::class_view_of(&dm, "Box").await.is_some());
+        assert_eq!(super::class_view_of(&dm, "Ball").await, None);
+
+        let mut tree = super::VNodeTree::new();
+        let id = tree.new_vnode(0);
+        assert_ne!(tree.new_vnode(id), id);
+        assert!(tree.rm_vnode(id).is_some());
+        assert!(!tree.vnode_mp.contains_key(&id));
+    }
+}
@@ -1,13 +1,20 @@
 //! Help the crate be a video provider, a event handler or a shape builder.
 
+#[cfg(feature = "physics")]
 use rapier3d::prelude::{Collider, GenericJoint};
 
+pub mod cadence;
+pub mod prop;
+pub mod random;
 pub mod shape;
+pub mod vec_math;
 
+#[cfg(feature = "physics")]
 pub struct BodyCollider {
     pub collider_v: Vec<Collider>,
 }
 
+#[cfg(feature = "physics")]
 pub struct Joint {
     pub body1: u64,
     pub body2: u64,
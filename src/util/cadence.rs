@@ -0,0 +1,87 @@
+//! Help a part of the engine, like the physics or the rendering, run at its own frequency.
+
+use std::time::Duration;
+
+/// The most ticks run for one advance, so a long stall is not caught up forever.
+const MAX_TICK_COUNT: u32 = 5;
+
+/// An accumulator of the time elapsed, ticking once per period.
+///
+/// Without a period, it ticks once per advance, as often as the engine is driven.
+#[derive(Clone, Copy, Default)]
+pub struct Cadence {
+    period_op: Option<Duration>,
+    acc: Duration,
+}
+
+impl Cadence {
+    /// called => the result = a [Cadence] ticking `hz` times per second, or once per advance if
+    /// `hz` is `None`, not positive and finite, or too low for a period
+    pub fn new(hz_op: Option<f32>) -> Self {
+        Self {
+            period_op: hz_op
+                .filter(|hz| *hz > 0.0 && hz.is_finite())
+                .and_then(|hz| Duration::try_from_secs_f32(1.0 / hz).ok()),
+            acc: Duration::ZERO,
+        }
+    }
+
+    pub fn period_op(&self) -> Option<Duration> {
+        self.period_op
+    }
+
+    /// Let the elapsed time be accumulated.
+    ///
+    /// called => the result = how many ticks are due
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        let period = match self.period_op {
+            Some(period) => period,
+            None => return 1,
+        };
+
+        self.acc += elapsed;
+        let mut count = 0;
+        while self.acc >= period {
+            self.acc -= period;
+            count += 1;
+
+            if count == MAX_TICK_COUNT {
+                self.acc = Duration::ZERO;
+                break;
+            }
+        }
+
+        count
+    }
+
+    /// called => the result = the seconds a tick stands for, `elapsed` without a period
+    pub fn dt(&self, elapsed: Duration) -> f32 {
+        self.period_op.unwrap_or(elapsed).as_secs_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn test_advance() {
+        let mut uncapped = super::Cadence::new(None);
+        assert_eq!(uncapped.advance(Duration::ZERO), 1);
+        assert_eq!(uncapped.advance(Duration::from_secs(1)), 1);
+
+        let mut cadence = super::Cadence::new(Some(10.0));
+        assert_eq!(cadence.advance(Duration::from_millis(50)), 0);
+        assert_eq!(cadence.advance(Duration::from_millis(60)), 1);
+        assert_eq!(cadence.advance(Duration::from_millis(200)), 2);
+        assert_eq!(
+            cadence.advance(Duration::from_secs(10)),
+            super::MAX_TICK_COUNT
+        );
+        assert_eq!(cadence.advance(Duration::ZERO), 0);
+
+        for hz in [0.0, f32::NAN, f32::INFINITY, 1e-30] {
+            assert_eq!(super::Cadence::new(Some(hz)).period_op(), None);
+        }
+    }
+}
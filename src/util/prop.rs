@@ -0,0 +1,129 @@
+//! Help the props be read whether their items are strings, numbers or booleans.
+//!
+//! A prop is usually a list like `["1.0", "2.0"]`, but `[1, 2]`, `[true]` or a bare `1` are
+//! read the same way.
+
+use std::collections::HashSet;
+
+/// called => the result = the keys of the props whose values differ between the old and the new,
+/// the keys added or removed included
+pub fn changed_key_set(old: &json::JsonValue, new: &json::JsonValue) -> HashSet<String> {
+    old.entries()
+        .chain(new.entries())
+        .map(|(key, _)| key)
+        .filter(|key| old[*key] != new[*key])
+        .map(|key| key.to_string())
+        .collect()
+}
+
+/// called => the result = the items of the prop, a bare value being the only item
+fn item_v(props: &json::JsonValue, key: &str) -> Vec<&json::JsonValue> {
+    let value = &props[key];
+
+    if value.is_array() {
+        value.members().collect()
+    } else if value.is_null() {
+        Vec::new()
+    } else {
+        vec![value]
+    }
+}
+
+/// called => the result = the item as a number
+pub fn f32_of(item: &json::JsonValue) -> Option<f32> {
+    match item {
+        json::JsonValue::Number(_) => item.as_f32(),
+        json::JsonValue::Boolean(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => item.as_str().and_then(|s| s.trim().parse().ok()),
+    }
+}
+
+/// called => the result = the item as a boolean
+pub fn bool_of(item: &json::JsonValue) -> Option<bool> {
+    match item {
+        json::JsonValue::Boolean(b) => Some(*b),
+        json::JsonValue::Number(_) => item.as_f32().map(|n| n != 0.0),
+        _ => match item.as_str()?.trim() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+    }
+}
+
+/// called => the result = the item as a text
+pub fn string_of(item: &json::JsonValue) -> Option<String> {
+    match item {
+        json::JsonValue::Number(_) | json::JsonValue::Boolean(_) => Some(item.dump()),
+        _ => item.as_str().map(|s| s.to_string()),
+    }
+}
+
+/// called => the result = the first item of the prop as a number
+pub fn number(props: &json::JsonValue, key: &str) -> Option<f32> {
+    item_v(props, key).first().and_then(|item| f32_of(item))
+}
+
+/// called => the result = the first item of the prop as a boolean
+pub fn boolean(props: &json::JsonValue, key: &str) -> Option<bool> {
+    item_v(props, key).first().and_then(|item| bool_of(item))
+}
+
+/// called => the result = the first item of the prop as a text
+pub fn string(props: &json::JsonValue, key: &str) -> Option<String> {
+    item_v(props, key).first().and_then(|item| string_of(item))
+}
+
+/// called => the result = the items of the prop as numbers, or `None` if any is not a number
+pub fn number_v(props: &json::JsonValue, key: &str) -> Option<Vec<f32>> {
+    let item_v = item_v(props, key);
+    if item_v.is_empty() {
+        return None;
+    }
+
+    item_v.into_iter().map(f32_of).collect()
+}
+
+/// called => the result = the first three items of the prop as numbers
+pub fn number3(props: &json::JsonValue, key: &str) -> Option<[f32; 3]> {
+    match number_v(props, key)?.as_slice() {
+        [x, y, z, ..] => Some([*x, *y, *z]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_coercion() {
+        let props = json::object! {
+            "$a": ["1.5", 2, true],
+            "$b": 3,
+            "$c": ["false"],
+            "$d": ["x"],
+        };
+
+        assert_eq!(super::number_v(&props, "$a"), Some(vec![1.5, 2.0, 1.0]));
+        assert_eq!(super::number(&props, "$b"), Some(3.0));
+        assert_eq!(super::boolean(&props, "$c"), Some(false));
+        assert_eq!(super::string(&props, "$b").as_deref(), Some("3"));
+        assert_eq!(super::number_v(&props, "$d"), None);
+        assert_eq!(super::number3(&props, "$c"), None);
+        assert_eq!(super::number(&props, "$e"), None);
+    }
+
+    #[test]
+    fn test_changed_key_set() {
+        let old = json::object! { "$a": ["1"], "$b": ["2"], "$c": ["3"] };
+        let new = json::object! { "$a": ["1"], "$b": ["4"], "$d": ["5"] };
+
+        let mut key_v = super::changed_key_set(&old, &new)
+            .into_iter()
+            .collect::<Vec<String>>();
+        key_v.sort();
+
+        assert_eq!(key_v, vec!["$b", "$c", "$d"]);
+        assert!(super::changed_key_set(&old, &old).is_empty());
+        assert_eq!(super::changed_key_set(&json::Null, &old).len(), 3);
+    }
+}
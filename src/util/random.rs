@@ -0,0 +1,53 @@
+//! Help the views draw random numbers that come again the same from the same seed, so a
+//! snapshot or a replay of the engine draws them alike.
+
+/// A SplitMix64 generator, its whole state a number.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// called => the result = the number the generator goes on from, the seed of a
+    /// [Random::new] drawing the same numbers
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// called => the result = the next number in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+
+        // The 53 bits a f64 holds exactly.
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Random;
+
+    #[test]
+    fn test_next_f64() {
+        let mut random = Random::new(7);
+        let value_v = (0..100).map(|_| random.next_f64()).collect::<Vec<f64>>();
+
+        assert!(value_v.iter().all(|value| (0.0..1.0).contains(value)));
+        assert_ne!(value_v[0], value_v[1]);
+
+        // Going on from the state draws what is left alike.
+        let mut random = Random::new(7);
+        random.next_f64();
+        let mut copy = Random::new(random.state());
+        assert_eq!(copy.next_f64(), value_v[1]);
+    }
+}
@@ -39,4 +39,198 @@ impl Shape {
     pub fn from_strip(point_v: Vec<Point2<f32>>) -> Self {
         Self { point_v }
     }
+
+    /// called => the result = the arc of the circle of the radius from the start angle to the end
+    /// angle, in radians counterclockwise from +X, as a strip of the segments
+    pub fn arc(radius: f32, start: f32, end: f32, segments: usize) -> Self {
+        let segments = segments.max(1);
+        let point_v = (0..=segments)
+            .map(|i| {
+                let angle = start + (end - start) * i as f32 / segments as f32;
+                Point2::new(radius * angle.cos(), radius * angle.sin())
+            })
+            .collect();
+
+        Self { point_v }
+    }
+
+    /// called => the result = the Bézier curve of the control points, of any degree, as a strip
+    /// of the segments
+    pub fn bezier(control_point_v: &[Point2<f32>], segments: usize) -> Self {
+        if control_point_v.len() < 2 {
+            return Self::from_strip(control_point_v.to_vec());
+        }
+
+        let segments = segments.max(1);
+        let point_v = (0..=segments)
+            .map(|i| {
+                let t = i as f32 / segments as f32;
+                // de Casteljau: lerp the neighbours until one point is left.
+                let mut point_v = control_point_v.to_vec();
+                while point_v.len() > 1 {
+                    point_v = point_v
+                        .windows(2)
+                        .map(|pair| pair[0] + (pair[1] - pair[0]) * t)
+                        .collect();
+                }
+
+                point_v[0]
+            })
+            .collect();
+
+        Self { point_v }
+    }
+
+    /// called => the result = the outline through the points, closed back to the first one
+    ///
+    /// The points go around the polygon in either direction; it may be concave but should not
+    /// cross itself.
+    pub fn polygon(mut point_v: Vec<Point2<f32>>) -> Self {
+        if let Some(first) = point_v.first().copied() {
+            point_v.push(first);
+        }
+
+        Self { point_v }
+    }
+
+    /// called => the result = the triangles filling the outline, as the indexes of their points,
+    /// counterclockwise
+    ///
+    /// The ears are clipped one by one; what is left of an outline crossing itself is not filled.
+    pub fn triangle_index_v(&self) -> Vec<[usize; 3]> {
+        let mut index_v = (0..self.point_v.len()).collect::<Vec<usize>>();
+        // A closed outline ends where it starts.
+        if let [first, .., last] = self.point_v.as_slice() {
+            if (last - first).norm() <= f32::EPSILON {
+                index_v.pop();
+            }
+        }
+        if index_v.len() < 3 {
+            return Vec::new();
+        }
+
+        let area2 = index_v
+            .iter()
+            .zip(index_v.iter().cycle().skip(1))
+            .map(|(a, b)| self.point_v[*a].coords.perp(&self.point_v[*b].coords))
+            .sum::<f32>();
+        if area2 < 0.0 {
+            index_v.reverse();
+        }
+
+        let cross = |a: usize, b: usize, c: usize| {
+            (self.point_v[b] - self.point_v[a]).perp(&(self.point_v[c] - self.point_v[b]))
+        };
+        let mut triangle_v = Vec::with_capacity(index_v.len() - 2);
+
+        while index_v.len() > 3 {
+            let len = index_v.len();
+            let ear_op = (0..len).find_map(|i| {
+                let (a, b, c) = (
+                    index_v[(i + len - 1) % len],
+                    index_v[i],
+                    index_v[(i + 1) % len],
+                );
+                let turn = cross(a, b, c);
+
+                // A point in a straight line is dropped, filling nothing.
+                if turn.abs() <= f32::EPSILON {
+                    return Some((i, None));
+                }
+                let is_ear = turn > 0.0
+                    && index_v.iter().all(|p| {
+                        [a, b, c].contains(p)
+                            || cross(a, b, *p) < 0.0
+                            || cross(b, c, *p) < 0.0
+                            || cross(c, a, *p) < 0.0
+                    });
+
+                is_ear.then_some((i, Some([a, b, c])))
+            });
+
+            match ear_op {
+                Some((i, triangle_op)) => {
+                    triangle_v.extend(triangle_op);
+                    index_v.remove(i);
+                }
+                None => return triangle_v,
+            }
+        }
+
+        if cross(index_v[0], index_v[1], index_v[2]) > f32::EPSILON {
+            triangle_v.push([index_v[0], index_v[1], index_v[2]]);
+        }
+
+        triangle_v
+    }
+
+    /// called => the result = the points of the triangles filling the outline, three a triangle
+    pub fn fill_v(&self) -> Vec<Point2<f32>> {
+        self.triangle_index_v()
+            .into_iter()
+            .flatten()
+            .map(|i| self.point_v[i])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+
+    #[test]
+    fn test_polygon_fill() {
+        // An L, clockwise.
+        let shape = super::Shape::polygon(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(0.0, 2.0),
+            Point2::new(1.0, 2.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(2.0, 1.0),
+            Point2::new(2.0, 0.0),
+        ]);
+        assert_eq!(shape.point_v.len(), 7);
+
+        let triangle_v = shape.triangle_index_v();
+        assert_eq!(triangle_v.len(), 4);
+
+        let area = triangle_v
+            .iter()
+            .map(|[a, b, c]| {
+                (shape.point_v[*b] - shape.point_v[*a])
+                    .perp(&(shape.point_v[*c] - shape.point_v[*a]))
+                    * 0.5
+            })
+            .sum::<f32>();
+        assert!((area - 3.0).abs() < 1e-5);
+        assert_eq!(shape.fill_v().len(), 12);
+
+        assert!(super::Shape::polygon(vec![Point2::new(0.0, 0.0)])
+            .triangle_index_v()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_arc_bezier() {
+        let arc = super::Shape::arc(2.0, 0.0, std::f32::consts::FRAC_PI_2, 4);
+        assert_eq!(arc.point_v.len(), 5);
+        assert!((arc.point_v[4] - Point2::new(0.0, 2.0)).norm() < 1e-5);
+
+        let bezier = super::Shape::bezier(
+            &[
+                Point2::new(0.0, 0.0),
+                Point2::new(1.0, 2.0),
+                Point2::new(2.0, 0.0),
+            ],
+            2,
+        );
+        assert_eq!(
+            bezier.point_v,
+            [
+                Point2::new(0.0, 0.0),
+                Point2::new(1.0, 1.0),
+                Point2::new(2.0, 0.0)
+            ]
+        );
+    }
 }
@@ -0,0 +1,134 @@
+//! Help the scripts do vector math, which their string lists have no operators for.
+//!
+//! The functions are got like `@vec_add(1,2,3;4,5,6)`: the numbers of a vector are separated
+//! by `,` and the vectors by `;`. The result is a number per item.
+
+use std::collections::HashMap;
+
+use error_stack::ResultExt;
+use nalgebra::UnitQuaternion;
+
+use crate::ClassFn;
+
+/// called => the result = the vectors in the source
+fn vector_v_of(source: &str) -> moon_class::err::Result<Vec<Vec<f32>>> {
+    source
+        .split(';')
+        .map(|vector| {
+            vector
+                .split(',')
+                .map(|n| n.trim().parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+                .change_context(moon_class::err::Error::NotFound)
+                .attach_printable_lazy(|| format!("{vector} is not a vector"))
+        })
+        .collect()
+}
+
+/// called => the result = the two vectors of the same length in the source
+fn pair_of(source: &str) -> moon_class::err::Result<(Vec<f32>, Vec<f32>)> {
+    match <[Vec<f32>; 2]>::try_from(vector_v_of(source)?) {
+        Ok([a, b]) if a.len() == b.len() => Ok((a, b)),
+        _ => Err(moon_class::err::Error::NotFound)
+            .attach_printable_lazy(|| format!("{source} is not two vectors of the same length")),
+    }
+}
+
+fn item_v_of(n_v: impl IntoIterator<Item = f32>) -> Vec<String> {
+    n_v.into_iter().map(|n| n.to_string()).collect()
+}
+
+/// called => the result = `a + b` of `a;b`
+pub fn vec_add(source: &str) -> moon_class::err::Result<Vec<String>> {
+    let (a, b) = pair_of(source)?;
+
+    Ok(item_v_of(a.iter().zip(&b).map(|(a, b)| a + b)))
+}
+
+/// called => the result = `v * s` of `v;s`
+pub fn vec_scale(source: &str) -> moon_class::err::Result<Vec<String>> {
+    match vector_v_of(source)?.as_slice() {
+        [v, s] if s.len() == 1 => Ok(item_v_of(v.iter().map(|n| n * s[0]))),
+        _ => Err(moon_class::err::Error::NotFound)
+            .attach_printable_lazy(|| format!("{source} is not a vector and a number")),
+    }
+}
+
+/// called => the result = the unit vector along the vector, or the zero vector itself
+pub fn vec_normalize(source: &str) -> moon_class::err::Result<Vec<String>> {
+    let v = match vector_v_of(source)?.as_slice() {
+        [v] => v.clone(),
+        _ => {
+            return Err(moon_class::err::Error::NotFound)
+                .attach_printable_lazy(|| format!("{source} is not a vector"))
+        }
+    };
+    let norm = v.iter().map(|n| n * n).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return Ok(item_v_of(v));
+    }
+
+    Ok(item_v_of(v.iter().map(|n| n / norm)))
+}
+
+/// called => the result = `a · b` of `a;b`
+pub fn vec_dot(source: &str) -> moon_class::err::Result<Vec<String>> {
+    let (a, b) = pair_of(source)?;
+
+    Ok(item_v_of([a.iter().zip(&b).map(|(a, b)| a * b).sum()]))
+}
+
+/// called => the result = the quaternion `[x, y, z, w]` of the euler angles `roll,pitch,yaw` in
+/// degrees, as `$rotation` takes them
+pub fn quat_from_euler(source: &str) -> moon_class::err::Result<Vec<String>> {
+    match vector_v_of(source)?.as_slice() {
+        [v] if v.len() == 3 => {
+            let q = UnitQuaternion::from_euler_angles(
+                v[0].to_radians(),
+                v[1].to_radians(),
+                v[2].to_radians(),
+            );
+
+            Ok(item_v_of([q.i, q.j, q.k, q.w]))
+        }
+        _ => Err(moon_class::err::Error::NotFound)
+            .attach_printable_lazy(|| format!("{source} is not roll, pitch and yaw")),
+    }
+}
+
+/// called => the result = the functions of this module as classes, for the engine to start with
+pub fn class_fn_mp() -> HashMap<String, ClassFn> {
+    let fn_v: [(&str, fn(&str) -> moon_class::err::Result<Vec<String>>); 5] = [
+        ("@vec_add", vec_add),
+        ("@vec_scale", vec_scale),
+        ("@vec_normalize", vec_normalize),
+        ("@vec_dot", vec_dot),
+        ("@quat_from_euler", quat_from_euler),
+    ];
+
+    fn_v.into_iter()
+        .map(|(name, f)| {
+            let class_fn: ClassFn = Box::new(move |source, _| f(source));
+
+            (name.to_string(), class_fn)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_vec_math() {
+        assert_eq!(super::vec_add("1,2,3;4,5,6").unwrap(), ["5", "7", "9"]);
+        assert_eq!(super::vec_scale("1,2;2").unwrap(), ["2", "4"]);
+        assert_eq!(super::vec_normalize("3,0,4").unwrap(), ["0.6", "0", "0.8"]);
+        assert_eq!(super::vec_dot("1,2,3;4,5,6").unwrap(), ["32"]);
+        assert_eq!(
+            super::quat_from_euler("0,0,0").unwrap(),
+            ["0", "0", "0", "1"]
+        );
+
+        assert!(super::vec_add("1,2;3").is_err());
+        assert!(super::vec_dot("1,x").is_err());
+    }
+}
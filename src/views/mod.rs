@@ -0,0 +1,138 @@
+//! Help the views be written one file a class, instead of being baked into one script.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use error_stack::ResultExt;
+use moon_class::AsClassManager;
+
+use crate::err;
+
+/// A directory of `.class` files, each holding the script of the view of the class it is named
+/// after, as written between `<` and `>`, like `views/Box.class` for `view(Box)`.
+pub struct ViewDir {
+    path: PathBuf,
+    /// The script of each view loaded.
+    script_mp: HashMap<String, String>,
+}
+
+impl ViewDir {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            script_mp: HashMap::new(),
+        }
+    }
+
+    /// called => the result = the view scripts in the directory, by class
+    fn scan(&self) -> err::Result<HashMap<String, String>> {
+        let entry_v = std::fs::read_dir(&self.path)
+            .change_context(err::Error::Other)
+            .attach_printable_lazy(|| format!("failed to read {}", self.path.display()))?;
+
+        let mut script_mp = HashMap::new();
+        for entry in entry_v {
+            let path = entry.change_context(err::Error::Other)?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("class") {
+                continue;
+            }
+            let class = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(class) => class.to_string(),
+                None => continue,
+            };
+
+            let script = std::fs::read_to_string(&path)
+                .change_context(err::Error::Other)
+                .attach_printable_lazy(|| format!("failed to read {}", path.display()))?;
+            script_mp.insert(class, script.trim().to_string());
+        }
+
+        Ok(script_mp)
+    }
+
+    /// Let the views added or changed since the last load be set in the data manager, and the
+    /// views whose files are gone be removed.
+    ///
+    /// called => the result = the classes whose views are set or removed, sorted
+    pub async fn load(&mut self, dm: &mut dyn AsClassManager) -> err::Result<Vec<String>> {
+        let script_mp = self.scan()?;
+
+        let mut class_v = script_mp
+            .iter()
+            .filter(|(class, script)| self.script_mp.get(*class) != Some(*script))
+            .map(|(class, _)| class.clone())
+            .chain(
+                self.script_mp
+                    .keys()
+                    .filter(|class| !script_mp.contains_key(*class))
+                    .cloned(),
+            )
+            .collect::<Vec<String>>();
+        class_v.sort();
+
+        for class in &class_v {
+            let old_v = dm
+                .get("view", class)
+                .await
+                .change_context(err::Error::Data)?;
+            if !old_v.is_empty() {
+                dm.remove("view", class, old_v)
+                    .await
+                    .change_context(err::Error::Data)?;
+            }
+
+            if let Some(script) = script_mp.get(class) {
+                dm.append("view", class, vec![script.clone()])
+                    .await
+                    .change_context(err::Error::Data)
+                    .attach_printable_lazy(|| format!("failed to set the view of {class}"))?;
+            }
+        }
+
+        self.script_mp = script_mp;
+
+        Ok(class_v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moon_class::AsClassManager;
+
+    use crate::dm::MemoryDataManager;
+
+    #[tokio::test]
+    async fn test_load() {
+        let path = std::env::temp_dir().join(format!("moon_world_views_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::write(path.join("Box.class"), "{$class: div} = $result();").unwrap();
+        std::fs::write(path.join("notes.txt"), "not a view").unwrap();
+
+        let mut dm = MemoryDataManager::new();
+        let mut view_dir = super::ViewDir::new(&path);
+
+        assert_eq!(view_dir.load(&mut dm).await.unwrap(), ["Box"]);
+        assert_eq!(
+            dm.get("view", "Box").await.unwrap(),
+            ["{$class: div} = $result();"]
+        );
+        assert!(view_dir.load(&mut dm).await.unwrap().is_empty());
+
+        std::fs::write(path.join("Box.class"), "{$class: span} = $result();").unwrap();
+        std::fs::write(path.join("Ball.class"), "{$class: div} = $result();").unwrap();
+        assert_eq!(view_dir.load(&mut dm).await.unwrap(), ["Ball", "Box"]);
+        assert_eq!(
+            dm.get("view", "Box").await.unwrap(),
+            ["{$class: span} = $result();"]
+        );
+
+        std::fs::remove_file(path.join("Ball.class")).unwrap();
+        assert_eq!(view_dir.load(&mut dm).await.unwrap(), ["Ball"]);
+        assert!(dm.get("view", "Ball").await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
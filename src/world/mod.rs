@@ -0,0 +1,239 @@
+//! Help the engines step the world and pass the events alike, the windowed one and the one of
+//! the simulation.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use view_manager::AsViewManager;
+
+use crate::{
+    err, tree,
+    util::{prop, random::Random},
+};
+
+/// The view tree of an engine and what it keeps about the elements applied to it.
+#[derive(Default)]
+pub struct World {
+    pub tree: tree::VNodeTree,
+    /// The props last applied to each element.
+    pub props_mp: HashMap<u64, json::JsonValue>,
+    /// The count of the steps simulated.
+    pub step_index: u64,
+    /// The generator of `@random`, locked as the gets only borrow the engine.
+    pub random: Mutex<Random>,
+    /// The items of the class data before the engine first changed them, by `(class, source)`,
+    /// for a snapshot to tell what to bring back, see [crate::snapshot].
+    pub origin_data_mp: HashMap<(String, String), Vec<String>>,
+    /// The vnodes skipped for malformed props, with the descriptions of what is wrong.
+    error_v: Vec<(u64, String)>,
+    /// Whether each event being propagated is stopped by a handler, the innermost last, as a
+    /// handler may propagate another event.
+    propagation_stop_v: Vec<bool>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let the vnode go without an element, and `$onerror` be propagated from it by the next
+    /// [propagate_error_v].
+    ///
+    /// called => the result = the vnode id
+    pub fn skip_element(&mut self, vnode_id: u64, e: error_stack::Report<err::Error>) -> u64 {
+        log::error!("failed to create the element of vnode {vnode_id}: {e:?}");
+
+        self.error_v.push((vnode_id, err::describe(&e)));
+
+        vnode_id
+    }
+
+    /// Let the props be the last applied to the element.
+    ///
+    /// called => the result = the keys changed since the props applied before, empty if the
+    /// props are the same
+    pub fn apply_props(&mut self, id: u64, props: &json::JsonValue) -> HashSet<String> {
+        let changed_key_set =
+            prop::changed_key_set(self.props_mp.get(&id).unwrap_or(&json::Null), props);
+        if !changed_key_set.is_empty() {
+            self.props_mp.insert(id, props.clone());
+        }
+
+        changed_key_set
+    }
+
+    /// called => the result = the vnode containing the vnode, either as its inner node or as an
+    /// embedded child
+    pub fn parent_of(&self, id: u64) -> Option<u64> {
+        self.tree
+            .vnode_mp
+            .iter()
+            .find(|(_, vnode)| vnode.inner_node.data == id || vnode.embeded_child_v.contains(&id))
+            .map(|(parent_id, _)| *parent_id)
+    }
+
+    /// Let the event being propagated go no further, see [propagate_from].
+    ///
+    /// called => the result = whether an event is being propagated
+    pub fn stop_propagation(&mut self) -> bool {
+        match self.propagation_stop_v.last_mut() {
+            Some(is_stopped) => {
+                *is_stopped = true;
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// called => the result = the next number of `@random`, in `[0, 1)`
+    pub fn random(&self) -> f64 {
+        self.random.lock().unwrap().next_f64()
+    }
+
+    /// Let `@random` draw the numbers of the seed from now on.
+    pub fn set_seed(&mut self, seed: u64) {
+        *self.random.get_mut().unwrap() = Random::new(seed);
+    }
+
+    /// called => the result = the data of the next `$onstep`, `$dt` and `$step`, the index of
+    /// the step
+    fn next_step_data(&mut self, dt: f32) -> json::JsonValue {
+        let step_data = json::object! { "$dt": dt, "$step": self.step_index };
+        self.step_index += 1;
+
+        step_data
+    }
+}
+
+/// An engine stepping a [World].
+pub trait AsWorld: AsViewManager {
+    fn world(&self) -> &World;
+
+    fn world_mut(&mut self) -> &mut World;
+
+    /// called => the result = the vnodes getting `$onstep`, like the dynamic bodies
+    fn stepped_id_v(&self) -> Vec<u64>;
+}
+
+/// Let the event be propagated between the root and each of the target vnodes in turn.
+///
+/// First the ancestors with a `<entry_name>_capture` prop get it from the root down, then the
+/// target and the ancestors with an `<entry_name>` prop get it from the target up. A vnode on the
+/// way of several targets gets it once. A handler appending to `$stop_propagation` keeps the rest
+/// from getting it, of every target. The data gets `$target`, the id of the target.
+///
+/// called => the result = whether any vnode handled the event
+pub async fn propagate_from<W: AsWorld>(
+    w: &mut W,
+    target_v: &[u64],
+    entry_name: &str,
+    data: &json::JsonValue,
+) -> bool {
+    let capture_name = format!("{entry_name}_capture");
+    let mut handled_set = HashSet::new();
+
+    w.world_mut().propagation_stop_v.push(false);
+    'target: for target in target_v {
+        let mut data = if data.is_null() {
+            json::object! {}
+        } else {
+            data.clone()
+        };
+        if data.is_object() {
+            data["$target"] = (*target).into();
+        }
+
+        let mut path = vec![*target];
+        while let Some(parent) = w.world().parent_of(*path.last().unwrap()) {
+            path.push(parent);
+        }
+
+        let handler_v = path
+            .iter()
+            .skip(1)
+            .rev()
+            .map(|id| (*id, capture_name.as_str()))
+            .chain(path.iter().map(|id| (*id, entry_name)))
+            .collect::<Vec<(u64, &str)>>();
+
+        for (id, name) in handler_v {
+            let has_handler = w
+                .world()
+                .tree
+                .vnode_mp
+                .get(&id)
+                .map(|vnode| !vnode.view_props.props[name].is_null())
+                .unwrap_or(false);
+            if !has_handler || !handled_set.insert((id, name)) {
+                continue;
+            }
+
+            if let Err(e) = w.event_entry(id, name, &data).await {
+                log::error!("failed to handle {name} of vnode {id}: {e:?}");
+            }
+
+            if w.world().propagation_stop_v.last() == Some(&true) {
+                break 'target;
+            }
+        }
+    }
+    w.world_mut().propagation_stop_v.pop();
+
+    !handled_set.is_empty()
+}
+
+/// Let the event be propagated between the root and the target vnode, see [propagate_from].
+///
+/// called => the result = whether any vnode handled the event
+pub async fn propagate<W: AsWorld>(
+    w: &mut W,
+    target: u64,
+    entry_name: &str,
+    data: &json::JsonValue,
+) -> bool {
+    propagate_from(w, &[target], entry_name, data).await
+}
+
+/// Let `$onerror` with `$message` be propagated from each vnode skipped since the last call.
+pub async fn propagate_error_v<W: AsWorld>(w: &mut W) {
+    for (id, message) in std::mem::take(&mut w.world_mut().error_v) {
+        propagate(w, id, "$onerror", &json::object! { "$message": message }).await;
+    }
+}
+
+/// Let `$onstep` be dispatched to the vnodes stepped, with `$dt`, the seconds the step stands
+/// for, and `$step`, the index of the step.
+pub async fn dispatch_step<W: AsWorld>(w: &mut W, dt: f32) {
+    let step_data = w.world_mut().next_step_data(dt);
+
+    for id in w.stepped_id_v() {
+        if let Err(e) = w.event_entry(id, "$onstep", &step_data).await {
+            log::error!("failed to handle $onstep of vnode {id}: {e:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_world() {
+        let mut world = super::World::new();
+
+        let props = json::object! { "$position": [0, 1, 0] };
+        assert!(world.apply_props(0, &props).contains("$position"));
+        assert!(world.apply_props(0, &props).is_empty());
+        assert_eq!(world.props_mp.get(&0), Some(&props));
+
+        // Nothing is being propagated.
+        assert!(!world.stop_propagation());
+        world.propagation_stop_v.push(false);
+        assert!(world.stop_propagation());
+        assert_eq!(world.propagation_stop_v, vec![true]);
+
+        assert_eq!(world.next_step_data(0.5)["$step"], 0);
+        assert_eq!(world.next_step_data(0.5)["$step"], 1);
+    }
+}